@@ -0,0 +1,91 @@
+/*
+ * identity_filter.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+// Measures a full traversal with an empty `Filter`, which rebuilds the whole
+// document without changing it: the baseline cost of running any filter.
+// Documents of two sizes are traversed, and the larger one must not be much
+// slower per node, which would point at quadratic behavior in the traversal.
+//
+// Run with `cargo bench --bench identity_filter`.
+
+use quarto_markdown_pandoc::filters::{Filter, topdown_traverse};
+use quarto_markdown_pandoc::pandoc::Pandoc;
+use quarto_markdown_pandoc::readers;
+use quarto_markdown_pandoc::utils::node_stats::count_nodes;
+use std::hint::black_box;
+use std::time::Instant;
+
+const ITERATIONS: usize = 20;
+
+const SECTION: &str = r#"## A section
+
+Some *emphasized* and **strong** text with a [link](https://example.com),
+`code`, $x^2$ and a note.^[With *inline* content.]
+
+- a list item
+- another one with [a span]{.mark}
+  - and a nested item
+
+> A quote with a paragraph
+> spanning two lines.
+
+| a | b |
+|---|---|
+| 1 | 2 |
+
+::: {.callout-note}
+A div with a paragraph.
+:::
+
+"#;
+
+fn read(sections: usize) -> Pandoc {
+    let input = SECTION.repeat(sections);
+    readers::qmd::read(input.as_bytes(), &mut std::io::sink()).unwrap()
+}
+
+// returns the nodes traversed per second
+fn bench(name: &str, doc: &Pandoc) -> f64 {
+    let stats = count_nodes(doc);
+    let nodes = stats.total_blocks() + stats.total_inlines();
+
+    // the filter consumes the document, so the copies are made up front
+    let copies: Vec<Pandoc> = (0..ITERATIONS).map(|_| doc.clone()).collect();
+    let start = Instant::now();
+    for copy in copies {
+        black_box(topdown_traverse(black_box(copy), &mut Filter::new()));
+    }
+    let elapsed = start.elapsed();
+
+    let nodes_per_second = (nodes * ITERATIONS) as f64 / elapsed.as_secs_f64();
+    println!(
+        "{:>6}: {:>8} nodes, {:>10.2?} per traversal, {:>12.0} nodes/s",
+        name,
+        nodes,
+        elapsed / ITERATIONS as u32,
+        nodes_per_second
+    );
+    nodes_per_second
+}
+
+fn main() {
+    let small = read(100);
+    let large = read(800);
+
+    // an empty filter gives back the document it was given
+    assert!(topdown_traverse(small.clone(), &mut Filter::new()) == small);
+
+    let small_rate = bench("small", &small);
+    let large_rate = bench("large", &large);
+
+    // linear traversal keeps the rate about the same for 8x the nodes;
+    // quadratic traversal would divide it by 8
+    assert!(
+        large_rate > small_rate / 3.0,
+        "traversal slowed from {:.0} to {:.0} nodes/s on a larger document",
+        small_rate,
+        large_rate
+    );
+}
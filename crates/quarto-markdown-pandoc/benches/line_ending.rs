@@ -0,0 +1,72 @@
+/*
+ * line_ending.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+// Measures `line_ending::normalize` on LF, CRLF and CR input, and checks that
+// LF input (the fast path) doesn't allocate.
+//
+// Run with `cargo bench --bench line_ending`.
+
+use quarto_markdown_pandoc::utils::line_ending::normalize;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ITERATIONS: usize = 200;
+
+fn bench(name: &str, input: &str) -> usize {
+    // warm up
+    black_box(normalize(black_box(input)));
+
+    let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(normalize(black_box(input)));
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+
+    let megabytes = (input.len() * ITERATIONS) as f64 / (1024.0 * 1024.0);
+    println!(
+        "{:>5}: {:>10.2?} per call, {:>8.1} MB/s, {} allocations in {} calls",
+        name,
+        elapsed / ITERATIONS as u32,
+        megabytes / elapsed.as_secs_f64(),
+        allocations,
+        ITERATIONS
+    );
+    allocations
+}
+
+fn main() {
+    let line = "Some *markdown* text with a [link](https://example.com) in it.";
+    let lf = format!("{}\n", line).repeat(20_000);
+    let crlf = format!("{}\r\n", line).repeat(20_000);
+    let cr = format!("{}\r", line).repeat(20_000);
+
+    let lf_allocations = bench("lf", &lf);
+    bench("crlf", &crlf);
+    bench("cr", &cr);
+
+    assert_eq!(lf_allocations, 0, "normalize allocated on LF-only input");
+}
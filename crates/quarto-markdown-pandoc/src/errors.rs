@@ -17,81 +17,147 @@
 // this solution is that cursor cloning requires walking the tree
 // and can take O(n) time.
 
+use crate::pandoc::location::{Range, node_location};
+use crate::traversals::topdown_traverse_concrete_tree;
+use crate::utils::diagnostics::Diagnostic;
 use tree_sitter_qmd::MarkdownTree;
 
-enum TreeSitterError {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeSitterError {
     MissingNode,
     UnexpectedNode,
 }
 
-fn node_can_have_empty_text<'a>(cursor: &tree_sitter_qmd::MarkdownCursor<'a>) -> bool {
-    match cursor.node().kind() {
-        "block_continuation" => true,
-        _ => false,
-    }
+fn node_can_have_empty_text(node: &tree_sitter::Node) -> bool {
+    matches!(node.kind(), "block_continuation")
 }
 
-fn is_error_node<'a>(cursor: &tree_sitter_qmd::MarkdownCursor<'a>) -> Option<TreeSitterError> {
-    if cursor.node().kind() == "ERROR" {
+fn is_error_node(node: &tree_sitter::Node) -> Option<TreeSitterError> {
+    if node.kind() == "ERROR" {
         return Some(TreeSitterError::UnexpectedNode);
     }
-    let byte_range = cursor.node().byte_range();
-    if byte_range.start == byte_range.end && !node_can_have_empty_text(cursor) {
+    let byte_range = node.byte_range();
+    if byte_range.start == byte_range.end && !node_can_have_empty_text(node) {
         return Some(TreeSitterError::MissingNode); // empty node, indicates that tree-sitter inserted a "missing" node?
     }
     return None;
 }
 
-fn accumulate_error_nodes<'a>(
-    cursor: &mut tree_sitter_qmd::MarkdownCursor<'a>,
-    errors: &mut Vec<(bool, usize)>,
-) {
-    if let Some(_) = is_error_node(cursor) {
-        errors.push(cursor.id());
-        return;
-    }
-    if cursor.goto_first_child() {
-        loop {
-            accumulate_error_nodes(cursor, errors);
-            if !cursor.goto_next_sibling() {
-                break;
-            }
-        }
-        cursor.goto_parent();
-    }
+// An ERROR or MISSING node found in the concrete tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorNode {
+    pub error: TreeSitterError,
+    pub kind: String,
+    pub range: Range,
+    // the text covered by the node (empty for missing nodes)
+    pub text: String,
+    // the full source line on which the node starts
+    pub snippet: String,
 }
 
-pub fn parse_is_good<'a>(tree: &'a MarkdownTree) -> Vec<(bool, usize)> {
-    let mut errors = Vec::new();
-    let mut cursor = tree.walk();
-    accumulate_error_nodes(&mut cursor, &mut errors);
-    errors
+fn source_line(input_bytes: &[u8], offset: usize) -> String {
+    let offset = offset.min(input_bytes.len());
+    let start = input_bytes[..offset]
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+    let end = input_bytes[offset..]
+        .iter()
+        .position(|b| *b == b'\n')
+        .map_or(input_bytes.len(), |i| offset + i);
+    String::from_utf8_lossy(&input_bytes[start..end]).to_string()
 }
 
-pub fn error_message(error: &mut tree_sitter_qmd::MarkdownCursor, input_bytes: &[u8]) -> String {
-    // assert!(error.goto_parent());
-    // assert!(error.goto_first_child());
+// Collects every ERROR and MISSING node in the tree, in document order.
+// Children of error nodes are not visited.
+pub fn collect_error_nodes(tree: &MarkdownTree, input_bytes: &[u8]) -> Vec<ErrorNode> {
+    let mut result = Vec::new();
+    topdown_traverse_concrete_tree(&mut tree.walk(), &mut |node, phase| {
+        if phase != crate::traversals::TraversePhase::Enter {
+            return true;
+        }
+        let Some(error) = is_error_node(node) else {
+            return true;
+        };
+        let range = node_location(node);
+        result.push(ErrorNode {
+            error,
+            kind: node.kind().to_string(),
+            text: node.utf8_text(input_bytes).unwrap_or("").to_string(),
+            snippet: source_line(input_bytes, range.start.offset),
+            range,
+        });
+        false
+    });
+    result
+}
 
-    if let Some(which_error) = is_error_node(&error) {
-        match which_error {
-            TreeSitterError::MissingNode => {
-                return format!(
-                    "Error: Missing {} at {}:{}",
-                    error.node().kind(),
-                    error.node().start_position().row,
-                    error.node().start_position().column,
-                );
-            }
-            TreeSitterError::UnexpectedNode => {
-                return format!(
-                    "Error: Unexpected {} at {}:{}",
-                    error.node().utf8_text(input_bytes).unwrap_or(""),
-                    error.node().start_position().row,
-                    error.node().start_position().column,
-                );
+// Quotes that tree-sitter closes with a MISSING delimiter at the end of
+// the block, with the name and opening-quote label for the diagnostic.
+fn unclosed_quote(kind: &str) -> Option<(&'static str, &'static str)> {
+    match kind {
+        "single_quoted_span_delimiter" => Some((
+            "Single",
+            "This is the opening quote. If you need an apostrophe, escape it with a backslash.",
+        )),
+        "double_quoted_span_delimiter" => Some((
+            "Double",
+            "This is the opening quote. If you need a literal quote, escape it with a backslash.",
+        )),
+        _ => None,
+    }
+}
+
+// Builds the diagnostic for an ERROR or MISSING node. Specific messages
+// for known situations go here; everything else gets a generic one.
+fn error_node_diagnostic(
+    node: &tree_sitter::Node,
+    error: TreeSitterError,
+    input_bytes: &[u8],
+) -> Diagnostic {
+    let range = node_location(node);
+    match error {
+        TreeSitterError::MissingNode => {
+            let opening = node.parent().and_then(|parent| parent.child(0));
+            match (unclosed_quote(node.kind()), opening) {
+                (Some((name, hint)), Some(opening)) if opening.id() != node.id() => {
+                    Diagnostic::new(
+                        format!("Unclosed {} Quote", name),
+                        node_location(&opening),
+                        hint,
+                    )
+                    .with_secondary(range, "This is where the block ended.")
+                }
+                _ => Diagnostic::new(
+                    format!("Missing {}", node.kind()),
+                    range,
+                    format!("Expected {} here.", node.kind()),
+                ),
             }
         }
+        TreeSitterError::UnexpectedNode => {
+            let text = node.utf8_text(input_bytes).unwrap_or("");
+            Diagnostic::new(
+                format!("Unexpected {}", text),
+                range,
+                "The parser didn't expect this here.",
+            )
+        }
     }
-    assert!(false, "No error message available for this node");
-    return String::new(); // unreachable
+}
+
+// Like `collect_error_nodes`, but with the diagnostics to show for them.
+pub fn collect_diagnostics(tree: &MarkdownTree, input_bytes: &[u8]) -> Vec<Diagnostic> {
+    let mut result = Vec::new();
+    topdown_traverse_concrete_tree(&mut tree.walk(), &mut |node, phase| {
+        if phase != crate::traversals::TraversePhase::Enter {
+            return true;
+        }
+        let Some(error) = is_error_node(node) else {
+            return true;
+        };
+        result.push(error_node_diagnostic(node, error, input_bytes));
+        false
+    });
+    result
 }
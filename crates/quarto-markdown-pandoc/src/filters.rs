@@ -6,6 +6,7 @@
 use crate::pandoc::MetaValue;
 use crate::pandoc::block::MetaBlock;
 use crate::pandoc::inline::AsInline;
+use crate::pandoc::location::Range;
 use crate::pandoc::meta::Meta;
 use crate::pandoc::{self, Block, Blocks, Inline, Inlines};
 
@@ -52,7 +53,7 @@ pub struct Filter<'a> {
     pub span: InlineFilterField<'a, pandoc::Span>,
     pub shortcode: InlineFilterField<'a, pandoc::Shortcode>,
     pub note_reference: InlineFilterField<'a, pandoc::NoteReference>,
-    pub attr: InlineFilterField<'a, pandoc::Attr>,
+    pub attr: InlineFilterField<'a, (pandoc::Attr, Range)>,
 
     pub paragraph: BlockFilterField<'a, pandoc::Paragraph>,
     pub plain: BlockFilterField<'a, pandoc::Plain>,
@@ -147,6 +148,23 @@ impl<'a> Filter<'a> {
         self
     }
 
+    // called for each inline (or block) that has no filter for its variant
+    pub fn with_inline<F>(mut self, f: F) -> Filter<'a>
+    where
+        F: FnMut(Inline) -> FilterReturn<Inline, Inlines> + 'a,
+    {
+        self.inline = Some(Box::new(f));
+        self
+    }
+
+    pub fn with_block<F>(mut self, f: F) -> Filter<'a>
+    where
+        F: FnMut(Block) -> FilterReturn<Block, Blocks> + 'a,
+    {
+        self.block = Some(Box::new(f));
+        self
+    }
+
     pub fn with_meta<F>(mut self, f: F) -> Filter<'a>
     where
         F: FnMut(Meta) -> FilterReturn<Meta, Meta> + 'a,
@@ -154,6 +172,15 @@ impl<'a> Filter<'a> {
         self.meta = Some(Box::new(f));
         self
     }
+
+    // attributes come with their source range
+    pub fn with_attr<F>(mut self, f: F) -> Filter<'a>
+    where
+        F: FnMut((pandoc::Attr, Range)) -> FilterReturn<(pandoc::Attr, Range), Inlines> + 'a,
+    {
+        self.attr = Some(Box::new(f));
+        self
+    }
 }
 
 macro_rules! define_filter_with_methods {
@@ -199,7 +226,6 @@ define_filter_with_methods!(
     span,
     shortcode,
     note_reference,
-    attr,
 );
 
 define_filter_with_methods!(
@@ -229,7 +255,7 @@ macro_rules! handle_inline_filter {
         } else if let Some(f) = &mut $filter.inline {
             return inlines_apply_and_maybe_recurse!($value.as_inline(), f, $filter);
         } else {
-            vec![traverse_inline_structure(Inline::$variant($value), $filter)]
+            vec![traverse_inline_structure($value.as_inline(), $filter)]
         }
     };
 }
@@ -270,10 +296,15 @@ impl_inline_filterable_terminal!(
     Math,
     RawInline,
     Shortcode,
-    NoteReference,
-    Attr
+    NoteReference
 );
 
+impl InlineFilterableStructure for (pandoc::Attr, Range) {
+    fn filter_structure(self, _: &mut Filter) -> Inline {
+        self.as_inline()
+    }
+}
+
 macro_rules! impl_inline_filterable_simple {
     ($($variant:ident),*) => {
         $(
@@ -614,7 +645,8 @@ pub fn topdown_traverse_inline(inline: Inline, filter: &mut Filter) -> Inlines {
         Inline::NoteReference(note_ref) => {
             handle_inline_filter!(NoteReference, note_ref, note_reference, filter)
         }
-        Inline::Attr(attr) => {
+        Inline::Attr(attr, range) => {
+            let attr = (attr, range);
             handle_inline_filter!(Attr, attr, attr, filter)
         }
     }
@@ -793,7 +825,7 @@ pub fn traverse_inline_structure(inline: Inline, filter: &mut Filter) -> Inline
         // extensions
         Inline::Shortcode(_) => inline,
         Inline::NoteReference(_) => inline,
-        Inline::Attr(_) => inline,
+        Inline::Attr(..) => inline,
         _ => traverse_inline_nonterminal(inline, filter),
     }
 }
@@ -1002,3 +1034,13 @@ pub fn topdown_traverse(doc: pandoc::Pandoc, filter: &mut Filter) -> pandoc::Pan
         blocks: topdown_traverse_blocks(doc.blocks, filter),
     }
 }
+
+// Runs `filters` one after the other, each on the output of the previous one.
+// This is a separate traversal per filter; filters that need to see each
+// other's results (e.g. numbering sections after ids are assigned) can be
+// chained this way without re-reading the document.
+pub fn run_filters(doc: pandoc::Pandoc, filters: &mut [Filter]) -> pandoc::Pandoc {
+    filters
+        .iter_mut()
+        .fold(doc, |doc, filter| topdown_traverse(doc, filter))
+}
@@ -9,6 +9,16 @@ pub mod errors;
 pub mod filters;
 pub mod pandoc;
 pub mod readers;
+pub mod transforms;
 pub mod traversals;
 pub mod utils;
+pub mod visitors;
 pub mod writers;
+
+// the reader goes through `treesitter_to_pandoc_with_options`; this is the
+// entry point for callers that parse the input themselves
+pub use pandoc::treesitter::treesitter_to_pandoc;
+
+// the reader accepts any line ending; callers that write the document back
+// out can use these to match the input
+pub use utils::line_ending::{LineEnding, infer_line_ending};
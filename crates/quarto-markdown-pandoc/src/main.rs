@@ -6,14 +6,16 @@
  */
 
 use clap::Parser;
-use std::io::{self, Read, Write};
+use std::io::{self, IsTerminal, Read, Write};
 
 mod errors;
 mod filters;
 mod pandoc;
 mod readers;
+mod transforms;
 mod traversals;
 mod utils;
+mod visitors;
 mod writers;
 use utils::output::VerboseOutput;
 
@@ -21,6 +23,14 @@ use utils::output::VerboseOutput;
 #[command(name = "quarto-markdown-pandoc")]
 #[command(about = "Convert Quarto markdown to various output formats")]
 struct Args {
+    #[arg(
+        short = 'f',
+        long = "from",
+        default_value = "qmd",
+        value_parser = ["qmd", "commonmark", "native"]
+    )]
+    from: String,
+
     #[arg(short = 't', long = "to", default_value = "native")]
     to: String,
 
@@ -29,6 +39,84 @@ struct Args {
 
     #[arg(short = 'i', long = "input", default_value = "-")]
     input: String,
+
+    #[arg(long = "dump-intermediate")]
+    dump_intermediate: bool,
+
+    // write only the document metadata (use with `-t yaml`)
+    #[arg(long = "extract-meta")]
+    extract_meta: bool,
+
+    // keep the front matter in the document as a raw block, for debugging
+    #[arg(long = "keep-meta-raw")]
+    keep_meta_raw: bool,
+
+    // keep straight quotes, `--`, `---` and `...` as they are written
+    #[arg(long = "no-smart")]
+    no_smart: bool,
+
+    // report the shape of each metadata key instead of writing the document
+    #[arg(long = "dump-meta-schema", conflicts_with = "extract_meta")]
+    dump_meta_schema: bool,
+
+    // print the document as an outline with the source range of each node,
+    // for checking source mapping
+    #[arg(long = "dump-ranges", conflicts_with_all = ["extract_meta", "dump_meta_schema"])]
+    dump_ranges: bool,
+
+    // transcode the input from this encoding (`latin1`, `windows-1252`, ...)
+    // instead of requiring UTF-8
+    #[arg(long = "input-encoding", value_name = "LABEL")]
+    input_encoding: Option<String>,
+
+    // print the pandoc-types API version of the JSON output and exit
+    #[arg(long = "pandoc-api-version")]
+    pandoc_api_version: bool,
+
+    // the Pandoc release the json and native output is for, 2.11 or later;
+    // it sets the API version, and before 3.0, figures are written as
+    // implicit figures
+    #[arg(long = "pandoc-version", value_name = "VERSION")]
+    pandoc_version: Option<String>,
+
+    #[arg(long = "id-prefix")]
+    id_prefix: Option<String>,
+
+    // expand tabs in code blocks to spaces in the markdown writer, with tab
+    // stops every N columns; tabs are written as they are by default
+    #[arg(long = "expand-tabs", value_name = "N")]
+    expand_tabs: Option<usize>,
+
+    // wrap grid table cells at this many columns in the markdown writer
+    #[arg(long = "table-column-width", value_name = "N")]
+    table_column_width: Option<usize>,
+
+    // write non-ASCII characters as character references (markdown) or
+    // escapes (json, native); markdown can't escape them in code
+    #[arg(long = "ascii")]
+    ascii: bool,
+
+    // write soft breaks as spaces in the markdown writer
+    #[arg(long = "soft-break-as-space")]
+    soft_break_as_space: bool,
+
+    // fail on content the reader didn't understand, instead of keeping it as
+    // plain text with a warning
+    #[arg(long = "strict")]
+    strict: bool,
+
+    // resolve tracked changes (`insertion`/`deletion` and comment spans,
+    // which is what CriticMarkup is read into)
+    #[arg(
+        long = "track-changes",
+        value_name = "MODE",
+        value_parser = ["accept", "reject", "all"]
+    )]
+    track_changes: Option<String>,
+
+    // join consecutive raw blocks of the same format into one
+    #[arg(long = "merge-raw-blocks")]
+    merge_raw_blocks: bool,
 }
 
 fn print_whole_tree<T: Write>(cursor: &mut tree_sitter_qmd::MarkdownCursor, buf: &mut T) {
@@ -46,8 +134,32 @@ fn print_whole_tree<T: Write>(cursor: &mut tree_sitter_qmd::MarkdownCursor, buf:
 
 fn main() {
     let args = Args::parse();
+    let pandoc_version = match &args.pandoc_version {
+        None => None,
+        Some(version) => match pandoc::parse_pandoc_version(version) {
+            Some(parsed) if pandoc::pandoc_api_version_for(parsed).is_some() => Some(parsed),
+            Some(_) => {
+                eprintln!(
+                    "Unsupported Pandoc version: {} (the oldest supported release is 2.11)",
+                    version
+                );
+                std::process::exit(1);
+            }
+            None => {
+                eprintln!("Invalid Pandoc version: {}", version);
+                std::process::exit(1);
+            }
+        },
+    };
+    if args.pandoc_api_version {
+        let version = pandoc_version
+            .and_then(pandoc::pandoc_api_version_for)
+            .unwrap_or_else(pandoc::pandoc_api_version);
+        println!("{}.{}.{}", version[0], version[1], version[2]);
+        return;
+    }
 
-    let mut input = String::new();
+    let mut input_bytes = Vec::new();
     let mut output_stream = if args.verbose {
         VerboseOutput::Stderr(io::stderr())
     } else {
@@ -56,43 +168,165 @@ fn main() {
     if args.input == "-" {
         // Read from stdin
         io::stdin()
-            .read_to_string(&mut input)
+            .read_to_end(&mut input_bytes)
             .expect("Failed to read from stdin");
     } else {
         // Read from file
         std::fs::File::open(&args.input)
             .expect("Failed to open input file")
-            .read_to_string(&mut input)
+            .read_to_end(&mut input_bytes)
             .expect("Failed to read input file");
     }
+    let mut input = match utils::encoding::decode_input(input_bytes, args.input_encoding.as_deref())
+    {
+        Ok(input) => input,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            std::process::exit(1);
+        }
+    };
 
-    if !input.ends_with("\n") {
+    if !input.ends_with("\n") && !input.trim().is_empty() {
         eprintln!("(Warning) Adding missing newline to end of input.");
         //
         input.push('\n'); // ensure the input ends with a newline
     }
 
-    let result = readers::qmd::read(input.as_bytes(), &mut output_stream);
-    let pandoc = match result {
-        Ok(p) => p,
-        Err(error_messages) => {
-            for msg in error_messages {
-                eprintln!("{}", msg);
+    let reader_options = readers::qmd::MarkdownParserOptions {
+        commonmark_only: args.from == "commonmark",
+        keep_meta_raw: args.keep_meta_raw,
+        no_smart: args.no_smart,
+        ..Default::default()
+    };
+
+    if args.dump_intermediate {
+        let dump = readers::qmd::dump_intermediate(&input, &reader_options);
+        println!("{}", serde_json::to_string_pretty(&dump).unwrap());
+        return;
+    }
+    // diagnostics are shown with the source they point at; the reader
+    // normalizes line endings, and their ranges are relative to that
+    let filename = if args.input == "-" {
+        "<stdin>"
+    } else {
+        &args.input
+    };
+    let normalized_input = utils::line_ending::normalize(&input);
+    let source = utils::source::Source::new(&normalized_input);
+    let color = io::stderr().is_terminal();
+    let report = |diagnostics: &[utils::diagnostics::Diagnostic]| {
+        for diagnostic in diagnostics {
+            eprint!("{}", diagnostic.render(filename, &source, color));
+        }
+    };
+    let mut pandoc = if args.from == "native" {
+        match readers::native::read(input.as_bytes()) {
+            Ok(p) => p,
+            Err(error_messages) => {
+                for msg in error_messages {
+                    eprintln!("{}", msg);
+                }
+                std::process::exit(1);
+            }
+        }
+    } else {
+        match readers::qmd::read_with_options(input.as_bytes(), &reader_options, &mut output_stream)
+        {
+            Ok((p, warnings)) => {
+                report(&warnings);
+                p
             }
+            Err(diagnostics) => {
+                report(&diagnostics);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let leftover_options = transforms::leftovers::LeftoverOptions {
+        strict: args.strict,
+    };
+    pandoc = match transforms::leftovers::strip_leftovers(pandoc, &leftover_options) {
+        Ok((p, warnings)) => {
+            report(&warnings);
+            p
+        }
+        Err(diagnostics) => {
+            report(&diagnostics);
             std::process::exit(1);
         }
     };
 
+    if let Some(prefix) = &args.id_prefix {
+        pandoc = transforms::id_prefix::prefix_ids(pandoc, prefix);
+    }
+
+    if let Some(mode) = &args.track_changes {
+        let mode = transforms::track_changes::TrackChanges::from_name(mode)
+            .expect("clap only accepts known modes");
+        pandoc = transforms::track_changes::apply_track_changes(pandoc, mode);
+    }
+
+    if args.merge_raw_blocks {
+        pandoc = transforms::merge_raw_blocks::merge_raw_blocks(pandoc);
+    }
+
     let mut buf = Vec::new();
-    match args.to.as_str() {
-        "json" => writers::json::write(&pandoc, &mut buf),
-        "native" => writers::native::write(&pandoc, &mut buf),
+    if args.dump_meta_schema {
+        utils::meta_schema::write_meta_schema(&pandoc.meta, &mut buf).unwrap();
+        let output = String::from_utf8(buf).expect("Invalid UTF-8 in output");
+        print!("{}", output);
+        return;
+    }
+    if args.dump_ranges {
+        utils::range_tree::write_range_tree(&pandoc, &mut buf).unwrap();
+        let output = String::from_utf8(buf).expect("Invalid UTF-8 in output");
+        print!("{}", output);
+        return;
+    }
+    if args.extract_meta {
+        if args.to != "yaml" {
+            eprintln!("--extract-meta only supports yaml output, got {}", args.to);
+            std::process::exit(1);
+        }
+        writers::yaml::write_meta(&pandoc.meta, &mut buf).unwrap();
+        let output = String::from_utf8(buf).expect("Invalid UTF-8 in output");
+        print!("{}", output);
+        return;
+    }
+    let result = match args.to.as_str() {
+        "json" => {
+            let options = writers::json::JsonWriterOptions {
+                ascii: args.ascii,
+                pandoc_version,
+            };
+            writers::json::write_with_options(&pandoc, &options, &mut buf)
+        }
+        "markdown" | "qmd" => {
+            let options = writers::markdown::MarkdownWriterOptions {
+                expand_tabs: args.expand_tabs,
+                max_table_column_width: args.table_column_width,
+                ascii: args.ascii,
+                soft_break_as_space: args.soft_break_as_space,
+            };
+            writers::markdown::write_with_options(&pandoc, &options, &mut buf)
+        }
+        "native" => {
+            let options = writers::native::NativeWriterOptions {
+                ascii: args.ascii,
+                pandoc_version,
+            };
+            writers::native::write_with_options(&pandoc, &options, &mut buf)
+        }
         _ => {
             eprintln!("Unknown output format: {}", args.to);
             return;
         }
+    };
+    if let Err(error) = result {
+        eprintln!("Error: {}", error);
+        std::process::exit(1);
     }
-    .unwrap();
     let output = String::from_utf8(buf).expect("Invalid UTF-8 in output");
     println!("{}", output);
 }
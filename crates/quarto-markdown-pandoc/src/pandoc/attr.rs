@@ -3,14 +3,33 @@
  * Copyright (c) 2025 Posit, PBC
  */
 
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 pub fn empty_attr() -> Attr {
-    ("".to_string(), vec![], HashMap::new())
+    ("".to_string(), vec![], IndexMap::new())
 }
 
-pub type Attr = (String, Vec<String>, HashMap<String, String>);
+pub type Attr = (String, Vec<String>, IndexMap<String, String>);
 
+// An attr is empty when it has no id, and no classes or key-value pairs
+// other than empty ones. Empty classes and keys carry no information, so
+// `canonicalize_attr` drops them.
 pub fn is_empty_attr(attr: &Attr) -> bool {
-    attr.0.is_empty() && attr.1.is_empty() && attr.2.is_empty()
+    attr.0.is_empty() && attr.1.iter().all(String::is_empty) && attr.2.keys().all(String::is_empty)
+}
+
+// The classes and key-value pairs that writers write: empty classes and
+// keys are written as if they weren't there, as if the attr had been
+// canonicalized first.
+pub fn attr_classes(attr: &Attr) -> impl Iterator<Item = &String> {
+    attr.1.iter().filter(|class| !class.is_empty())
+}
+
+pub fn attr_key_values(attr: &Attr) -> impl Iterator<Item = (&String, &String)> {
+    attr.2.iter().filter(|(key, _)| !key.is_empty())
+}
+
+pub fn canonicalize_attr(attr: &mut Attr) {
+    attr.1.retain(|class| !class.is_empty());
+    attr.2.retain(|key, _| !key.is_empty());
 }
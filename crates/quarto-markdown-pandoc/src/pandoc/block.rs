@@ -9,9 +9,9 @@ use crate::pandoc::attr::Attr;
 use crate::pandoc::caption::Caption;
 use crate::pandoc::inline::Inlines;
 use crate::pandoc::list::ListAttributes;
-use crate::pandoc::location::Range;
 use crate::pandoc::location::SourceLocation;
 use crate::pandoc::location::node_location;
+use crate::pandoc::location::{Range, empty_range};
 use crate::pandoc::table::Table;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -174,6 +174,47 @@ impl_source_location!(
     MetaBlock
 );
 
+macro_rules! impl_from_for_block {
+    ($($variant:ident($type:ident)),*) => {
+        $(
+            impl From<$type> for Block {
+                fn from(block: $type) -> Block {
+                    Block::$variant(block)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_for_block!(
+    Plain(Plain),
+    Paragraph(Paragraph),
+    LineBlock(LineBlock),
+    CodeBlock(CodeBlock),
+    RawBlock(RawBlock),
+    BlockQuote(BlockQuote),
+    OrderedList(OrderedList),
+    BulletList(BulletList),
+    DefinitionList(DefinitionList),
+    Header(Header),
+    HorizontalRule(HorizontalRule),
+    Table(Table),
+    Figure(Figure),
+    Div(Div),
+    BlockMetadata(MetaBlock)
+);
+
+// a Paragraph without a source location
+impl From<Inlines> for Block {
+    fn from(content: Inlines) -> Block {
+        Block::Paragraph(Paragraph {
+            content,
+            filename: None,
+            range: empty_range(),
+        })
+    }
+}
+
 fn make_block_leftover(node: &tree_sitter::Node, input_bytes: &[u8]) -> Block {
     let text = node.utf8_text(input_bytes).unwrap().to_string();
     Block::RawBlock(RawBlock {
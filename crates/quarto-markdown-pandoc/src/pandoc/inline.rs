@@ -6,8 +6,8 @@
 use crate::impl_source_location;
 use crate::pandoc::attr::{Attr, is_empty_attr};
 use crate::pandoc::block::Blocks;
-use crate::pandoc::location::Range;
 use crate::pandoc::location::SourceLocation;
+use crate::pandoc::location::{Range, empty_range};
 use crate::pandoc::shortcode::Shortcode;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -38,8 +38,9 @@ pub enum Inline {
     Shortcode(Shortcode),
     NoteReference(NoteReference),
     // this is used to represent commonmark attributes in the document in places
-    // where they are not directly attached to a block, like in headings and tables
-    Attr(Attr),
+    // where they are not directly attached to a block, like in headings and tables.
+    // The range is where they are in the source.
+    Attr(Attr, Range),
 }
 
 pub type Inlines = Vec<Inline>;
@@ -61,6 +62,8 @@ pub enum MathType {
 #[derive(Debug, Clone, PartialEq)]
 pub struct Str {
     pub text: String,
+    pub filename: Option<String>,
+    pub range: Range,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -107,6 +110,9 @@ pub struct Quoted {
 #[derive(Debug, Clone, PartialEq)]
 pub struct Cite {
     pub citations: Vec<Citation>,
+    // What to show when the citations aren't resolved: the source text,
+    // as in Pandoc (`@foo`, `[@foo, p. 3]`). Citation processing replaces
+    // it. The markdown writer ignores it and writes the citations.
     pub content: Inlines,
 }
 
@@ -194,7 +200,7 @@ pub enum CitationMode {
     NormalCitation,
 }
 
-impl_source_location!(Space, LineBreak, SoftBreak);
+impl_source_location!(Str, Space, LineBreak, SoftBreak);
 
 pub trait AsInline {
     fn as_inline(self) -> Inline;
@@ -240,10 +246,72 @@ impl_as_inline!(
     Note,
     Span,
     Shortcode,
-    NoteReference,
-    Attr
+    NoteReference
 );
 
+impl AsInline for (Attr, Range) {
+    fn as_inline(self) -> Inline {
+        Inline::Attr(self.0, self.1)
+    }
+}
+
+// `From` conversions for constructing inlines by hand, e.g. in tests:
+// `Inline::from("word")` or `Str { .. }.into()`. `Attr` is left out,
+// since a bare tuple converting to an inline would be surprising.
+macro_rules! impl_from_for_inline {
+    ($($type:ident),*) => {
+        $(
+            impl From<$type> for Inline {
+                fn from(inline: $type) -> Inline {
+                    Inline::$type(inline)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_for_inline!(
+    Str,
+    Emph,
+    Underline,
+    Strong,
+    Strikeout,
+    Superscript,
+    Subscript,
+    SmallCaps,
+    Quoted,
+    Cite,
+    Code,
+    Space,
+    SoftBreak,
+    LineBreak,
+    Math,
+    RawInline,
+    Link,
+    Image,
+    Note,
+    Span,
+    Shortcode,
+    NoteReference
+);
+
+// a Str without a source location
+impl From<&str> for Inline {
+    fn from(text: &str) -> Inline {
+        Inline::from(text.to_string())
+    }
+}
+
+impl From<String> for Inline {
+    fn from(text: String) -> Inline {
+        Inline::Str(Str {
+            text,
+            filename: None,
+            range: empty_range(),
+        })
+    }
+}
+
 pub fn is_empty_target(target: &Target) -> bool {
     target.0.is_empty() && target.1.is_empty()
 }
@@ -306,7 +374,7 @@ pub fn make_cite_inline(attr: Attr, target: Target, content: Inlines) -> Inline
     // before deciding to destructively create a Cite
 
     let is_semicolon = |inline: &Inline| match &inline {
-        Inline::Str(Str { text }) => text == ";",
+        Inline::Str(Str { text, .. }) => text == ";",
         _ => false,
     };
 
@@ -322,6 +390,17 @@ pub fn make_cite_inline(attr: Attr, target: Target, content: Inlines) -> Inline
         return make_span_inline(attr, target, content);
     }
 
+    // the fallback content is the source text, with each citation's own
+    // `@id` in place of its Cite
+    let mut fallback: Inlines = vec![Inline::from("[")];
+    for inline in &content {
+        match inline {
+            Inline::Cite(c) => fallback.extend(c.content.iter().cloned()),
+            _ => fallback.push(inline.clone()),
+        }
+    }
+    fallback.push(Inline::from("]"));
+
     // we can now destructively create a Cite inline
     // from the content.
 
@@ -368,7 +447,7 @@ pub fn make_cite_inline(attr: Attr, target: Target, content: Inlines) -> Inline
         .collect();
     return Inline::Cite(Cite {
         citations,
-        content: vec![],
+        content: fallback,
     });
 }
 
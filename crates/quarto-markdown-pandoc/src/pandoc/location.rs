@@ -56,6 +56,25 @@ pub fn empty_range() -> Range {
     }
 }
 
+pub fn is_empty_range(range: &Range) -> bool {
+    *range == empty_range()
+}
+
+// The smallest range covering both `first` and `second`, assuming `first`
+// comes before `second` in the source. Empty (synthesized) ranges are ignored.
+pub fn merge_ranges(first: &Range, second: &Range) -> Range {
+    if is_empty_range(first) {
+        return second.clone();
+    }
+    if is_empty_range(second) {
+        return first.clone();
+    }
+    Range {
+        start: first.start.clone(),
+        end: second.end.clone(),
+    }
+}
+
 #[macro_export]
 macro_rules! impl_source_location {
     ($($type:ty),*) => {
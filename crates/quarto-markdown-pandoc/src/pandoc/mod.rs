@@ -27,11 +27,13 @@ pub use crate::pandoc::inline::{
     Str, Strikeout, Strong, Subscript, Superscript, Underline,
 };
 pub use crate::pandoc::list::{ListAttributes, ListNumberDelim, ListNumberStyle};
-pub use crate::pandoc::pandoc::Pandoc;
+pub use crate::pandoc::pandoc::{
+    PANDOC_API_VERSION, Pandoc, pandoc_api_version, pandoc_api_version_for, parse_pandoc_version,
+};
 pub use crate::pandoc::shortcode::Shortcode;
 pub use crate::pandoc::table::{
-    Alignment, Cell, ColWidth, Row, Table, TableBody, TableFoot, TableHead,
+    Alignment, Cell, ColSpec, ColWidth, Row, Table, TableBody, TableFoot, TableHead,
 };
 
 pub use crate::pandoc::meta::{Meta, MetaValue, rawblock_to_meta};
-pub use crate::pandoc::treesitter::treesitter_to_pandoc;
+pub use crate::pandoc::treesitter::treesitter_to_pandoc_with_options;
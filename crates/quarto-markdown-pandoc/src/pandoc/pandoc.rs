@@ -5,13 +5,59 @@
 
 pub use crate::pandoc::block::Blocks;
 pub use crate::pandoc::meta::Meta;
+use std::fmt;
 /*
  * A data structure that mimics Pandoc's `data Pandoc` type.
  * This is used to represent the parsed structure of a Quarto Markdown document.
  */
 
+// The pandoc-types API version of the documents we write. Pandoc only
+// reads JSON whose major and minor versions (the first two numbers)
+// match its own.
+pub const PANDOC_API_VERSION: [u32; 3] = [1, 23, 1];
+
+pub fn pandoc_api_version() -> [u32; 3] {
+    PANDOC_API_VERSION
+}
+
+// The API version Pandoc release `version` (major, minor) reads, or None
+// for releases before 2.11, whose APIs had a different JSON format. 2.x
+// releases read 1.22, which is 1.23 without Figure blocks.
+pub fn pandoc_api_version_for(version: (u32, u32)) -> Option<[u32; 3]> {
+    match version {
+        (major, _) if major >= 3 => Some(PANDOC_API_VERSION),
+        (2, minor) if minor >= 11 => Some([1, 22, 2]),
+        _ => None,
+    }
+}
+
+// Parses a Pandoc release number like `3.1.2` or `2.19` into its major and
+// minor parts. A missing minor part is read as 0.
+pub fn parse_pandoc_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = match parts.next() {
+        Some(minor) => minor.parse().ok()?,
+        None => 0,
+    };
+    if parts.any(|part| part.parse::<u32>().is_err()) {
+        return None;
+    }
+    Some((major, minor))
+}
+
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct Pandoc {
     pub meta: Meta,
     pub blocks: Blocks,
 }
+
+// Renders the document in Pandoc's native format, which is much easier to
+// read than the derived Debug output (e.g. in failing test assertions).
+impl fmt::Display for Pandoc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = Vec::new();
+        crate::writers::native::write(self, &mut buf).map_err(|_| fmt::Error)?;
+        f.write_str(&String::from_utf8_lossy(&buf))
+    }
+}
@@ -4,7 +4,7 @@
  */
 
 use crate::pandoc::inline::{Inline, Inlines, Span};
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ShortcodeArg {
@@ -12,19 +12,47 @@ pub enum ShortcodeArg {
     Number(f64),
     Boolean(bool),
     Shortcode(Shortcode),
-    KeyValue(HashMap<String, ShortcodeArg>),
+    KeyValue(IndexMap<String, ShortcodeArg>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Shortcode {
     pub is_escaped: bool,
     pub name: String,
-    pub positional_args: Vec<ShortcodeArg>,
-    pub keyword_args: HashMap<String, ShortcodeArg>,
+    // positional and keyword arguments (as `KeyValue`) in source order,
+    // so that `{{< x a b=1 c d=2 >}}` can be written back as it was
+    pub args: Vec<ShortcodeArg>,
+}
+
+impl Shortcode {
+    pub fn positional_args(&self) -> impl Iterator<Item = &ShortcodeArg> {
+        self.args
+            .iter()
+            .filter(|arg| !matches!(arg, ShortcodeArg::KeyValue(_)))
+    }
+
+    pub fn keyword_args(&self) -> impl Iterator<Item = (&String, &ShortcodeArg)> {
+        self.args.iter().flat_map(|arg| match arg {
+            ShortcodeArg::KeyValue(spec) => spec.iter().collect(),
+            _ => vec![],
+        })
+    }
+}
+
+// Shortcode names follow the grammar's `shortcode_name`: a letter or
+// underscore, followed by letters, digits, underscores and dashes.
+pub fn is_valid_shortcode_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        }
+        _ => false,
+    }
 }
 
 fn shortcode_value_span(str: String) -> Inline {
-    let mut attr_hash = HashMap::new();
+    let mut attr_hash = IndexMap::new();
     attr_hash.insert("data-raw".to_string(), str.clone());
     attr_hash.insert("data-value".to_string(), str);
     attr_hash.insert("data-is-shortcode".to_string(), "1".to_string());
@@ -40,7 +68,7 @@ fn shortcode_value_span(str: String) -> Inline {
 }
 
 fn shortcode_key_value_span(key: String, value: String) -> Inline {
-    let mut attr_hash = HashMap::new();
+    let mut attr_hash = IndexMap::new();
 
     // this needs to be fixed and needs to use the actual source. We'll do that when we have source mapping
     attr_hash.insert(
@@ -62,9 +90,9 @@ fn shortcode_key_value_span(key: String, value: String) -> Inline {
 }
 
 pub fn shortcode_to_span(shortcode: Shortcode) -> Span {
-    let mut attr_hash: HashMap<String, String> = HashMap::new();
+    let mut attr_hash: IndexMap<String, String> = IndexMap::new();
     let mut content: Inlines = vec![shortcode_value_span(shortcode.name)];
-    for arg in shortcode.positional_args {
+    for arg in shortcode.args {
         match arg {
             ShortcodeArg::String(text) => {
                 content.push(shortcode_value_span(text));
@@ -17,29 +17,37 @@ use crate::pandoc::inline::{
 
 use crate::pandoc::inline::{make_cite_inline, make_span_inline};
 use crate::pandoc::list::{ListAttributes, ListNumberDelim, ListNumberStyle};
-use crate::pandoc::location::{Range, empty_range, node_location};
+use crate::pandoc::location::{Location, Range, empty_range, merge_ranges, node_location};
 use crate::pandoc::meta::Meta;
 use crate::pandoc::pandoc::Pandoc;
-use crate::pandoc::shortcode::{Shortcode, ShortcodeArg, shortcode_to_span};
+use crate::pandoc::shortcode::{
+    Shortcode, ShortcodeArg, is_valid_shortcode_name, shortcode_to_span,
+};
 use crate::pandoc::table::{
     Alignment, Cell, ColSpec, ColWidth, Row, Table, TableBody, TableFoot, TableHead,
 };
 use crate::utils::autoid;
+use crate::utils::diagnostics::Diagnostic;
 use core::panic;
+use html_escape::decode_html_entities;
+use indexmap::IndexMap;
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::collections::HashMap;
+use serde_json::{Value, json};
 use std::io::Write;
 
+use crate::errors::collect_diagnostics;
 use crate::filters::{
     Filter, FilterReturn::FilterResult, FilterReturn::Unchanged, topdown_traverse,
 };
+use crate::readers::qmd::MarkdownParserOptions;
+use crate::transforms::legacy_figures::implicit_figure;
 use crate::traversals::bottomup_traverse_concrete_tree;
 
 #[derive(Debug, Clone, PartialEq)]
 enum PandocNativeIntermediate {
     IntermediatePandoc(Pandoc),
-    IntermediateAttr(Attr),
+    IntermediateAttr(Attr, Range),
     IntermediateSection(Vec<Block>),
     IntermediateBlock(Block),
     IntermediateInline(Inline),
@@ -47,7 +55,7 @@ enum PandocNativeIntermediate {
     IntermediateBaseText(String, Range),
     IntermediateLatexInlineDelimiter(Range),
     IntermediateLatexDisplayDelimiter(Range),
-    IntermediateKeyValueSpec(HashMap<String, String>),
+    IntermediateKeyValueSpec(IndexMap<String, String>),
     IntermediateRawFormat(String, Range),
     IntermediateShortcodeArg(ShortcodeArg, Range),
     IntermediateUnknown(Range),
@@ -61,11 +69,106 @@ enum PandocNativeIntermediate {
     IntermediateSetextHeadingLevel(usize),
 }
 
+impl PandocNativeIntermediate {
+    fn variant_name(&self) -> &'static str {
+        match self {
+            PandocNativeIntermediate::IntermediatePandoc(_) => "IntermediatePandoc",
+            PandocNativeIntermediate::IntermediateAttr(..) => "IntermediateAttr",
+            PandocNativeIntermediate::IntermediateSection(_) => "IntermediateSection",
+            PandocNativeIntermediate::IntermediateBlock(_) => "IntermediateBlock",
+            PandocNativeIntermediate::IntermediateInline(_) => "IntermediateInline",
+            PandocNativeIntermediate::IntermediateInlines(_) => "IntermediateInlines",
+            PandocNativeIntermediate::IntermediateBaseText(_, _) => "IntermediateBaseText",
+            PandocNativeIntermediate::IntermediateLatexInlineDelimiter(_) => {
+                "IntermediateLatexInlineDelimiter"
+            }
+            PandocNativeIntermediate::IntermediateLatexDisplayDelimiter(_) => {
+                "IntermediateLatexDisplayDelimiter"
+            }
+            PandocNativeIntermediate::IntermediateKeyValueSpec(_) => "IntermediateKeyValueSpec",
+            PandocNativeIntermediate::IntermediateRawFormat(_, _) => "IntermediateRawFormat",
+            PandocNativeIntermediate::IntermediateShortcodeArg(_, _) => "IntermediateShortcodeArg",
+            PandocNativeIntermediate::IntermediateUnknown(_) => "IntermediateUnknown",
+            PandocNativeIntermediate::IntermediateListItem(_, _, _) => "IntermediateListItem",
+            PandocNativeIntermediate::IntermediateOrderedListMarker(_, _) => {
+                "IntermediateOrderedListMarker"
+            }
+            PandocNativeIntermediate::IntermediateMetadataString(_, _) => {
+                "IntermediateMetadataString"
+            }
+            PandocNativeIntermediate::IntermediateCell(_) => "IntermediateCell",
+            PandocNativeIntermediate::IntermediateRow(_) => "IntermediateRow",
+            PandocNativeIntermediate::IntermediatePipeTableDelimiterCell(_) => {
+                "IntermediatePipeTableDelimiterCell"
+            }
+            PandocNativeIntermediate::IntermediatePipeTableDelimiterRow(_) => {
+                "IntermediatePipeTableDelimiterRow"
+            }
+            PandocNativeIntermediate::IntermediateSetextHeadingLevel(_) => {
+                "IntermediateSetextHeadingLevel"
+            }
+        }
+    }
+
+    // A reduced, serializable form of the intermediate: the variant name
+    // plus its Debug rendering. This is only meant for debugging the visitor.
+    fn to_debug_json(&self) -> Value {
+        json!({
+            "variant": self.variant_name(),
+            "value": format!("{:?}", self),
+        })
+    }
+}
+
+// Removes the optional closing sequence of an ATX heading (`## Title ##`):
+// a final run of `#`s preceded by a space, or making up the whole content.
+// Escaped `\#`s are content.
+fn strip_atx_closing_sequence(content: &mut Vec<Inline>, input_bytes: &[u8]) {
+    // the closing sequence can be followed by attributes: `## Title ## {#id}`
+    if let Some(Inline::Attr(..)) = content.last() {
+        let attr = content.pop().unwrap();
+        strip_atx_closing_sequence(content, input_bytes);
+        content.push(Inline::Space(Space {
+            filename: None,
+            range: empty_range(),
+        }));
+        content.push(attr);
+        return;
+    }
+    let mut end = content.len();
+    while end > 0 && matches!(content[end - 1], Inline::Space(_)) {
+        end -= 1;
+    }
+    // at this point, each `#` is still a separate Str
+    let is_closing_hash = |inline: &Inline| match inline {
+        Inline::Str(s) => {
+            !s.text.is_empty()
+                && s.text.chars().all(|c| c == '#')
+                && input_bytes.get(s.range.start.offset) == Some(&b'#')
+        }
+        _ => false,
+    };
+    let mut start = end;
+    while start > 0 && is_closing_hash(&content[start - 1]) {
+        start -= 1;
+    }
+    if start == end || (start > 0 && !matches!(content[start - 1], Inline::Space(_))) {
+        return;
+    }
+    content.truncate(start);
+    while matches!(content.last(), Some(Inline::Space(_))) {
+        content.pop();
+    }
+}
+
 fn native_visitor<T: Write>(
     buf: &mut T,
     node: &tree_sitter::Node,
     children: Vec<(String, PandocNativeIntermediate)>,
     input_bytes: &[u8],
+    options: &MarkdownParserOptions,
+    warnings: &mut Vec<Diagnostic>,
+    inline_trees: &[InlineTree],
 ) -> PandocNativeIntermediate {
     // TODO What sounded like a good idea with two buffers
     // is becoming annoying now...
@@ -74,8 +177,8 @@ fn native_visitor<T: Write>(
     let mut link_buf = Vec::<u8>::new();
     let mut image_buf = Vec::<u8>::new();
 
-    let whitespace_re: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
-    let indent_re: Lazy<Regex> = Lazy::new(|| Regex::new(r"[ \t]+").unwrap());
+    // only ASCII whitespace separates words; a nonbreaking space is text
+    let whitespace_re: Lazy<Regex> = Lazy::new(|| Regex::new(r"[ \t\r\n]+").unwrap());
     let escaped_double_quote_re: Lazy<Regex> = Lazy::new(|| Regex::new("[\\\\][\"]").unwrap());
     let escaped_single_quote_re: Lazy<Regex> = Lazy::new(|| Regex::new("[\\\\][']").unwrap());
 
@@ -103,16 +206,22 @@ fn native_visitor<T: Write>(
             PandocNativeIntermediate::IntermediateBaseText(value, location)
         }
     };
-    let native_inline = |(node, child)| match child {
-        PandocNativeIntermediate::IntermediateInline(inline) => inline,
+    let mut native_inline = |(node, child): (String, PandocNativeIntermediate)| match child {
+        PandocNativeIntermediate::IntermediateInline(inline) => vec![inline],
+        // some nodes (e.g. shortcodes in CommonMark-only mode) produce more than one inline
+        PandocNativeIntermediate::IntermediateInlines(inlines) => inlines,
         PandocNativeIntermediate::IntermediateBaseText(text, range) => {
             if let Some(_) = whitespace_re.find(&text) {
-                Inline::Space(Space {
+                vec![Inline::Space(Space {
                     filename: None,
                     range,
-                })
+                })]
             } else {
-                Inline::Str(Str { text })
+                vec![Inline::Str(Str {
+                    text,
+                    filename: None,
+                    range,
+                })]
             }
         }
         // as a special inline, we need to allow commonmark attributes
@@ -121,7 +230,18 @@ fn native_visitor<T: Write>(
         //
         // see tests/cursed/002.qmd for why this cannot be parsed directly in
         // the block grammar.
-        PandocNativeIntermediate::IntermediateAttr(attr) => Inline::Attr(attr),
+        PandocNativeIntermediate::IntermediateAttr(attr, range) => vec![Inline::Attr(attr, range)],
+        // brackets that didn't become part of a link or an image (`[`, or
+        // `![a]` without a reference) are text, as in CommonMark
+        PandocNativeIntermediate::IntermediateUnknown(range)
+            if matches!(node.as_str(), "[" | "]" | "!") =>
+        {
+            vec![Inline::Str(Str {
+                text: node,
+                filename: None,
+                range,
+            })]
+        }
         PandocNativeIntermediate::IntermediateUnknown(range) => {
             writeln!(
                 inline_buf,
@@ -131,10 +251,11 @@ fn native_visitor<T: Write>(
                 node
             )
             .unwrap();
-            Inline::RawInline(RawInline {
+            vec![Inline::RawInline(RawInline {
                 format: "quarto-internal-leftover".to_string(),
-                text: node_text(),
-            })
+                text: String::from_utf8_lossy(&input_bytes[range.start.offset..range.end.offset])
+                    .to_string(),
+            })]
         }
         other => {
             writeln!(
@@ -143,10 +264,10 @@ fn native_visitor<T: Write>(
                 other
             )
             .unwrap();
-            Inline::RawInline(RawInline {
+            vec![Inline::RawInline(RawInline {
                 format: "quarto-internal-leftover".to_string(),
                 text: node_text(),
-            })
+            })]
         }
     };
     let mut native_inlines = |children| {
@@ -164,7 +285,11 @@ fn native_visitor<T: Write>(
                             range,
                         }))
                     } else {
-                        inlines.push(Inline::Str(Str { text }))
+                        inlines.push(Inline::Str(Str {
+                            text,
+                            filename: None,
+                            range,
+                        }))
                     }
                 }
                 other => {
@@ -180,7 +305,60 @@ fn native_visitor<T: Write>(
         inlines
     };
 
+    let children = with_hidden_tokens(node, children, input_bytes, options, warnings, inline_trees);
     let result = match node.kind() {
+        // In CommonMark-only mode, the inline extensions are plain text.
+        // Spans, and brackets without a link destination, are too.
+        "superscript" | "subscript" | "strikeout" | "inline_note" | "quoted_span" | "citation"
+        | "latex_span"
+            if options.commonmark_only =>
+        {
+            markup_as_text(node, children, input_bytes, inline_trees, native_inline)
+        }
+        "inline_link"
+            if options.commonmark_only && !children.iter().any(|(kind, _)| kind == "(") =>
+        {
+            markup_as_text(node, children, input_bytes, inline_trees, native_inline)
+        }
+        "inline_link" | "image" | "code_span"
+            if options.commonmark_only && children.iter().any(|(kind, _)| is_attribute(kind)) =>
+        {
+            // the attribute is text after the link, image or code
+            let mut cursor = node.walk();
+            let attribute_start = node
+                .children(&mut cursor)
+                .find(|child| is_attribute(child.kind()))
+                .map_or(node.end_byte(), |child| child.start_byte());
+            let children = children
+                .into_iter()
+                .filter(|(kind, _)| !is_attribute(kind))
+                .collect();
+            let mut inlines = native_inline((
+                node.kind().to_string(),
+                native_visitor(
+                    buf,
+                    node,
+                    children,
+                    input_bytes,
+                    options,
+                    warnings,
+                    inline_trees,
+                ),
+            ));
+            inlines.extend(source_text_to_inlines(&String::from_utf8_lossy(
+                &input_bytes[attribute_start..node.end_byte()],
+            )));
+            PandocNativeIntermediate::IntermediateInlines(inlines)
+        }
+        // attributes of headings, and attribute paragraphs
+        "commonmark_attribute"
+            if options.commonmark_only
+                && node.parent().is_some_and(|parent| {
+                    parent.kind() == "inline" && parent.parent().is_none()
+                }) =>
+        {
+            PandocNativeIntermediate::IntermediateInlines(source_text_to_inlines(&node_text()))
+        }
         "numeric_character_reference" => {
             // Convert numeric character references to their corresponding characters
             // &#x0040; => @, &#64; => @, etc
@@ -205,11 +383,57 @@ fn native_visitor<T: Write>(
             PandocNativeIntermediate::IntermediateBaseText(result_text, node_location(node))
         }
 
+        "entity_reference" => PandocNativeIntermediate::IntermediateBaseText(
+            decode_html_entities(&node_text()).to_string(),
+            node_location(node),
+        ),
+        // `\ ` is a nonbreaking space. The grammar only reads backslash
+        // escapes of punctuation, so the backslash and the space after it
+        // are text of their own.
+        "text_base" if !options.commonmark_only && is_escaped_space(node, input_bytes) => {
+            let range = node_location(node);
+            PandocNativeIntermediate::IntermediateBaseText(
+                "\u{a0}".to_string(),
+                Range {
+                    end: Location {
+                        offset: range.end.offset + 1,
+                        row: range.end.row,
+                        column: range.end.column + 1,
+                    },
+                    ..range
+                },
+            )
+        }
+        "text_base"
+            if !options.commonmark_only
+                && node
+                    .prev_sibling()
+                    .is_some_and(|prev| is_escaped_space(&prev, input_bytes)) =>
+        {
+            // the space is part of the nonbreaking space, and any more
+            // whitespace is a Space
+            let text = node_text();
+            if text.len() > 1 {
+                let range = node_location(node);
+                PandocNativeIntermediate::IntermediateBaseText(
+                    text[1..].to_string(),
+                    Range {
+                        start: Location {
+                            offset: range.start.offset + 1,
+                            row: range.start.row,
+                            column: range.start.column + 1,
+                        },
+                        ..range
+                    },
+                )
+            } else {
+                PandocNativeIntermediate::IntermediateInlines(Vec::new())
+            }
+        }
         "language"
         | "note_reference_id"
         | "citation_id_suppress_author"
         | "citation_id_author_in_text"
-        | "link_destination"
         | "key_value_key"
         | "code_content"
         | "latex_content"
@@ -278,6 +502,34 @@ fn native_visitor<T: Write>(
                     inlines.extend(inner_inlines);
                 }
             }
+            // The grammar only reads a bare underline as a setext heading's.
+            // Like a title line, an underline can end with attributes, which
+            // make the paragraph a heading too.
+            let line_break = inlines
+                .iter()
+                .rposition(|inline| matches!(inline, Inline::SoftBreak(_)));
+            let has_title_attr = line_break.is_some_and(|line_break| {
+                inlines[..line_break]
+                    .iter()
+                    .any(|inline| matches!(inline, Inline::Attr(..)))
+            });
+            if let (Some(level), Some(line_break), Some(Inline::Attr(..)), false) = (
+                last_line_of(node, input_bytes, inline_trees).and_then(setext_underline_level),
+                line_break,
+                inlines.last(),
+                has_title_attr,
+            ) {
+                let attr = inlines.pop().unwrap();
+                inlines.truncate(line_break);
+                inlines.push(attr);
+                return PandocNativeIntermediate::IntermediateBlock(Block::Header(Header {
+                    level,
+                    attr: empty_attr(),
+                    content: inlines,
+                    filename: None,
+                    range: node_location(node),
+                }));
+            }
             PandocNativeIntermediate::IntermediateBlock(Block::Paragraph(Paragraph {
                 content: inlines,
 
@@ -290,7 +542,7 @@ fn native_visitor<T: Write>(
             let outer_range = node_location(node);
             // first, find the beginning of the contents in the node itself
             let outer_string = node_text();
-            let mut start_offset = indent_re.find(&outer_string).map_or(0, |m| m.end());
+            let mut start_offset = code_indent_len(&outer_string);
 
             for (node, children) in children {
                 if node == "block_continuation" {
@@ -341,7 +593,7 @@ fn native_visitor<T: Write>(
                     };
                     content = text;
                 } else if node == "commonmark_attribute" {
-                    let PandocNativeIntermediate::IntermediateAttr(a) = child else {
+                    let PandocNativeIntermediate::IntermediateAttr(a, _) = child else {
                         panic!("Expected Attr in commonmark_attribute, got {:?}", child)
                     };
                     attr = a;
@@ -356,12 +608,27 @@ fn native_visitor<T: Write>(
                     };
                     attr.1.push(lang); // set the language
                 } else if node == "info_string" {
-                    let PandocNativeIntermediate::IntermediateAttr(inner_attr) = child else {
+                    let PandocNativeIntermediate::IntermediateAttr(inner_attr, _) = child else {
                         panic!("Expected Attr in info_string, got {:?}", child)
                     };
                     attr = inner_attr;
                 }
             }
+            if options.commonmark_only {
+                // the first word of the info string is the language,
+                // whatever it looks like, and there are no raw blocks
+                let text = node_text();
+                let info = text
+                    .lines()
+                    .next()
+                    .unwrap_or("")
+                    .trim_start()
+                    .trim_start_matches(['`', '~']);
+                attr = empty_attr();
+                attr.1
+                    .extend(info.split_whitespace().next().map(str::to_string));
+                raw_format = None;
+            }
             let location = node_location(node);
 
             // it might be the case (because of tree-sitter error recovery)
@@ -389,9 +656,9 @@ fn native_visitor<T: Write>(
         "attribute" => (|| {
             for (node, child) in children {
                 match child {
-                    PandocNativeIntermediate::IntermediateAttr(attr) => {
+                    PandocNativeIntermediate::IntermediateAttr(attr, range) => {
                         if node == "commonmark_attribute" {
-                            return PandocNativeIntermediate::IntermediateAttr(attr);
+                            return PandocNativeIntermediate::IntermediateAttr(attr, range);
                         } else if node == "raw_attribute" {
                             panic!("Unexpected raw attribute in attribute: {:?}", attr);
                         } else {
@@ -404,7 +671,7 @@ fn native_visitor<T: Write>(
             panic!("No commonmark_attribute found in attribute node");
         })(),
         "commonmark_attribute" => {
-            let mut attr = ("".to_string(), vec![], HashMap::new());
+            let mut attr = ("".to_string(), vec![], IndexMap::new());
             children.into_iter().for_each(|(node, child)| match child {
                 PandocNativeIntermediate::IntermediateBaseText(id, _) => {
                     if node == "id_specifier" {
@@ -423,11 +690,15 @@ fn native_visitor<T: Write>(
                 PandocNativeIntermediate::IntermediateUnknown(_) => {}
                 _ => panic!("Unexpected child in commonmark_attribute: {:?}", child),
             });
-            PandocNativeIntermediate::IntermediateAttr(attr)
+            PandocNativeIntermediate::IntermediateAttr(attr, node_location(node))
         }
         "class_specifier" => {
-            let id = node_text().split_off(1);
-            PandocNativeIntermediate::IntermediateBaseText(id, node_location(node))
+            // `-` is short for `unnumbered` (see `canonicalize_unnumbered_class`)
+            let mut class = node_text();
+            if class != "-" {
+                class = class.split_off(1);
+            }
+            PandocNativeIntermediate::IntermediateBaseText(class, node_location(node))
         }
         "id_specifier" => {
             let id = node_text().split_off(1);
@@ -454,14 +725,28 @@ fn native_visitor<T: Write>(
             )
         }
         "key_value_value" => string_as_base_text(),
+        // entities are decoded in link destinations and titles,
+        // so that `&amp;` in a URL becomes `&`
+        "link_destination" => {
+            let text = node_text();
+            // `<url with spaces>` is a destination too, without the brackets
+            let text = match text.strip_prefix('<').and_then(|t| t.strip_suffix('>')) {
+                Some(inner) => inner,
+                None => &text,
+            };
+            PandocNativeIntermediate::IntermediateBaseText(
+                decode_html_entities(text).into_owned(),
+                node_location(node),
+            )
+        }
         "link_title" => {
             let title = node_text();
-            let title = title[1..title.len() - 1].to_string();
+            let title = decode_html_entities(&title[1..title.len() - 1]).into_owned();
             PandocNativeIntermediate::IntermediateBaseText(title, node_location(node))
         }
         "link_text" => PandocNativeIntermediate::IntermediateInlines(native_inlines(children)),
         "image" => {
-            let mut attr = ("".to_string(), vec![], HashMap::new());
+            let mut attr = ("".to_string(), vec![], IndexMap::new());
             let mut target: Target = ("".to_string(), "".to_string());
             let mut content: Vec<Inline> = Vec::new();
             for (node, child) in children {
@@ -481,7 +766,7 @@ fn native_visitor<T: Write>(
                             node_text()
                         );
                     }
-                    PandocNativeIntermediate::IntermediateAttr(a) => attr = a,
+                    PandocNativeIntermediate::IntermediateAttr(a, _) => attr = a,
                     PandocNativeIntermediate::IntermediateBaseText(text, _) => {
                         if node == "link_destination" {
                             target.0 = text; // URL
@@ -516,9 +801,11 @@ fn native_visitor<T: Write>(
             PandocNativeIntermediate::IntermediateInlines(native_inlines(children))
         }
         "inline_link" => {
-            let mut attr: Attr = ("".to_string(), vec![], HashMap::new());
+            let mut attr: Attr = ("".to_string(), vec![], IndexMap::new());
             let mut target = ("".to_string(), "".to_string());
             let mut content: Vec<Inline> = Vec::new();
+            // `[x](<>)` is a link with an empty destination, not a span
+            let mut has_explicit_destination = false;
 
             for (node, child) in children {
                 match child {
@@ -530,9 +817,10 @@ fn native_visitor<T: Write>(
                             node_text()
                         );
                     }
-                    PandocNativeIntermediate::IntermediateAttr(a) => attr = a,
+                    PandocNativeIntermediate::IntermediateAttr(a, _) => attr = a,
                     PandocNativeIntermediate::IntermediateBaseText(text, _) => {
                         if node == "link_destination" {
+                            has_explicit_destination = true;
                             target.0 = text; // URL
                         } else if node == "link_title" {
                             target.1 = text; // Title
@@ -561,16 +849,25 @@ fn native_visitor<T: Write>(
 
             // an inline link might be a Cite if it has citations, no destination, and no title
             // and no attributes
-            let is_cite = has_citations && is_empty_target(&target) && is_empty_attr(&attr);
+            let is_cite = has_citations
+                && !has_explicit_destination
+                && is_empty_target(&target)
+                && is_empty_attr(&attr);
 
             PandocNativeIntermediate::IntermediateInline(if is_cite {
                 make_cite_inline(attr, target, content)
+            } else if has_explicit_destination && is_empty_target(&target) {
+                Inline::Link(Link {
+                    attr,
+                    content,
+                    target,
+                })
             } else {
                 make_span_inline(attr, target, content)
             })
         }
         "key_value_specifier" => {
-            let mut spec = HashMap::new();
+            let mut spec = IndexMap::new();
             let mut current_key: Option<String> = None;
             for (node, child) in children {
                 if let PandocNativeIntermediate::IntermediateBaseText(value, _) = child {
@@ -610,7 +907,7 @@ fn native_visitor<T: Write>(
                 .filter(|(node, _)| {
                     node != "emphasis_delimiter" // skip emphasis delimiters
                 })
-                .map(native_inline)
+                .flat_map(native_inline)
                 .collect();
             PandocNativeIntermediate::IntermediateInline(Inline::Emph(Emph { content: inlines }))
         }
@@ -620,14 +917,14 @@ fn native_visitor<T: Write>(
                 .filter(|(node, _)| {
                     node != "emphasis_delimiter" // skip emphasis delimiters
                 })
-                .map(native_inline)
+                .flat_map(native_inline)
                 .collect();
             PandocNativeIntermediate::IntermediateInline(Inline::Strong(Strong {
                 content: inlines,
             }))
         }
         "inline" => {
-            let inlines: Vec<Inline> = children.into_iter().map(native_inline).collect();
+            let inlines: Vec<Inline> = children.into_iter().flat_map(native_inline).collect();
             PandocNativeIntermediate::IntermediateInlines(inlines)
         }
         "citation" => {
@@ -665,7 +962,11 @@ fn native_visitor<T: Write>(
                     note_num: 0, // this needs to be set later
                     hash: 0,
                 }],
-                content: vec![Inline::Str(Str { text: node_text() })],
+                content: vec![Inline::Str(Str {
+                    text: node_text(),
+                    filename: None,
+                    range: node_location(node),
+                })],
             }))
         }
         "note_reference" => {
@@ -688,11 +989,13 @@ fn native_visitor<T: Write>(
                 range: node_location(node),
             }))
         }
+        "shortcode" | "shortcode_escaped" if options.commonmark_only => {
+            PandocNativeIntermediate::IntermediateInlines(literal_text_to_inlines(&node_text()))
+        }
         "shortcode" | "shortcode_escaped" => {
             let is_escaped = node.kind() == "shortcode_escaped";
             let mut name = String::new();
-            let mut positional_args: Vec<ShortcodeArg> = Vec::new();
-            let mut keyword_args: HashMap<String, ShortcodeArg> = HashMap::new();
+            let mut args: Vec<ShortcodeArg> = Vec::new();
             for (node, child) in children {
                 match (node.as_str(), child) {
                     (
@@ -719,7 +1022,7 @@ fn native_visitor<T: Write>(
                         if name.is_empty() {
                             name = text;
                         } else {
-                            positional_args.push(ShortcodeArg::String(text));
+                            args.push(ShortcodeArg::String(text));
                         }
                     }
                     (
@@ -729,15 +1032,13 @@ fn native_visitor<T: Write>(
                             _,
                         ),
                     ) => {
-                        for (key, value) in spec {
-                            keyword_args.insert(key, value);
-                        }
+                        args.push(ShortcodeArg::KeyValue(spec));
                     }
                     (
                         "shortcode",
                         PandocNativeIntermediate::IntermediateInline(Inline::Shortcode(arg)),
                     ) => {
-                        positional_args.push(ShortcodeArg::Shortcode(arg));
+                        args.push(ShortcodeArg::Shortcode(arg));
                     }
                     (
                         "shortcode_number",
@@ -747,7 +1048,7 @@ fn native_visitor<T: Write>(
                         "shortcode_boolean",
                         PandocNativeIntermediate::IntermediateShortcodeArg(arg, _),
                     ) => {
-                        positional_args.push(arg);
+                        args.push(arg);
                     }
                     ("shortcode_delimiter", _) => {
                         // This is a marker node, we don't need to do anything with it
@@ -760,15 +1061,20 @@ fn native_visitor<T: Write>(
                     ),
                 }
             }
+            if !is_valid_shortcode_name(&name) {
+                warnings.push(invalid_shortcode_warning(node_location(node), false));
+                return PandocNativeIntermediate::IntermediateInlines(literal_text_to_inlines(
+                    &node_text(),
+                ));
+            }
             PandocNativeIntermediate::IntermediateInline(Inline::Shortcode(Shortcode {
                 is_escaped,
                 name,
-                positional_args,
-                keyword_args,
+                args,
             }))
         }
         "shortcode_keyword_param" => {
-            let mut result = HashMap::new();
+            let mut result = IndexMap::new();
             let mut name = String::new();
             for (node, child) in children {
                 match node.as_str() {
@@ -930,7 +1236,7 @@ fn native_visitor<T: Write>(
             let inlines: Vec<_> = children
                 .into_iter()
                 .filter(|(node, _)| node != "inline_note_delimiter")
-                .map(native_inline)
+                .flat_map(native_inline)
                 .collect();
             PandocNativeIntermediate::IntermediateInline(Inline::Note(Note {
                 content: vec![Block::Paragraph(Paragraph {
@@ -941,31 +1247,25 @@ fn native_visitor<T: Write>(
                 })],
             }))
         }
-        "superscript" => {
-            let inlines: Vec<_> = children
-                .into_iter()
-                .filter(|(node, _)| node != "superscript_delimiter")
-                .map(native_inline)
-                .collect();
-            PandocNativeIntermediate::IntermediateInline(Inline::Superscript(Superscript {
-                content: inlines,
-            }))
-        }
-        "subscript" => {
-            let inlines: Vec<_> = children
-                .into_iter()
-                .filter(|(node, _)| node != "subscript_delimiter")
-                .map(native_inline)
-                .collect();
-            PandocNativeIntermediate::IntermediateInline(Inline::Subscript(Subscript {
-                content: inlines,
-            }))
-        }
+        "superscript" => script_inline(
+            children,
+            "superscript_delimiter",
+            "^",
+            native_inline,
+            |content| Inline::Superscript(Superscript { content }),
+        ),
+        "subscript" => script_inline(
+            children,
+            "subscript_delimiter",
+            "~",
+            native_inline,
+            |content| Inline::Subscript(Subscript { content }),
+        ),
         "strikeout" => {
             let inlines: Vec<_> = children
                 .into_iter()
                 .filter(|(node, _)| node != "strikeout_delimiter")
-                .map(native_inline)
+                .flat_map(native_inline)
                 .collect();
             PandocNativeIntermediate::IntermediateInline(Inline::Strikeout(Strikeout {
                 content: inlines,
@@ -991,7 +1291,7 @@ fn native_visitor<T: Write>(
                         }
                     }
                 })
-                .map(native_inline)
+                .flat_map(native_inline)
                 .collect();
             PandocNativeIntermediate::IntermediateInline(Inline::Quoted(Quoted {
                 quote_type,
@@ -1000,14 +1300,14 @@ fn native_visitor<T: Write>(
         }
         "code_span" => (|| {
             let mut is_raw: Option<String> = None;
-            let mut attr = ("".to_string(), vec![], HashMap::new());
+            let mut attr = ("".to_string(), vec![], IndexMap::new());
             let mut language_attribute: Option<String> = None;
             let mut inlines: Vec<_> = children
                 .into_iter()
                 .map(|(node_name, child)| {
                     let range = node_location(node);
                     match child {
-                        PandocNativeIntermediate::IntermediateAttr(a) => {
+                        PandocNativeIntermediate::IntermediateAttr(a, _) => {
                             attr = a;
                             // IntermediateUnknown here "consumes" the node
                             (
@@ -1322,13 +1622,20 @@ fn native_visitor<T: Write>(
                         ));
                         return false; // skip the marker node
                     }
-                    matches!(child, PandocNativeIntermediate::IntermediateBlock(_))
+                    if node == "block_continuation" {
+                        return false;
+                    }
+                    matches!(
+                        child,
+                        PandocNativeIntermediate::IntermediateBlock(_)
+                            | PandocNativeIntermediate::IntermediateSection(_)
+                    )
                 })
-                .map(|(_, child)| {
-                    let PandocNativeIntermediate::IntermediateBlock(block) = child else {
-                        panic!("Expected Block in paragraph, got {:?}", child);
-                    };
-                    block
+                .flat_map(|(_, child)| match child {
+                    PandocNativeIntermediate::IntermediateBlock(block) => vec![block],
+                    // a heading in the item starts a section
+                    PandocNativeIntermediate::IntermediateSection(blocks) => blocks,
+                    _ => panic!("Expected Block in list_item, got {:?}", child),
                 })
                 .collect();
             PandocNativeIntermediate::IntermediateListItem(children, node_location(node), list_attr)
@@ -1336,12 +1643,11 @@ fn native_visitor<T: Write>(
         "info_string" => (|| {
             for (_, child) in children {
                 match child {
-                    PandocNativeIntermediate::IntermediateBaseText(text, _) => {
-                        return PandocNativeIntermediate::IntermediateAttr((
-                            "".to_string(),
-                            vec![text],
-                            HashMap::new(),
-                        ));
+                    PandocNativeIntermediate::IntermediateBaseText(text, range) => {
+                        return PandocNativeIntermediate::IntermediateAttr(
+                            ("".to_string(), vec![text], IndexMap::new()),
+                            range,
+                        );
                     }
                     _ => {}
                 }
@@ -1377,23 +1683,18 @@ fn native_visitor<T: Write>(
         "block_quote" => {
             let mut content: Blocks = Vec::new();
             for (node_type, child) in children {
-                if node_type == "block_quote_marker" {
-                    if matches!(child, PandocNativeIntermediate::IntermediateUnknown(_)) {
-                        if node_type != "block_continuation" {
-                            writeln!(
-                                buf,
-                                "Warning: Unhandled node kind in block_quote: {}, {:?}",
-                                node_type, child,
-                            )
-                            .unwrap();
-                        }
-                    }
+                // markers and continuations carry no content
+                if node_type == "block_quote_marker" || node_type == "block_continuation" {
                     continue;
                 }
                 match child {
                     PandocNativeIntermediate::IntermediateBlock(block) => {
                         content.push(block);
                     }
+                    // a heading in the quote starts a section
+                    PandocNativeIntermediate::IntermediateSection(blocks) => {
+                        content.extend(blocks);
+                    }
                     _ => {
                         writeln!(
                         buf,
@@ -1410,7 +1711,7 @@ fn native_visitor<T: Write>(
             }))
         }
         "fenced_div_block" => {
-            let mut attr: Attr = ("".to_string(), vec![], HashMap::new());
+            let mut attr: Attr = ("".to_string(), vec![], IndexMap::new());
             let mut content: Vec<Block> = Vec::new();
             for (node, child) in children {
                 if node == "block_continuation" {
@@ -1442,7 +1743,7 @@ fn native_visitor<T: Write>(
                         )
                         .unwrap();
                     }
-                    PandocNativeIntermediate::IntermediateAttr(a) => {
+                    PandocNativeIntermediate::IntermediateAttr(a, _) => {
                         attr = a;
                     }
                     PandocNativeIntermediate::IntermediateBlock(block) => {
@@ -1471,7 +1772,7 @@ fn native_visitor<T: Write>(
         "atx_heading" => {
             let mut level = 0;
             let mut content: Vec<Inline> = Vec::new();
-            let mut attr: Attr = ("".to_string(), vec![], HashMap::new());
+            let mut attr: Attr = ("".to_string(), vec![], IndexMap::new());
             for (node, child) in children {
                 if node == "block_continuation" {
                     continue;
@@ -1495,7 +1796,7 @@ fn native_visitor<T: Write>(
                         panic!("Expected Inlines in atx_heading, got {:?}", child);
                     }
                 } else if node == "attribute" {
-                    if let PandocNativeIntermediate::IntermediateAttr(inner_attr) = child {
+                    if let PandocNativeIntermediate::IntermediateAttr(inner_attr, _) = child {
                         attr = inner_attr;
                     } else {
                         panic!("Expected Attr in attribute, got {:?}", child);
@@ -1504,6 +1805,23 @@ fn native_visitor<T: Write>(
                     writeln!(buf, "Warning: Unhandled node kind in atx_heading: {}", node).unwrap();
                 }
             }
+            if level == 0 {
+                // shouldn't happen for a parsed heading, but an error-recovered one
+                // may lack its marker. Count the `#`s in the source instead.
+                let text = node.utf8_text(input_bytes).unwrap_or("");
+                let hashes = text.trim_start().chars().take_while(|c| *c == '#').count();
+                level = hashes.clamp(1, 6);
+                let range = node_location(node);
+                writeln!(
+                    buf,
+                    "Warning: No heading marker found in atx_heading at ({}:{}); using level {}.",
+                    range.start.row + 1,
+                    range.start.column + 1,
+                    level
+                )
+                .unwrap();
+            }
+            strip_atx_closing_sequence(&mut content, input_bytes);
             PandocNativeIntermediate::IntermediateBlock(Block::Header(Header {
                 level,
                 attr,
@@ -1520,13 +1838,16 @@ fn native_visitor<T: Write>(
         }
         "backslash_escape" => {
             // This is a backslash escape, we need to extract the content
-            // by removing the backslash
+            // by removing the backslash. As in CommonMark, only ASCII
+            // punctuation can be escaped: anything else keeps its backslash.
             let text = node.utf8_text(input_bytes).unwrap();
             if text.len() < 2 || !text.starts_with('\\') {
                 panic!("Invalid backslash escape: {}", text);
             }
             let content = &text[1..]; // remove the leading backslash
-            PandocNativeIntermediate::IntermediateBaseText(content.to_string(), node_location(node))
+            let is_escapable = content.len() == 1 && content.as_bytes()[0].is_ascii_punctuation();
+            let text = if is_escapable { content } else { text };
+            PandocNativeIntermediate::IntermediateBaseText(text.to_string(), node_location(node))
         }
         "minus_metadata" => {
             let text = node.utf8_text(input_bytes).unwrap();
@@ -1543,12 +1864,19 @@ fn native_visitor<T: Write>(
                 panic!("Invalid URI autolink: {}", text);
             }
             let content = &text[1..text.len() - 1]; // remove the angle brackets
-            let mut attr = ("".to_string(), vec![], HashMap::new());
+            let mut content_range = node_location(node);
+            content_range.start.offset += 1;
+            content_range.start.column += 1;
+            content_range.end.offset -= 1;
+            content_range.end.column -= 1;
+            let mut attr = ("".to_string(), vec![], IndexMap::new());
             // pandoc adds the class "uri" to autolinks
             attr.1.push("uri".to_string());
             PandocNativeIntermediate::IntermediateInline(Inline::Link(Link {
                 content: vec![Inline::Str(Str {
                     text: content.to_string(),
+                    filename: None,
+                    range: content_range,
                 })],
                 attr,
                 target: (content.to_string(), "".to_string()),
@@ -1626,14 +1954,22 @@ fn native_visitor<T: Write>(
                 alignment: Alignment::Left,
                 col_span: 1,
                 row_span: 1,
-                attr: ("".to_string(), vec![], HashMap::new()),
+                attr: ("".to_string(), vec![], IndexMap::new()),
                 content: vec![],
             };
             for (node, child) in children {
                 if node == "inline" {
                     match child {
                         PandocNativeIntermediate::IntermediateInlines(inlines) => {
-                            plain_content.extend(inlines);
+                            // a cell is one line of source, as in GFM, so line
+                            // breaks in a cell's inlines are read as spaces
+                            plain_content.extend(inlines.into_iter().map(|inline| match inline {
+                                Inline::SoftBreak(soft_break) => Inline::Space(Space {
+                                    filename: soft_break.filename,
+                                    range: soft_break.range,
+                                }),
+                                inline => inline,
+                            }));
                         }
                         _ => panic!("Expected Inlines in pipe_table_cell, got {:?}", child),
                     }
@@ -1736,6 +2072,9 @@ fn native_visitor<T: Write>(
                     }
                 }
             }
+            // A trailing `{#id .cls}` on the title line is still an Attr inline at
+            // the end of `content`; the header desugaring below moves it into
+            // `attr`. Attributes after the underline are in the "paragraph" arm.
             PandocNativeIntermediate::IntermediateBlock(Block::Header(Header {
                 level,
                 attr: empty_attr(),
@@ -1762,6 +2101,23 @@ fn native_visitor<T: Write>(
     result
 }
 
+// Indented code blocks are indented by four columns; anything past
+// that (including tabs) is content.
+fn code_indent_len(line: &str) -> usize {
+    let mut column = 0;
+    for (i, ch) in line.char_indices() {
+        if column >= 4 {
+            return i;
+        }
+        match ch {
+            ' ' => column += 1,
+            '\t' => column += 4 - column % 4,
+            _ => return i,
+        }
+    }
+    line.len()
+}
+
 fn trim_inlines(inlines: Inlines) -> (Inlines, bool) {
     let mut result: Inlines = Vec::new();
     let mut at_start = true;
@@ -1792,7 +2148,139 @@ fn trim_inlines(inlines: Inlines) -> (Inlines, bool) {
     (result, changed)
 }
 
-fn desugar(doc: Pandoc) -> Result<Pandoc, Vec<String>> {
+// Pandoc accepts `-` as a shorthand for the `unnumbered` class on headers.
+// We canonicalize it here so that downstream numbering logic only has to
+// look for `unnumbered`. Other classes (such as `unlisted`) are preserved.
+//
+// Returns true if the attribute was changed.
+fn canonicalize_unnumbered_class(attr: &mut Attr) -> bool {
+    if !attr.1.iter().any(|class| class == "-") {
+        return false;
+    }
+    let mut classes: Vec<String> = Vec::new();
+    for class in attr.1.drain(..) {
+        let class = if class == "-" {
+            "unnumbered".to_string()
+        } else {
+            class
+        };
+        if !classes.contains(&class) {
+            classes.push(class);
+        }
+    }
+    attr.1 = classes;
+    true
+}
+
+// A line holding only attributes attaches them to the paragraph it ends
+// (`text\n{.cls}`). A paragraph holding only attributes attaches them to
+// the paragraph or table right before it, or else to the table right after
+// it. Tables take the attributes as their own, and so do the figures that
+// single-image paragraphs become; other Pandoc paragraphs have no
+// attributes, so a paragraph is wrapped in a Div that carries them.
+//
+// Removes and returns the attributes of a trailing attribute line, if the
+// paragraph has one.
+fn take_attr_line(content: &mut Inlines) -> Option<Attr> {
+    if !matches!(
+        content.as_slice(),
+        [.., Inline::SoftBreak(_), Inline::Attr(..)]
+    ) {
+        return None;
+    }
+    let Some(Inline::Attr(attr, _)) = content.pop() else {
+        unreachable!("content should end with an attribute");
+    };
+    content.pop();
+    Some(attr)
+}
+
+fn is_attr_paragraph(block: &Block) -> bool {
+    match block {
+        Block::Paragraph(para) => matches!(para.content.as_slice(), [Inline::Attr(..)]),
+        _ => false,
+    }
+}
+
+fn take_attr_paragraph_attr(block: Block) -> Attr {
+    let Block::Paragraph(mut para) = block else {
+        unreachable!("attribute paragraphs are paragraphs");
+    };
+    let Some(Inline::Attr(attr, _)) = para.content.pop() else {
+        unreachable!("attribute paragraphs hold an attribute");
+    };
+    attr
+}
+
+fn wrap_paragraph_in_div(para: Paragraph, attr: Attr) -> Block {
+    Block::Div(Div {
+        attr,
+        filename: para.filename.clone(),
+        range: para.range.clone(),
+        content: vec![Block::Paragraph(para)],
+    })
+}
+
+// Attaches `attr` to `para`: to the figure it becomes, if it's a single
+// captioned image, or else to a Div around it.
+fn attach_attr_to_paragraph(para: Paragraph, attr: Attr, legacy_figures: bool) -> Block {
+    let Some(mut figure) = image_figure(&para) else {
+        return wrap_paragraph_in_div(para, attr);
+    };
+    let (id, classes, attributes) = attr;
+    if figure.attr.0.is_empty() {
+        figure.attr.0 = id;
+    }
+    figure.attr.1.extend(classes);
+    figure.attr.2.extend(attributes);
+    figure_block(figure, legacy_figures)
+}
+
+// The figure for a paragraph holding only an image with a caption.
+fn image_figure(para: &Paragraph) -> Option<Figure> {
+    let [Inline::Image(image)] = para.content.as_slice() else {
+        return None;
+    };
+    if image.content.is_empty() {
+        return None;
+    }
+    let figure_attr: Attr = (image.attr.0.clone(), vec![], IndexMap::new());
+    let image_attr: Attr = ("".to_string(), image.attr.1.clone(), image.attr.2.clone());
+    let mut new_image = image.clone();
+    new_image.attr = image_attr;
+    // FIXME all source location is broken here
+    Some(Figure {
+        attr: figure_attr,
+        caption: Caption {
+            short: None,
+            long: Some(vec![Block::Plain(Plain {
+                content: image.content.clone(),
+                filename: None,
+                range: empty_range(),
+            })]),
+        },
+        content: vec![Block::Plain(Plain {
+            content: vec![Inline::Image(new_image)],
+            filename: None,
+            range: empty_range(),
+        })],
+        filename: None,
+        range: empty_range(),
+    })
+}
+
+// `figure`, as an implicit figure if the reader makes those
+fn figure_block(figure: Figure, legacy_figures: bool) -> Block {
+    if !legacy_figures {
+        return Block::Figure(figure);
+    }
+    match implicit_figure(&figure) {
+        Some(para) => Block::Paragraph(para),
+        None => Block::Figure(figure),
+    }
+}
+
+fn desugar(doc: Pandoc, legacy_figures: bool) -> Result<Pandoc, Vec<Diagnostic>> {
     let mut errors = Vec::new();
     let raw_reader_format_specifier: Lazy<Regex> =
         Lazy::new(|| Regex::new(r"<(?P<reader>.+)").unwrap());
@@ -1815,9 +2303,10 @@ fn desugar(doc: Pandoc) -> Result<Pandoc, Vec<String>> {
                 let is_last_attr = header
                     .content
                     .last()
-                    .map_or(false, |v| matches!(v, Inline::Attr(_)));
+                    .map_or(false, |v| matches!(v, Inline::Attr(..)));
                 if !is_last_attr {
                     let mut attr = header.attr.clone();
+                    let classes_changed = canonicalize_unnumbered_class(&mut attr);
                     if attr.0.is_empty() {
                         attr.0 = autoid::auto_generated_id(&header.content);
                         if !is_empty_attr(&attr) {
@@ -1826,62 +2315,90 @@ fn desugar(doc: Pandoc) -> Result<Pandoc, Vec<String>> {
                         } else {
                             Unchanged(header)
                         }
+                    } else if classes_changed {
+                        header.attr = attr;
+                        FilterResult(vec![Block::Header(header)], true)
                     } else {
                         Unchanged(header)
                     }
                 } else {
-                    let Some(Inline::Attr(attr)) = header.content.pop() else {
+                    let Some(Inline::Attr(mut attr, _)) = header.content.pop() else {
                         panic!("shouldn't happen, header should have an attribute at this point");
                     };
+                    canonicalize_unnumbered_class(&mut attr);
                     header.attr = attr;
                     header.content = trim_inlines(header.content).0;
                     FilterResult(vec![Block::Header(header)], true)
                 }
             })
-            // attempt to desugar single-image paragraphs into figures
-            .with_paragraph(|para| {
-                if para.content.len() != 1 {
-                    return Unchanged(para);
+            // attach standalone attribute paragraphs to the table before
+            // them, or else to the table after them, or else to the
+            // paragraph before them (`{#tbl-x}` between a paragraph and a
+            // table labels the table)
+            .with_blocks(|blocks| {
+                if !blocks.iter().any(is_attr_paragraph) {
+                    return Unchanged(blocks);
                 }
-                let first = para.content.first().unwrap();
-                let Inline::Image(image) = first else {
-                    return Unchanged(para);
-                };
-                if image.content.is_empty() {
-                    return Unchanged(para);
+                let mut result: Blocks = vec![];
+                let mut blocks = blocks.into_iter().peekable();
+                while let Some(block) = blocks.next() {
+                    if is_attr_paragraph(&block) {
+                        let table_follows = matches!(
+                            blocks.peek(),
+                            Some(Block::Table(table)) if is_empty_attr(&table.attr)
+                        );
+                        match result.pop() {
+                            Some(Block::Paragraph(para)) if !table_follows => {
+                                let attr = take_attr_paragraph_attr(block);
+                                result.push(attach_attr_to_paragraph(para, attr, legacy_figures));
+                            }
+                            Some(Block::Table(mut table)) if is_empty_attr(&table.attr) => {
+                                table.attr = take_attr_paragraph_attr(block);
+                                result.push(Block::Table(table));
+                            }
+                            // nothing to attach to (yet): a following table
+                            // can still take it, otherwise the leftover
+                            // attribute is reported below
+                            previous => {
+                                result.extend(previous);
+                                result.push(block);
+                            }
+                        }
+                    } else if let Block::Table(mut table) = block {
+                        if result.last().is_some_and(is_attr_paragraph)
+                            && is_empty_attr(&table.attr)
+                        {
+                            table.attr = take_attr_paragraph_attr(result.pop().unwrap());
+                        }
+                        result.push(Block::Table(table));
+                    } else {
+                        result.push(block);
+                    }
+                }
+                FilterResult(result, true)
+            })
+            // attach trailing attribute lines, and attempt to desugar
+            // single-image paragraphs into figures
+            .with_paragraph(|mut para| {
+                if let Some(attr) = take_attr_line(&mut para.content) {
+                    let block = attach_attr_to_paragraph(para, attr, legacy_figures);
+                    return FilterResult(vec![block], true);
+                }
+                match image_figure(&para) {
+                    Some(figure) => match figure_block(figure, legacy_figures) {
+                        // an implicit figure is a paragraph, which would be
+                        // made a figure again
+                        Block::Paragraph(para) => Unchanged(para),
+                        block => FilterResult(vec![block], true),
+                    },
+                    None => Unchanged(para),
                 }
-                let figure_attr: Attr = (image.attr.0.clone(), vec![], HashMap::new());
-                let image_attr: Attr = ("".to_string(), image.attr.1.clone(), image.attr.2.clone());
-                let mut new_image = image.clone();
-                new_image.attr = image_attr;
-                // FIXME all source location is broken here
-                FilterResult(
-                    vec![Block::Figure(Figure {
-                        attr: figure_attr,
-                        caption: Caption {
-                            short: None,
-                            long: Some(vec![Block::Plain(Plain {
-                                content: image.content.clone(),
-                                filename: None,
-                                range: empty_range(),
-                            })]),
-                        },
-                        content: vec![Block::Plain(Plain {
-                            content: vec![Inline::Image(new_image)],
-                            filename: None,
-                            range: empty_range(),
-                        })],
-                        filename: None,
-                        range: empty_range(),
-                    })],
-                    true,
-                )
             })
             .with_shortcode(|shortcode| {
                 FilterResult(vec![Inline::Span(shortcode_to_span(shortcode))], false)
             })
             .with_note_reference(|note_ref| {
-                let mut kv = HashMap::new();
+                let mut kv = IndexMap::new();
                 kv.insert("reference-id".to_string(), note_ref.id);
                 FilterResult(
                     vec![Inline::Span(Span {
@@ -2012,12 +2529,19 @@ fn desugar(doc: Pandoc) -> Result<Pandoc, Vec<String>> {
                     false,
                 );
             })
-            .with_attr(|attr| {
-                // TODO in order to do good error messages here, attr will need source mapping
-                errors.push(format!(
-                    "Found attr in desugar: {:?} - this should have been removed",
-                    attr
-                ));
+            // attributes nothing above took
+            .with_attr(|(_, range)| {
+                errors.push(
+                    Diagnostic::new(
+                        "Unattached attributes",
+                        range,
+                        "Nothing here takes these attributes.",
+                    )
+                    .with_note(
+                        "Attributes go right after a heading, span, link, image or code span, \
+                         or on their own line next to a paragraph or table.",
+                    ),
+                );
                 FilterResult(vec![], false)
             });
         topdown_traverse(doc, &mut filter)
@@ -2041,34 +2565,46 @@ fn as_smart_str(s: String) -> String {
     }
 }
 
-fn merge_strs(pandoc: Pandoc) -> Pandoc {
+fn merge_strs(pandoc: Pandoc, smart: bool) -> Pandoc {
     topdown_traverse(
         pandoc,
         &mut Filter::new().with_inlines(|inlines| {
-            let mut current_str: Option<String> = None;
+            let mut current_str: Option<Str> = None;
             let mut result: Inlines = Vec::new();
             let mut did_merge = false;
             for inline in inlines {
                 match inline {
                     Inline::Str(s) => {
-                        let str_text = as_smart_str(s.text);
+                        let str_text = if smart { as_smart_str(s.text) } else { s.text };
                         if let Some(ref mut current) = current_str {
-                            current.push_str(&str_text);
+                            current.text.push_str(&str_text);
+                            current.range = merge_ranges(&current.range, &s.range);
                             did_merge = true;
                         } else {
-                            current_str = Some(str_text);
+                            current_str = Some(Str {
+                                text: str_text,
+                                ..s
+                            });
                         }
                     }
                     _ => {
                         if let Some(current) = current_str.take() {
-                            result.push(Inline::Str(Str { text: current }));
+                            result.push(Inline::Str(current));
+                        }
+                        // as in Pandoc, a space before a line break
+                        // (`foo \` at the end of a line) is dropped
+                        if let (Inline::LineBreak(_), Some(Inline::Space(_))) =
+                            (&inline, result.last())
+                        {
+                            result.pop();
+                            did_merge = true;
                         }
                         result.push(inline);
                     }
                 }
             }
             if let Some(current) = current_str {
-                result.push(Inline::Str(Str { text: current }));
+                result.push(Inline::Str(current));
             }
             if did_merge {
                 FilterResult(result, true)
@@ -2079,19 +2615,719 @@ fn merge_strs(pandoc: Pandoc) -> Pandoc {
     )
 }
 
+// Whether the text node `node` is the backslash of `\ `.
+fn is_escaped_space(node: &tree_sitter::Node, input_bytes: &[u8]) -> bool {
+    // an escaped backslash, `\\`, is a `backslash_escape`
+    node.kind() == "text_base"
+        && &input_bytes[node.byte_range()] == b"\\"
+        && input_bytes.get(node.end_byte()) == Some(&b' ')
+}
+
+// As in Pandoc, `^...^` and `~...~` are only super- and subscripts when
+// there are no unescaped spaces between the delimiters (`\ ` is allowed and
+// becomes a nonbreaking space). Otherwise the delimiters are literal text.
+fn script_inline(
+    children: Vec<(String, PandocNativeIntermediate)>,
+    delimiter: &str,
+    delimiter_text: &str,
+    mut native_inline: impl FnMut((String, PandocNativeIntermediate)) -> Inlines,
+    make_inline: impl FnOnce(Inlines) -> Inline,
+) -> PandocNativeIntermediate {
+    let mut delimiters: Vec<Range> = Vec::new();
+    let mut content: Inlines = Vec::new();
+    for (node, child) in children {
+        if node == delimiter {
+            if let PandocNativeIntermediate::IntermediateUnknown(range) = child {
+                delimiters.push(range);
+            }
+        } else {
+            content.extend(native_inline((node, child)));
+        }
+    }
+    let has_spaces = content.iter().any(|inline| {
+        matches!(
+            inline,
+            Inline::Space(_) | Inline::SoftBreak(_) | Inline::LineBreak(_)
+        )
+    });
+    if !has_spaces {
+        return PandocNativeIntermediate::IntermediateInline(make_inline(content));
+    }
+    let delimiter_str = |range: Option<&Range>| {
+        Inline::Str(Str {
+            text: delimiter_text.to_string(),
+            filename: None,
+            range: range.cloned().unwrap_or_else(empty_range),
+        })
+    };
+    let mut result = vec![delimiter_str(delimiters.first())];
+    result.extend(content);
+    result.push(delimiter_str(delimiters.get(1)));
+    PandocNativeIntermediate::IntermediateInlines(result)
+}
+
+// An inline tree: its root node, and its included ranges, which leave out
+// the block continuations (`> ` and the like) inside a paragraph.
+struct InlineTree<'t> {
+    root: tree_sitter::Node<'t>,
+    ranges: Vec<tree_sitter::Range>,
+}
+
+impl<'t> InlineTree<'t> {
+    fn new(tree: &'t tree_sitter::Tree) -> InlineTree<'t> {
+        InlineTree {
+            root: tree.root_node(),
+            ranges: tree.included_ranges(),
+        }
+    }
+}
+
+// The inline tree `node` belongs to. For an `inline` node of the block
+// tree, this is the tree of its content.
+fn inline_tree_of<'a, 't>(
+    inline_trees: &'a [InlineTree<'t>],
+    node: &tree_sitter::Node,
+) -> Option<&'a InlineTree<'t>> {
+    inline_trees.iter().find(|tree| {
+        tree.ranges
+            .first()
+            .is_some_and(|r| r.start_byte <= node.start_byte())
+            && tree
+                .ranges
+                .last()
+                .is_some_and(|r| r.end_byte >= node.end_byte())
+    })
+}
+
+// The included ranges of the inline tree `node` belongs to.
+fn ranges_of(inline_trees: &[InlineTree], node: &tree_sitter::Node) -> Vec<tree_sitter::Range> {
+    inline_tree_of(inline_trees, node)
+        .map_or_else(|| vec![node.range()], |tree| tree.ranges.clone())
+}
+
+// The CriticMarkup delimiters (see scanner.c).
+const CRITIC_MARKUP_DELIMITERS: [&str; 11] = [
+    "{++", "++}", "{--", "--}", "{~~", "~~}", "~>", "{>>", "<<}", "{==", "==}",
+];
+
+// CriticMarkup delimiters and shortcodes without a valid name are hidden
+// tokens, so they have no nodes: they are in the gaps between the children
+// of the node they belong to. This adds each delimiter to `children` as a
+// Str, which `critic_markup_to_spans` turns into spans later. No other Str
+// is only a delimiter, because the grammar never reads `{` or `}` as text,
+// and reads other punctuation one character at a time. Invalid shortcodes
+// are reported, and kept as text.
+fn with_hidden_tokens(
+    node: &tree_sitter::Node,
+    children: Vec<(String, PandocNativeIntermediate)>,
+    input_bytes: &[u8],
+    options: &MarkdownParserOptions,
+    warnings: &mut Vec<Diagnostic>,
+    inline_trees: &[InlineTree],
+) -> Vec<(String, PandocNativeIntermediate)> {
+    // an `inline` node of the block tree gets the children of the root of
+    // its inline tree
+    let parent = if node.kind() == "inline" {
+        match inline_tree_of(inline_trees, node) {
+            Some(tree) => tree.root,
+            None => return children,
+        }
+    } else {
+        *node
+    };
+    let mut cursor = parent.walk();
+    let child_nodes: Vec<tree_sitter::Node> = parent.children(&mut cursor).collect();
+    // only the inline content itself can be nothing but hidden tokens;
+    // other nodes without children are tokens
+    if (child_nodes.is_empty() && node.kind() != "inline") || child_nodes.len() != children.len() {
+        return children;
+    }
+
+    // the gaps before each child and after the last one, as their start
+    // byte and point and their end byte
+    let mut gap_start = (node.start_byte(), node.start_position());
+    let mut gaps = Vec::with_capacity(child_nodes.len() + 1);
+    for child in &child_nodes {
+        gaps.push((gap_start, child.start_byte()));
+        gap_start = (child.end_byte(), child.end_position());
+    }
+    gaps.push((gap_start, node.end_byte()));
+    let may_have_delimiters = gaps.iter().any(|&((start, _), end)| {
+        input_bytes[start..end.max(start)]
+            .iter()
+            .any(|byte| matches!(byte, b'{' | b'}' | b'>'))
+    });
+    if !may_have_delimiters || !is_in_inline_tree(&parent) {
+        return children;
+    }
+    let Some(tree) = inline_tree_of(inline_trees, node) else {
+        return children;
+    };
+
+    let mut tokens = gaps.into_iter().map(|((start, point), end)| {
+        hidden_tokens_in(start..end, point, &tree.ranges, input_bytes)
+    });
+    let mut result = Vec::with_capacity(children.len() + 2);
+    let mut push_tokens = |result: &mut Vec<_>, tokens: Vec<_>| {
+        for (kind, token) in tokens {
+            let (kind, token) = match (kind, token) {
+                (kind, PandocNativeIntermediate::IntermediateBaseText(text, range))
+                    if kind == "invalid_shortcode" =>
+                {
+                    if !options.commonmark_only {
+                        let name = input_bytes[range.start.offset + 3..]
+                            .iter()
+                            .find(|&&byte| byte != b' ' && byte != b'\t');
+                        let missing_name = matches!(name, None | Some(b'>' | b'\n'));
+                        warnings.push(invalid_shortcode_warning(range, missing_name));
+                    }
+                    let inlines = literal_text_to_inlines(&text);
+                    (kind, PandocNativeIntermediate::IntermediateInlines(inlines))
+                }
+                other => other,
+            };
+            result.push((kind, token));
+        }
+    };
+    for child in children {
+        push_tokens(&mut result, tokens.next().unwrap_or_default());
+        result.push(child);
+    }
+    push_tokens(&mut result, tokens.next().unwrap_or_default());
+    result
+}
+
+// Whether `node` is part of an inline tree, rather than the block tree
+// (whose root is a `document`).
+fn is_in_inline_tree(node: &tree_sitter::Node) -> bool {
+    let mut root = *node;
+    while let Some(parent) = root.parent() {
+        root = parent;
+    }
+    root.kind() == "inline"
+}
+
+// The CriticMarkup delimiters and invalid shortcodes in the gap `bytes`,
+// which starts at `point`, skipping whitespace and what's outside of
+// `ranges`. There are none if the gap has something else (like the backtick
+// of an unclosed code span).
+fn hidden_tokens_in(
+    bytes: std::ops::Range<usize>,
+    mut point: tree_sitter::Point,
+    ranges: &[tree_sitter::Range],
+    input_bytes: &[u8],
+) -> Vec<(String, PandocNativeIntermediate)> {
+    let mut tokens = Vec::new();
+    let mut offset = bytes.start;
+    while offset < bytes.end {
+        let rest = &input_bytes[offset..bytes.end];
+        let included = ranges
+            .iter()
+            .any(|range| range.start_byte <= offset && offset < range.end_byte);
+        let delimiter = CRITIC_MARKUP_DELIMITERS
+            .iter()
+            .find(|delimiter| included && rest.starts_with(delimiter.as_bytes()));
+        let token = match delimiter {
+            // an invalid shortcode ends at the first `>}}` before the next
+            // shortcode; without one, it's only the delimiter (see
+            // scanner.c)
+            None if included && rest.starts_with(b"{{<") => {
+                let after_open = &rest[3..];
+                let next_shortcode = after_open
+                    .windows(3)
+                    .position(|w| w == b"{{<")
+                    .unwrap_or(after_open.len());
+                let length = after_open[..next_shortcode]
+                    .windows(3)
+                    .position(|w| w == b">}}")
+                    .map_or(3, |i| i + 6);
+                let text: String = ranges
+                    .iter()
+                    .filter(|range| range.start_byte < offset + length && offset < range.end_byte)
+                    .map(|range| {
+                        String::from_utf8_lossy(
+                            &input_bytes[offset.max(range.start_byte)
+                                ..(offset + length).min(range.end_byte)],
+                        )
+                    })
+                    .collect();
+                Some(("invalid_shortcode", text, length))
+            }
+            Some(delimiter) => Some((
+                "critic_markup_delimiter",
+                delimiter.to_string(),
+                delimiter.len(),
+            )),
+            None if !included || rest[0].is_ascii_whitespace() => None,
+            None => return Vec::new(),
+        };
+        let length = token.as_ref().map_or(1, |(_, _, length)| *length);
+        let start = Location {
+            offset,
+            row: point.row,
+            column: point.column,
+        };
+        for &byte in &rest[..length] {
+            if byte == b'\n' {
+                point.row += 1;
+                point.column = 0;
+            } else {
+                point.column += 1;
+            }
+        }
+        offset += length;
+        if let Some((kind, text, _)) = token {
+            let end = Location {
+                offset,
+                row: point.row,
+                column: point.column,
+            };
+            tokens.push((
+                kind.to_string(),
+                PandocNativeIntermediate::IntermediateBaseText(text, Range { start, end }),
+            ));
+        }
+    }
+    tokens
+}
+
+fn is_str(inline: &Inline, text: &str) -> bool {
+    matches!(inline, Inline::Str(s) if s.text == text)
+}
+
+fn change_span(class: &str, content: Inlines) -> Inline {
+    Inline::Span(Span {
+        attr: (String::new(), vec![class.to_string()], IndexMap::new()),
+        content,
+    })
+}
+
+// Turns the CriticMarkup delimiters added by `with_critic_markup_delimiters`
+// into the spans Pandoc uses for tracked changes (see
+// transforms/track_changes.rs). `{++a++}` is an insertion, `{--a--}` a
+// deletion, `{~~a~>b~~}` a deletion followed by an insertion, and
+// `{>>a<<}` a comment. `{==a==}` is a `mark` span, and a comment right
+// after it comments on the highlighted text. A delimiter without a match in
+// the same list of inlines stays text.
+fn critic_markup_to_spans(pandoc: Pandoc) -> Pandoc {
+    let closing_delimiter = |inline: &Inline| match inline {
+        Inline::Str(s) => match s.text.as_str() {
+            "{++" => Some("++}"),
+            "{--" => Some("--}"),
+            "{~~" => Some("~~}"),
+            "{>>" => Some("<<}"),
+            "{==" => Some("==}"),
+            _ => None,
+        },
+        _ => None,
+    };
+    topdown_traverse(
+        pandoc,
+        &mut Filter::new().with_inlines(|inlines| {
+            if !inlines
+                .iter()
+                .any(|inline| closing_delimiter(inline).is_some())
+            {
+                return Unchanged(inlines);
+            }
+            let mut result: Inlines = Vec::with_capacity(inlines.len());
+            // the opening delimiter, its closing delimiter and the content
+            // read so far
+            let mut open: Option<(Inline, &str, Inlines)> = None;
+            // the index in `result` of the last highlight, which a comment
+            // right after it is about
+            let mut highlight: Option<usize> = None;
+            for inline in inlines {
+                let Some((opening, closing, mut content)) = open.take() else {
+                    match closing_delimiter(&inline) {
+                        Some(closing) => open = Some((inline, closing, Vec::new())),
+                        None => result.push(inline),
+                    }
+                    continue;
+                };
+                if !is_str(&inline, closing) {
+                    content.push(inline);
+                    open = Some((opening, closing, content));
+                    continue;
+                }
+                match closing {
+                    "++}" => result.push(change_span("insertion", content)),
+                    "--}" => result.push(change_span("deletion", content)),
+                    "~~}" => match content.iter().position(|inline| is_str(inline, "~>")) {
+                        Some(separator) => {
+                            let new = content.split_off(separator + 1);
+                            content.pop();
+                            result.push(change_span("deletion", content));
+                            result.push(change_span("insertion", new));
+                        }
+                        None => result.push(change_span("deletion", content)),
+                    },
+                    "==}" => {
+                        result.push(change_span("mark", content));
+                        highlight = Some(result.len() - 1);
+                    }
+                    _ => {
+                        let comment = change_span("comment-start", content);
+                        if highlight.is_some_and(|index| index + 1 == result.len()) {
+                            result.insert(result.len() - 1, comment);
+                            result.push(change_span("comment-end", Vec::new()));
+                        } else {
+                            result.push(comment);
+                        }
+                    }
+                }
+            }
+            if let Some((opening, _, content)) = open {
+                result.push(opening);
+                result.extend(content);
+            }
+            FilterResult(result, true)
+        }),
+    )
+}
+
+// The last line of a paragraph's text, without its block continuation.
+fn last_line_of<'a>(
+    node: &tree_sitter::Node,
+    input_bytes: &'a [u8],
+    inline_trees: &[InlineTree],
+) -> Option<&'a str> {
+    let mut cursor = node.walk();
+    let inline = node
+        .children(&mut cursor)
+        .find(|child| child.kind() == "inline")?;
+    let ranges = ranges_of(inline_trees, &inline);
+    let text = std::str::from_utf8(&input_bytes[..inline.end_byte()]).ok()?;
+    let text = text.trim_end();
+    let line_start = text.rfind('\n').map_or(0, |i| i + 1);
+    let range_start = ranges.last().map_or(0, |range| range.start_byte);
+    text.get(line_start.max(range_start)..)
+}
+
+// The heading level of a setext underline followed by attributes, like
+// `=====  {#id}`.
+fn setext_underline_level(line: &str) -> Option<usize> {
+    let (underline, attr) = line.trim().split_once('{')?;
+    if !attr.ends_with('}') {
+        return None;
+    }
+    match underline.trim_end() {
+        underline if !underline.is_empty() && underline.bytes().all(|b| b == b'=') => Some(1),
+        underline if !underline.is_empty() && underline.bytes().all(|b| b == b'-') => Some(2),
+        _ => None,
+    }
+}
+
+fn invalid_shortcode_warning(range: Range, missing_name: bool) -> Diagnostic {
+    let label = if missing_name {
+        "This shortcode has no name."
+    } else {
+        "Shortcode names must start with a letter or an underscore, and only have letters, digits, underscores and dashes."
+    };
+    Diagnostic::warning("Invalid shortcode", range, label)
+        .with_note("The shortcode is kept as text.")
+}
+
+// Splits literal source text into Str, Space and SoftBreak inlines,
+// the way a CommonMark reader would for plain text.
+fn literal_text_to_inlines(text: &str) -> Inlines {
+    let mut result: Inlines = Vec::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        result.push(Inline::Str(Str {
+            text: rest[..word_end].to_string(),
+            filename: None,
+            range: empty_range(),
+        }));
+        let after = rest[word_end..].trim_start();
+        let whitespace = &rest[word_end..rest.len() - after.len()];
+        if whitespace.contains('\n') {
+            result.push(Inline::SoftBreak(SoftBreak {
+                filename: None,
+                range: empty_range(),
+            }));
+        } else if !whitespace.is_empty() {
+            result.push(Inline::Space(Space {
+                filename: None,
+                range: empty_range(),
+            }));
+        }
+        rest = after;
+    }
+    result
+}
+
+// Like `literal_text_to_inlines`, but leading and trailing whitespace is
+// kept as a Space or SoftBreak, for text that sits between other inlines.
+fn source_text_to_inlines(text: &str) -> Inlines {
+    let whitespace = |text: &str| {
+        if text.contains('\n') {
+            Inline::SoftBreak(SoftBreak {
+                filename: None,
+                range: empty_range(),
+            })
+        } else {
+            Inline::Space(Space {
+                filename: None,
+                range: empty_range(),
+            })
+        }
+    };
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return if text.is_empty() {
+            Vec::new()
+        } else {
+            vec![whitespace(text)]
+        };
+    }
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+    let mut result = Vec::new();
+    if !leading.is_empty() {
+        result.push(whitespace(leading));
+    }
+    result.extend(literal_text_to_inlines(trimmed));
+    if !trailing.is_empty() {
+        result.push(whitespace(trailing));
+    }
+    result
+}
+
+fn is_attribute(kind: &str) -> bool {
+    matches!(
+        kind,
+        "commonmark_attribute" | "raw_attribute" | "language_attribute"
+    )
+}
+
+// Reads an extension's markup as CommonMark does: its inline content is
+// kept, and everything else in it (delimiters, attributes, citation keys,
+// ...) is its source text.
+fn markup_as_text(
+    node: &tree_sitter::Node,
+    children: Vec<(String, PandocNativeIntermediate)>,
+    input_bytes: &[u8],
+    inline_trees: &[InlineTree],
+    mut native_inline: impl FnMut((String, PandocNativeIntermediate)) -> Inlines,
+) -> PandocNativeIntermediate {
+    // the source text between `start` and `end`, without the block
+    // continuations (`> ` and the like) outside of the inline tree
+    let ranges = ranges_of(inline_trees, node);
+    let source = |start: usize, end: usize| {
+        let text: String = ranges
+            .iter()
+            .filter(|range| range.start_byte < end && start < range.end_byte)
+            .map(|range| {
+                String::from_utf8_lossy(
+                    &input_bytes[start.max(range.start_byte)..end.min(range.end_byte)],
+                )
+            })
+            .collect();
+        source_text_to_inlines(&text)
+    };
+    // CriticMarkup delimiters and invalid shortcodes are in the gaps, which
+    // are kept as text
+    let children = children
+        .into_iter()
+        .filter(|(kind, _)| kind != "critic_markup_delimiter" && kind != "invalid_shortcode");
+    let mut result = Vec::new();
+    let mut offset = node.start_byte();
+    let mut cursor = node.walk();
+    for (child_node, (kind, child)) in node.children(&mut cursor).zip(children) {
+        result.extend(source(offset, child_node.start_byte()));
+        let is_content = match child {
+            PandocNativeIntermediate::IntermediateInline(_)
+            | PandocNativeIntermediate::IntermediateInlines(_) => true,
+            // escapes and references are text too, but read as such
+            PandocNativeIntermediate::IntermediateBaseText(_, _) => matches!(
+                kind.as_str(),
+                "backslash_escape" | "entity_reference" | "numeric_character_reference"
+            ),
+            _ => false,
+        };
+        if is_content {
+            result.extend(native_inline((kind, child)));
+        } else {
+            result.extend(source(child_node.start_byte(), child_node.end_byte()));
+        }
+        offset = child_node.end_byte();
+    }
+    result.extend(source(offset, node.end_byte()));
+    PandocNativeIntermediate::IntermediateInlines(result)
+}
+
+// In CommonMark, fenced div markers are just paragraph text. This replaces
+// every Div with its content, turning the fences back into literal text
+// that joins the adjacent paragraph when there's no blank line in between.
+fn fenced_divs_to_commonmark(doc: Pandoc, input_bytes: &[u8]) -> Pandoc {
+    let mut filter = Filter::new().with_div(|div| {
+        let source =
+            String::from_utf8_lossy(&input_bytes[div.range.start.offset..div.range.end.offset]);
+        let start_row = div.range.start.row;
+        let opening_fence = source.lines().next().unwrap_or("").trim();
+        let closing_fence = source
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .last()
+            .filter(|(i, line)| *i > 0 && line.trim_start().starts_with(":::"))
+            .map(|(i, line)| (start_row + i, line.trim()));
+
+        let mut blocks = div.content;
+        let fence_paragraph = |text: &str| {
+            Block::Paragraph(Paragraph {
+                content: literal_text_to_inlines(text),
+                filename: None,
+                range: empty_range(),
+            })
+        };
+        let soft_break = || {
+            Inline::SoftBreak(SoftBreak {
+                filename: None,
+                range: empty_range(),
+            })
+        };
+
+        match blocks.first_mut() {
+            Some(Block::Paragraph(para)) if para.range.start.row == start_row + 1 => {
+                let mut content = literal_text_to_inlines(opening_fence);
+                content.push(soft_break());
+                content.append(&mut para.content);
+                para.content = content;
+            }
+            _ => blocks.insert(0, fence_paragraph(opening_fence)),
+        }
+        if let Some((closing_row, closing_fence)) = closing_fence {
+            match blocks.last_mut() {
+                Some(Block::Paragraph(para))
+                    if para.range.start.row > start_row
+                        && last_content_row(&para.range) + 1 == closing_row =>
+                {
+                    para.content.push(soft_break());
+                    para.content.extend(literal_text_to_inlines(closing_fence));
+                }
+                _ => blocks.push(fence_paragraph(closing_fence)),
+            }
+        }
+        FilterResult(blocks, true)
+    });
+    topdown_traverse(doc, &mut filter)
+}
+
+fn last_content_row(range: &Range) -> usize {
+    if range.end.column == 0 && range.end.row > range.start.row {
+        range.end.row - 1
+    } else {
+        range.end.row
+    }
+}
+
+// Converts the tree, reporting errors as one-line messages. Warnings are
+// written to `buf`.
 pub fn treesitter_to_pandoc<T: Write>(
     buf: &mut T,
     tree: &tree_sitter_qmd::MarkdownTree,
     input_bytes: &[u8],
 ) -> Result<Pandoc, Vec<String>> {
+    match treesitter_to_pandoc_with_options(
+        buf,
+        tree,
+        input_bytes,
+        &MarkdownParserOptions::default(),
+    ) {
+        Ok((pandoc, warnings)) => {
+            for warning in warnings {
+                writeln!(buf, "{}", warning.message()).unwrap();
+            }
+            Ok(pandoc)
+        }
+        Err(diagnostics) => Err(diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.is_error())
+            .map(Diagnostic::message)
+            .collect()),
+    }
+}
+
+// Converts the tree, returning the document with the warnings about it,
+// or, if there are errors, every diagnostic found.
+pub fn treesitter_to_pandoc_with_options<T: Write>(
+    buf: &mut T,
+    tree: &tree_sitter_qmd::MarkdownTree,
+    input_bytes: &[u8],
+    options: &MarkdownParserOptions,
+) -> Result<(Pandoc, Vec<Diagnostic>), Vec<Diagnostic>> {
+    // ERROR and MISSING nodes have no sensible conversion, so report them
+    // instead of letting them turn into leftovers
+    let diagnostics = collect_diagnostics(tree, input_bytes);
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+    let mut warnings = Vec::new();
+    let inline_trees: Vec<InlineTree> = tree.inline_trees().iter().map(InlineTree::new).collect();
     let result = bottomup_traverse_concrete_tree(
         &mut tree.walk(),
-        &mut |node, children, input_bytes| native_visitor(buf, node, children, input_bytes),
+        &mut |node, children, input_bytes| {
+            native_visitor(
+                buf,
+                node,
+                children,
+                input_bytes,
+                options,
+                &mut warnings,
+                &inline_trees,
+            )
+        },
         &input_bytes,
     );
     let (_, PandocNativeIntermediate::IntermediatePandoc(pandoc)) = result else {
         panic!("Expected Pandoc, got {:?}", result)
     };
-    let result = desugar(pandoc)?;
-    Ok(merge_strs(result))
+    let pandoc = if options.commonmark_only {
+        fenced_divs_to_commonmark(pandoc, input_bytes)
+    } else {
+        critic_markup_to_spans(pandoc)
+    };
+    let result = desugar(pandoc, options.legacy_figures)?;
+    // smart punctuation is an extension too
+    let smart = !options.no_smart && !options.commonmark_only;
+    Ok((merge_strs(result, smart), warnings))
+}
+
+// Runs the bottom-up traversal and records, in post-order, the intermediate
+// each concrete node was converted to. This is the most direct way to see why
+// a node ended up as IntermediateUnknown or a leftover.
+pub fn treesitter_to_intermediate_dump(
+    tree: &tree_sitter_qmd::MarkdownTree,
+    input_bytes: &[u8],
+    options: &MarkdownParserOptions,
+) -> Value {
+    let mut entries: Vec<Value> = Vec::new();
+    let inline_trees: Vec<InlineTree> = tree.inline_trees().iter().map(InlineTree::new).collect();
+    bottomup_traverse_concrete_tree(
+        &mut tree.walk(),
+        &mut |node, children, input_bytes| {
+            let result = native_visitor(
+                &mut std::io::sink(),
+                node,
+                children,
+                input_bytes,
+                options,
+                &mut Vec::new(),
+                &inline_trees,
+            );
+            let range = node_location(node);
+            entries.push(json!({
+                "kind": node.kind(),
+                "start": [range.start.row, range.start.column],
+                "end": [range.end.row, range.end.column],
+                "intermediate": result.to_debug_json(),
+            }));
+            result
+        },
+        input_bytes,
+    );
+    Value::Array(entries)
 }
@@ -3,4 +3,5 @@
  * Copyright (c) 2025 Posit, PBC
  */
 
+pub mod native;
 pub mod qmd;
@@ -0,0 +1,755 @@
+/*
+ * native.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+// A reader for Pandoc's native format: the Haskell `show` output of the
+// AST that `pandoc -t native` writes, and that our native writer writes.
+//
+// The input is either a list of blocks (`[ Para [Str "x"] ]`), or a whole
+// document (`Pandoc Meta { unMeta = fromList [] } [ ... ]`) as written by
+// `pandoc -t native -s`. Constructors that our AST can't represent are
+// reported as errors.
+
+use crate::pandoc::attr::empty_attr;
+use crate::pandoc::location::empty_range;
+use crate::pandoc::table::{Cell, ColSpec, Row, TableBody, TableFoot, TableHead};
+use crate::pandoc::{
+    Alignment, Attr, Block, BlockQuote, Blocks, BulletList, Caption, Citation, CitationMode, Cite,
+    Code, CodeBlock, ColWidth, DefinitionList, Div, Emph, Figure, Header, HorizontalRule, Image,
+    Inline, Inlines, LineBlock, LineBreak, Link, ListAttributes, ListNumberDelim, ListNumberStyle,
+    Math, MathType, Meta, MetaValue, Note, OrderedList, Pandoc, Paragraph, Plain, QuoteType,
+    Quoted, RawBlock, RawInline, SmallCaps, SoftBreak, Space, Span, Str, Strikeout, Strong,
+    Subscript, Superscript, Table, Underline,
+};
+use crate::utils::encoding::check_utf8;
+
+type ParseResult<T> = Result<T, String>;
+
+pub fn read(input_bytes: &[u8]) -> Result<Pandoc, Vec<String>> {
+    check_utf8(input_bytes).map_err(|e| vec![format!("Error: {}", e)])?;
+    let input = std::str::from_utf8(input_bytes).unwrap();
+    let mut parser = NativeParser { input, pos: 0 };
+    parser.document().map_err(|e| vec![e])
+}
+
+struct NativeParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> NativeParser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn error(&self, message: &str) -> String {
+        let before = &self.input[..self.pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+        format!(
+            "Error: {} at line {}, column {} of the native input",
+            message, line, column
+        )
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        if self.peek() == Some(ch) {
+            self.pos += ch.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, ch: char) -> ParseResult<()> {
+        if self.eat(ch) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", ch)))
+        }
+    }
+
+    fn identifier(&mut self) -> ParseResult<&'a str> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '\''))
+            .unwrap_or(rest.len());
+        if len == 0 || !rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return Err(self.error("expected a constructor"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn keyword(&mut self, expected: &str) -> ParseResult<()> {
+        let start = self.pos;
+        let name = self.identifier()?;
+        if name != expected {
+            self.pos = start;
+            return Err(self.error(&format!("expected `{}`, found `{}`", expected, name)));
+        }
+        Ok(())
+    }
+
+    fn number_text(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == 'e'))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn usize(&mut self) -> ParseResult<usize> {
+        let start = self.pos;
+        let text = self.number_text();
+        text.parse().map_err(|_| {
+            self.pos = start;
+            self.error("expected a number")
+        })
+    }
+
+    fn f64(&mut self) -> ParseResult<f64> {
+        let start = self.pos;
+        let text = self.number_text();
+        text.parse().map_err(|_| {
+            self.pos = start;
+            self.error("expected a number")
+        })
+    }
+
+    // A Haskell string literal, with the escapes `show` produces.
+    fn string(&mut self) -> ParseResult<String> {
+        self.expect('"')?;
+        let mut result = String::new();
+        let mut chars = self.rest().char_indices();
+        loop {
+            let Some((i, ch)) = chars.next() else {
+                self.pos = self.input.len();
+                return Err(self.error("unterminated string"));
+            };
+            match ch {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(result);
+                }
+                '\\' => {
+                    let Some((j, escape)) = chars.next() else {
+                        continue;
+                    };
+                    match escape {
+                        '\\' | '"' | '\'' => result.push(escape),
+                        'n' => result.push('\n'),
+                        't' => result.push('\t'),
+                        'r' => result.push('\r'),
+                        'a' => result.push('\u{7}'),
+                        'b' => result.push('\u{8}'),
+                        'f' => result.push('\u{c}'),
+                        'v' => result.push('\u{b}'),
+                        // separates a numeric escape from a following digit
+                        '&' => {}
+                        _ => {
+                            let (radix, digits_start) = match escape {
+                                'x' => (16, j + 1),
+                                'o' => (8, j + 1),
+                                _ => (10, j),
+                            };
+                            let digits: String = self.rest()[digits_start..]
+                                .chars()
+                                .take_while(|c| c.is_digit(radix))
+                                .collect();
+                            let code = u32::from_str_radix(&digits, radix).ok();
+                            let Some(decoded) = code.and_then(char::from_u32) else {
+                                self.pos += i;
+                                return Err(self.error("unsupported escape in string"));
+                            };
+                            result.push(decoded);
+                            // skip the digits after the first one, which
+                            // the iterator has already consumed for decimal
+                            let skip = digits.len() - if radix == 10 { 1 } else { 0 };
+                            for _ in 0..skip {
+                                chars.next();
+                            }
+                        }
+                    }
+                }
+                _ => result.push(ch),
+            }
+        }
+    }
+
+    fn list<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<Vec<T>> {
+        self.expect('[')?;
+        let mut result = Vec::new();
+        if self.eat(']') {
+            return Ok(result);
+        }
+        loop {
+            result.push(item(self)?);
+            if self.eat(']') {
+                return Ok(result);
+            }
+            if !self.eat(',') {
+                return Err(self.error("expected `,` or `]`"));
+            }
+        }
+    }
+
+    // Constructor applications may be wrapped in parentheses, as they are
+    // in argument position (`(Format "html")`, `(TableHead ...)`).
+    fn parenthesized<T>(
+        &mut self,
+        mut value: impl FnMut(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<T> {
+        if self.eat('(') {
+            let result = value(self)?;
+            self.expect(')')?;
+            Ok(result)
+        } else {
+            value(self)
+        }
+    }
+
+    fn document(&mut self) -> ParseResult<Pandoc> {
+        let pandoc = if self.peek() == Some('[') {
+            Pandoc {
+                meta: Meta::default(),
+                blocks: self.blocks()?,
+            }
+        } else {
+            self.keyword("Pandoc")?;
+            let meta = self.meta()?;
+            let blocks = self.blocks()?;
+            Pandoc { meta, blocks }
+        };
+        if self.peek().is_some() {
+            return Err(self.error("unexpected input after the document"));
+        }
+        Ok(pandoc)
+    }
+
+    fn meta(&mut self) -> ParseResult<Meta> {
+        self.parenthesized(|p| {
+            p.keyword("Meta")?;
+            p.expect('{')?;
+            p.keyword("unMeta")?;
+            p.expect('=')?;
+            let meta = p.meta_map()?;
+            p.expect('}')?;
+            Ok(meta)
+        })
+    }
+
+    fn meta_map(&mut self) -> ParseResult<Meta> {
+        self.keyword("fromList")?;
+        let entries = self.list(|p| {
+            p.expect('(')?;
+            let key = p.string()?;
+            p.expect(',')?;
+            let value = p.meta_value()?;
+            p.expect(')')?;
+            Ok((key, value))
+        })?;
+        Ok(entries.into_iter().collect())
+    }
+
+    fn meta_value(&mut self) -> ParseResult<MetaValue> {
+        self.parenthesized(|p| {
+            let start = p.pos;
+            match p.identifier()? {
+                "MetaString" => Ok(MetaValue::MetaString(p.string()?)),
+                "MetaBool" => match p.identifier()? {
+                    "True" => Ok(MetaValue::MetaBool(true)),
+                    "False" => Ok(MetaValue::MetaBool(false)),
+                    _ => Err(p.error("expected `True` or `False`")),
+                },
+                "MetaInlines" => Ok(MetaValue::MetaInlines(p.inlines()?)),
+                "MetaBlocks" => Ok(MetaValue::MetaBlocks(p.blocks()?)),
+                "MetaList" => Ok(MetaValue::MetaList(p.list(Self::meta_value)?)),
+                "MetaMap" => Ok(MetaValue::MetaMap(p.parenthesized(Self::meta_map)?)),
+                name => {
+                    p.pos = start;
+                    Err(p.error(&format!("unsupported metadata value `{}`", name)))
+                }
+            }
+        })
+    }
+
+    fn attr(&mut self) -> ParseResult<Attr> {
+        self.expect('(')?;
+        let id = self.string()?;
+        self.expect(',')?;
+        let classes = self.list(Self::string)?;
+        self.expect(',')?;
+        let pairs = self.list(|p| {
+            p.expect('(')?;
+            let key = p.string()?;
+            p.expect(',')?;
+            let value = p.string()?;
+            p.expect(')')?;
+            Ok((key, value))
+        })?;
+        self.expect(')')?;
+        let mut attr = empty_attr();
+        attr.0 = id;
+        attr.1 = classes;
+        attr.2 = pairs.into_iter().collect();
+        Ok(attr)
+    }
+
+    fn target(&mut self) -> ParseResult<(String, String)> {
+        self.expect('(')?;
+        let url = self.string()?;
+        self.expect(',')?;
+        let title = self.string()?;
+        self.expect(')')?;
+        Ok((url, title))
+    }
+
+    fn format(&mut self) -> ParseResult<String> {
+        self.parenthesized(|p| {
+            p.keyword("Format")?;
+            p.string()
+        })
+    }
+
+    fn inlines(&mut self) -> ParseResult<Inlines> {
+        self.list(Self::inline)
+    }
+
+    fn blocks(&mut self) -> ParseResult<Blocks> {
+        self.list(Self::block)
+    }
+
+    fn inline(&mut self) -> ParseResult<Inline> {
+        self.parenthesized(|p| {
+            let start = p.pos;
+            let inline = match p.identifier()? {
+                "Str" => Inline::Str(Str {
+                    text: p.string()?,
+                    filename: None,
+                    range: empty_range(),
+                }),
+                "Space" => Inline::Space(Space {
+                    filename: None,
+                    range: empty_range(),
+                }),
+                "SoftBreak" => Inline::SoftBreak(SoftBreak {
+                    filename: None,
+                    range: empty_range(),
+                }),
+                "LineBreak" => Inline::LineBreak(LineBreak {
+                    filename: None,
+                    range: empty_range(),
+                }),
+                "Emph" => Emph {
+                    content: p.inlines()?,
+                }
+                .into(),
+                "Underline" => Underline {
+                    content: p.inlines()?,
+                }
+                .into(),
+                "Strong" => Strong {
+                    content: p.inlines()?,
+                }
+                .into(),
+                "Strikeout" => Strikeout {
+                    content: p.inlines()?,
+                }
+                .into(),
+                "Superscript" => Superscript {
+                    content: p.inlines()?,
+                }
+                .into(),
+                "Subscript" => Subscript {
+                    content: p.inlines()?,
+                }
+                .into(),
+                "SmallCaps" => SmallCaps {
+                    content: p.inlines()?,
+                }
+                .into(),
+                "Quoted" => {
+                    let quote_type = match p.identifier()? {
+                        "SingleQuote" => QuoteType::SingleQuote,
+                        "DoubleQuote" => QuoteType::DoubleQuote,
+                        _ => return Err(p.error("expected a quote type")),
+                    };
+                    Quoted {
+                        quote_type,
+                        content: p.inlines()?,
+                    }
+                    .into()
+                }
+                "Cite" => Cite {
+                    citations: p.list(Self::citation)?,
+                    content: p.inlines()?,
+                }
+                .into(),
+                "Code" => Code {
+                    attr: p.attr()?,
+                    text: p.string()?,
+                }
+                .into(),
+                "Math" => {
+                    let math_type = match p.identifier()? {
+                        "InlineMath" => MathType::InlineMath,
+                        "DisplayMath" => MathType::DisplayMath,
+                        _ => return Err(p.error("expected a math type")),
+                    };
+                    Math {
+                        math_type,
+                        text: p.string()?,
+                    }
+                    .into()
+                }
+                "RawInline" => RawInline {
+                    format: p.format()?,
+                    text: p.string()?,
+                }
+                .into(),
+                "Link" => Link {
+                    attr: p.attr()?,
+                    content: p.inlines()?,
+                    target: p.target()?,
+                }
+                .into(),
+                "Image" => Image {
+                    attr: p.attr()?,
+                    content: p.inlines()?,
+                    target: p.target()?,
+                }
+                .into(),
+                "Note" => Note {
+                    content: p.blocks()?,
+                }
+                .into(),
+                "Span" => Span {
+                    attr: p.attr()?,
+                    content: p.inlines()?,
+                }
+                .into(),
+                name => {
+                    p.pos = start;
+                    return Err(p.error(&format!("unsupported inline `{}`", name)));
+                }
+            };
+            Ok(inline)
+        })
+    }
+
+    fn citation(&mut self) -> ParseResult<Citation> {
+        self.parenthesized(|p| {
+            p.keyword("Citation")?;
+            p.expect('{')?;
+            let mut citation = Citation {
+                id: String::new(),
+                prefix: vec![],
+                suffix: vec![],
+                mode: CitationMode::NormalCitation,
+                note_num: 0,
+                hash: 0,
+            };
+            loop {
+                let start = p.pos;
+                let field = p.identifier()?;
+                p.expect('=')?;
+                match field {
+                    "citationId" => citation.id = p.string()?,
+                    "citationPrefix" => citation.prefix = p.inlines()?,
+                    "citationSuffix" => citation.suffix = p.inlines()?,
+                    "citationMode" => {
+                        citation.mode = match p.identifier()? {
+                            "NormalCitation" => CitationMode::NormalCitation,
+                            "SuppressAuthor" => CitationMode::SuppressAuthor,
+                            "AuthorInText" => CitationMode::AuthorInText,
+                            _ => return Err(p.error("expected a citation mode")),
+                        }
+                    }
+                    "citationNoteNum" => citation.note_num = p.usize()?,
+                    "citationHash" => citation.hash = p.usize()?,
+                    _ => {
+                        p.pos = start;
+                        return Err(p.error(&format!("unknown citation field `{}`", field)));
+                    }
+                }
+                if p.eat('}') {
+                    return Ok(citation);
+                }
+                p.expect(',')?;
+            }
+        })
+    }
+
+    fn block(&mut self) -> ParseResult<Block> {
+        self.parenthesized(|p| {
+            let start = p.pos;
+            let block = match p.identifier()? {
+                "Plain" => Plain {
+                    content: p.inlines()?,
+                    filename: None,
+                    range: empty_range(),
+                }
+                .into(),
+                "Para" => Paragraph {
+                    content: p.inlines()?,
+                    filename: None,
+                    range: empty_range(),
+                }
+                .into(),
+                "LineBlock" => LineBlock {
+                    content: p.list(Self::inlines)?,
+                    filename: None,
+                    range: empty_range(),
+                }
+                .into(),
+                "CodeBlock" => CodeBlock {
+                    attr: p.attr()?,
+                    text: p.string()?,
+                    filename: None,
+                    range: empty_range(),
+                }
+                .into(),
+                "RawBlock" => RawBlock {
+                    format: p.format()?,
+                    text: p.string()?,
+                    filename: None,
+                    range: empty_range(),
+                }
+                .into(),
+                "BlockQuote" => BlockQuote {
+                    content: p.blocks()?,
+                    filename: None,
+                    range: empty_range(),
+                }
+                .into(),
+                "OrderedList" => OrderedList {
+                    attr: p.list_attributes()?,
+                    content: p.list(Self::blocks)?,
+                    filename: None,
+                    range: empty_range(),
+                }
+                .into(),
+                "BulletList" => BulletList {
+                    content: p.list(Self::blocks)?,
+                    filename: None,
+                    range: empty_range(),
+                }
+                .into(),
+                "DefinitionList" => DefinitionList {
+                    content: p.list(|p| {
+                        p.expect('(')?;
+                        let term = p.inlines()?;
+                        p.expect(',')?;
+                        let definitions = p.list(Self::blocks)?;
+                        p.expect(')')?;
+                        Ok((term, definitions))
+                    })?,
+                    filename: None,
+                    range: empty_range(),
+                }
+                .into(),
+                "Header" => Header {
+                    level: p.usize()?,
+                    attr: p.attr()?,
+                    content: p.inlines()?,
+                    filename: None,
+                    range: empty_range(),
+                }
+                .into(),
+                "HorizontalRule" => HorizontalRule {
+                    filename: None,
+                    range: empty_range(),
+                }
+                .into(),
+                "Table" => p.table()?.into(),
+                "Figure" => Figure {
+                    attr: p.attr()?,
+                    caption: p.caption()?,
+                    content: p.blocks()?,
+                    filename: None,
+                    range: empty_range(),
+                }
+                .into(),
+                "Div" => Div {
+                    attr: p.attr()?,
+                    content: p.blocks()?,
+                    filename: None,
+                    range: empty_range(),
+                }
+                .into(),
+                name => {
+                    p.pos = start;
+                    return Err(p.error(&format!("unsupported block `{}`", name)));
+                }
+            };
+            Ok(block)
+        })
+    }
+
+    fn list_attributes(&mut self) -> ParseResult<ListAttributes> {
+        self.expect('(')?;
+        let start = self.usize()?;
+        self.expect(',')?;
+        let style = match self.identifier()? {
+            "DefaultStyle" => ListNumberStyle::Default,
+            "Decimal" => ListNumberStyle::Decimal,
+            "LowerRoman" => ListNumberStyle::LowerRoman,
+            "UpperRoman" => ListNumberStyle::UpperRoman,
+            "LowerAlpha" => ListNumberStyle::LowerAlpha,
+            "UpperAlpha" => ListNumberStyle::UpperAlpha,
+            _ => return Err(self.error("unsupported list number style")),
+        };
+        self.expect(',')?;
+        let delim = match self.identifier()? {
+            "DefaultDelim" => ListNumberDelim::Default,
+            "Period" => ListNumberDelim::Period,
+            "OneParen" => ListNumberDelim::OneParen,
+            "TwoParens" => ListNumberDelim::TwoParens,
+            _ => return Err(self.error("unsupported list number delimiter")),
+        };
+        self.expect(')')?;
+        Ok((start, style, delim))
+    }
+
+    fn caption(&mut self) -> ParseResult<Caption> {
+        self.parenthesized(|p| {
+            p.keyword("Caption")?;
+            let short = if p.peek() == Some('[') {
+                Some(p.inlines()?)
+            } else {
+                match p.identifier()? {
+                    "Nothing" => None,
+                    "Just" => Some(p.parenthesized(Self::inlines)?),
+                    _ => return Err(p.error("expected `Nothing` or `Just`")),
+                }
+            };
+            let long = p.blocks()?;
+            Ok(Caption {
+                short,
+                long: if long.is_empty() { None } else { Some(long) },
+            })
+        })
+    }
+
+    fn alignment(&mut self) -> ParseResult<Alignment> {
+        match self.identifier()? {
+            "AlignLeft" => Ok(Alignment::Left),
+            "AlignRight" => Ok(Alignment::Right),
+            "AlignCenter" => Ok(Alignment::Center),
+            "AlignDefault" => Ok(Alignment::Default),
+            _ => Err(self.error("expected an alignment")),
+        }
+    }
+
+    fn colspec(&mut self) -> ParseResult<ColSpec> {
+        self.expect('(')?;
+        let alignment = self.alignment()?;
+        self.expect(',')?;
+        let width = self.parenthesized(|p| match p.identifier()? {
+            "ColWidthDefault" => Ok(ColWidth::Default),
+            "ColWidth" => Ok(ColWidth::Percentage(p.f64()?)),
+            _ => Err(p.error("expected a column width")),
+        })?;
+        self.expect(')')?;
+        Ok((alignment, width))
+    }
+
+    fn rows(&mut self) -> ParseResult<Vec<Row>> {
+        self.list(|p| {
+            p.parenthesized(|p| {
+                p.keyword("Row")?;
+                Ok(Row {
+                    attr: p.attr()?,
+                    cells: p.list(Self::cell)?,
+                })
+            })
+        })
+    }
+
+    fn cell(&mut self) -> ParseResult<Cell> {
+        self.parenthesized(|p| {
+            p.keyword("Cell")?;
+            let attr = p.attr()?;
+            let alignment = p.alignment()?;
+            let row_span = p.parenthesized(|p| {
+                p.keyword("RowSpan")?;
+                p.usize()
+            })?;
+            let col_span = p.parenthesized(|p| {
+                p.keyword("ColSpan")?;
+                p.usize()
+            })?;
+            Ok(Cell {
+                attr,
+                alignment,
+                row_span,
+                col_span,
+                content: p.blocks()?,
+            })
+        })
+    }
+
+    fn table(&mut self) -> ParseResult<Table> {
+        let attr = self.attr()?;
+        let caption = self.caption()?;
+        let colspec = self.list(Self::colspec)?;
+        let head = self.parenthesized(|p| {
+            p.keyword("TableHead")?;
+            Ok(TableHead {
+                attr: p.attr()?,
+                rows: p.rows()?,
+            })
+        })?;
+        let bodies = self.list(|p| {
+            p.parenthesized(|p| {
+                p.keyword("TableBody")?;
+                let attr = p.attr()?;
+                let rowhead_columns = p.parenthesized(|p| {
+                    p.keyword("RowHeadColumns")?;
+                    p.usize()
+                })?;
+                Ok(TableBody {
+                    attr,
+                    rowhead_columns,
+                    head: p.rows()?,
+                    body: p.rows()?,
+                })
+            })
+        })?;
+        let foot = self.parenthesized(|p| {
+            p.keyword("TableFoot")?;
+            Ok(TableFoot {
+                attr: p.attr()?,
+                rows: p.rows()?,
+            })
+        })?;
+        Ok(Table {
+            attr,
+            caption,
+            colspec,
+            head,
+            bodies,
+            foot,
+            filename: None,
+            range: empty_range(),
+        })
+    }
+}
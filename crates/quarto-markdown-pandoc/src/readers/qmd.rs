@@ -1,5 +1,3 @@
-use crate::errors;
-use crate::errors::parse_is_good;
 use crate::filters::FilterReturn::Unchanged;
 use crate::filters::topdown_traverse;
 use crate::filters::{Filter, FilterReturn};
@@ -8,6 +6,12 @@ use crate::pandoc::meta::parse_metadata_strings;
 use crate::pandoc::{self, Block, Meta};
 use crate::pandoc::{MetaValue, rawblock_to_meta};
 use crate::traversals;
+use crate::utils::content_hash::content_hash;
+use crate::utils::diagnostics::Diagnostic;
+use crate::utils::encoding::check_utf8;
+use crate::utils::line_ending::normalize;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::io::Write;
 // use tree_sitter::LogType;
 use tree_sitter_qmd::MarkdownParser;
@@ -25,12 +29,91 @@ fn print_whole_tree<T: Write>(cursor: &mut tree_sitter_qmd::MarkdownCursor, buf:
     });
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownParserOptions {
+    // Parse as plain CommonMark: shortcodes, citations, math, inline
+    // notes, super- and subscripts, strikeouts, bracketed spans and
+    // attributes (including raw `{=format}` ones) stay literal text, fenced
+    // div markers (and so callouts) become paragraph text, and there's no
+    // smart punctuation. Front matter, pipe tables and footnotes, which
+    // GitHub-flavored Markdown has too, are still read.
+    pub commonmark_only: bool,
+    // Keep each metadata block in the document as the `quarto_minus_metadata`
+    // RawBlock it was captured as, next to the parsed metadata. This is
+    // for debugging the metadata parser.
+    pub keep_meta_raw: bool,
+    // Keep quotes, dashes and ellipses as they are written, instead of
+    // reading them as `Quoted` and typographic punctuation.
+    pub no_smart: bool,
+    // Read a paragraph holding only a captioned image as Pandoc did before
+    // 3.0: a `Para` with the image, its title prefixed with `fig:`, instead
+    // of a `Figure`.
+    pub legacy_figures: bool,
+}
+
+fn parser_for(options: &MarkdownParserOptions) -> MarkdownParser {
+    if options.no_smart || options.commonmark_only {
+        MarkdownParser::without_smart_quotes()
+    } else {
+        MarkdownParser::default()
+    }
+}
+
+// The intermediate each concrete node of the document is converted to, as
+// `read_with_options` would convert it (see
+// `treesitter::treesitter_to_intermediate_dump`).
+pub fn dump_intermediate(input: &str, options: &MarkdownParserOptions) -> serde_json::Value {
+    let input = normalize(input);
+    let tree = parser_for(options)
+        .parse(input.as_bytes(), None)
+        .expect("Failed to parse input");
+    pandoc::treesitter::treesitter_to_intermediate_dump(&tree, input.as_bytes(), options)
+}
+
+// Reads the document, reporting errors as one-line messages. Warnings are
+// written to `output_stream`.
 pub fn read<T: Write>(
     input_bytes: &[u8],
-    mut output_stream: &mut T,
+    output_stream: &mut T,
 ) -> Result<pandoc::Pandoc, Vec<String>> {
-    let mut parser = MarkdownParser::default();
-    let mut error_messages: Vec<String> = Vec::new();
+    match read_with_options(
+        input_bytes,
+        &MarkdownParserOptions::default(),
+        output_stream,
+    ) {
+        Ok((doc, warnings)) => {
+            for warning in warnings {
+                writeln!(output_stream, "{}", warning.message()).unwrap();
+            }
+            Ok(doc)
+        }
+        Err(diagnostics) => Err(diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.is_error())
+            .map(Diagnostic::message)
+            .collect()),
+    }
+}
+
+// Reads the document, returning it with the warnings about it, or, if
+// there are errors, every diagnostic found.
+pub fn read_with_options<T: Write>(
+    input_bytes: &[u8],
+    options: &MarkdownParserOptions,
+    mut output_stream: &mut T,
+) -> Result<(pandoc::Pandoc, Vec<Diagnostic>), Vec<Diagnostic>> {
+    check_utf8(input_bytes).map_err(|e| vec![Diagnostic::unlocated(e)])?;
+    // The document is read with `\n` line endings, so source ranges are
+    // relative to the normalized input. For `\r\n` input, rows and columns
+    // are the same as in the original; only offsets differ.
+    let input = normalize(std::str::from_utf8(input_bytes).unwrap());
+    let input_bytes = input.as_bytes();
+    // the grammar needs at least one line to build a document from, and
+    // reads indented blank lines as code, so blank input is handled here
+    if input_bytes.iter().all(u8::is_ascii_whitespace) {
+        return Ok((pandoc::Pandoc::default(), Vec::new()));
+    }
+    let mut parser = parser_for(options);
     // let mut found_error: bool = false;
 
     // parser
@@ -53,27 +136,16 @@ pub fn read<T: Write>(
     // this is here mostly to prevent our fuzzer from blowing the stack
     // with a deeply nested document
     if depth > 100 {
-        error_messages.push(format!(
+        return Err(vec![Diagnostic::unlocated(format!(
             "The input document is too deeply nested (max depth: {} > 100).",
             depth
-        ));
-        return Err(error_messages);
+        ))]);
     }
 
-    let errors = parse_is_good(&tree);
     print_whole_tree(&mut tree.walk(), &mut output_stream);
-    if !errors.is_empty() {
-        let mut cursor = tree.walk();
-        for error in errors {
-            cursor.goto_id(error);
-            error_messages.push(errors::error_message(&mut cursor, &input_bytes));
-        }
-    }
-    if !error_messages.is_empty() {
-        return Err(error_messages);
-    }
 
-    let mut result = pandoc::treesitter_to_pandoc(&mut output_stream, &tree, &input_bytes)?;
+    let (mut result, warnings) =
+        pandoc::treesitter_to_pandoc_with_options(&mut output_stream, &tree, input_bytes, options)?;
     let mut meta_from_parses = Meta::default();
 
     result = {
@@ -83,6 +155,10 @@ pub fn read<T: Write>(
             }
             let filename = rb.filename.clone();
             let range = rb.range.clone();
+            let mut kept: Vec<Block> = Vec::new();
+            if options.keep_meta_raw {
+                kept.push(Block::RawBlock(rb.clone()));
+            }
             let result = rawblock_to_meta(rb);
             let is_lexical = {
                 let val = result.get("_scope");
@@ -101,14 +177,11 @@ pub fn read<T: Write>(
                 for (k, v) in inner_meta_from_parses {
                     meta_map.insert(k, v);
                 }
-                return FilterReturn::FilterResult(
-                    vec![Block::BlockMetadata(MetaBlock {
-                        meta: meta_map,
-                        filename,
-                        range,
-                    })],
-                    false,
-                );
+                kept.push(Block::BlockMetadata(MetaBlock {
+                    meta: meta_map,
+                    filename,
+                    range,
+                }));
             } else {
                 let meta_map =
                     match parse_metadata_strings(MetaValue::MetaMap(result), &mut meta_from_parses)
@@ -119,13 +192,100 @@ pub fn read<T: Write>(
                 for (k, v) in meta_map {
                     meta_from_parses.insert(k, v);
                 }
-                return FilterReturn::FilterResult(vec![], false);
             }
+            FilterReturn::FilterResult(kept, false)
         });
         topdown_traverse(result, &mut filter)
     };
     for (k, v) in meta_from_parses.into_iter() {
         result.meta.insert(k, v);
     }
-    Ok(result)
+    Ok((result, warnings))
+}
+
+// Memoizes the results of `read`, keyed by the content hash of the input
+// after its line endings are normalized.
+//
+// Identical inputs share an entry regardless of where their bytes live,
+// and so do inputs that differ only in their line endings. Each entry
+// keeps the normalized input it was made from, and a hit is only
+// answered from the cache when that input matches: a hash collision (see
+// `content_hash` for how unlikely that is) costs a parse, never a wrong
+// document. The entry also keeps what `read` wrote to its output stream,
+// so a hit reports the same warnings as the read it stands in for.
+#[derive(Default)]
+pub struct ParseCache {
+    entries: HashMap<u64, CacheEntry>,
+    hits: usize,
+}
+
+struct CacheEntry {
+    input: Vec<u8>,
+    output: Vec<u8>,
+    result: Result<pandoc::Pandoc, Vec<String>>,
+}
+
+impl ParseCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // number of calls to `read_with_cache` that were answered from the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    pub fn contains(&self, input_bytes: &[u8]) -> bool {
+        let input = normalize_bytes(input_bytes);
+        self.entries
+            .get(&content_hash(&input))
+            .is_some_and(|entry| entry.input == *input)
+    }
+}
+
+// The input with its line endings normalized. Input that isn't UTF-8 is
+// left alone; `read` rejects it anyway.
+fn normalize_bytes(input_bytes: &[u8]) -> Cow<'_, [u8]> {
+    match std::str::from_utf8(input_bytes) {
+        Ok(input) => match normalize(input) {
+            Cow::Borrowed(input) => Cow::Borrowed(input.as_bytes()),
+            Cow::Owned(input) => Cow::Owned(input.into_bytes()),
+        },
+        Err(_) => Cow::Borrowed(input_bytes),
+    }
+}
+
+pub fn read_with_cache<T: Write>(
+    input_bytes: &[u8],
+    cache: &mut ParseCache,
+    output_stream: &mut T,
+) -> Result<pandoc::Pandoc, Vec<String>> {
+    let input = normalize_bytes(input_bytes);
+    let key = content_hash(&input);
+    let cached = cache.entries.get(&key);
+    if let Some(entry) = cached.filter(|entry| entry.input == *input) {
+        cache.hits += 1;
+        output_stream.write_all(&entry.output).unwrap();
+        return entry.result.clone();
+    }
+    let mut output = Vec::new();
+    let result = read(&input, &mut output);
+    output_stream.write_all(&output).unwrap();
+    cache.entries.insert(
+        key,
+        CacheEntry {
+            input: input.into_owned(),
+            output,
+            result: result.clone(),
+        },
+    );
+    result
 }
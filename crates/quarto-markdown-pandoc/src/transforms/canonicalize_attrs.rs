@@ -0,0 +1,73 @@
+/*
+ * canonicalize_attrs.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::filters::{Filter, FilterReturn::Unchanged, topdown_traverse};
+use crate::pandoc::attr::canonicalize_attr;
+use crate::pandoc::{Pandoc, Row};
+
+fn canonicalize_rows(rows: &mut [Row]) {
+    for row in rows {
+        canonicalize_attr(&mut row.attr);
+        for cell in &mut row.cells {
+            canonicalize_attr(&mut cell.attr);
+        }
+    }
+}
+
+// Drops the empty classes and keys of every attr in the document, so that
+// two documents that only differ in how they spell an empty attr compare
+// equal, and are written the same way.
+pub fn canonicalize_attrs(doc: Pandoc) -> Pandoc {
+    // In the handlers below, we return the modified element as `Unchanged` so
+    // that the traversal continues into its children without running the
+    // handler on the element again.
+    let mut filter = Filter::new()
+        .with_header(|mut header| {
+            canonicalize_attr(&mut header.attr);
+            Unchanged(header)
+        })
+        .with_div(|mut div| {
+            canonicalize_attr(&mut div.attr);
+            Unchanged(div)
+        })
+        .with_code_block(|mut code_block| {
+            canonicalize_attr(&mut code_block.attr);
+            Unchanged(code_block)
+        })
+        .with_figure(|mut figure| {
+            canonicalize_attr(&mut figure.attr);
+            Unchanged(figure)
+        })
+        .with_table(|mut table| {
+            canonicalize_attr(&mut table.attr);
+            canonicalize_attr(&mut table.head.attr);
+            canonicalize_rows(&mut table.head.rows);
+            for body in &mut table.bodies {
+                canonicalize_attr(&mut body.attr);
+                canonicalize_rows(&mut body.head);
+                canonicalize_rows(&mut body.body);
+            }
+            canonicalize_attr(&mut table.foot.attr);
+            canonicalize_rows(&mut table.foot.rows);
+            Unchanged(table)
+        })
+        .with_span(|mut span| {
+            canonicalize_attr(&mut span.attr);
+            Unchanged(span)
+        })
+        .with_code(|mut code| {
+            canonicalize_attr(&mut code.attr);
+            Unchanged(code)
+        })
+        .with_image(|mut image| {
+            canonicalize_attr(&mut image.attr);
+            Unchanged(image)
+        })
+        .with_link(|mut link| {
+            canonicalize_attr(&mut link.attr);
+            Unchanged(link)
+        });
+    topdown_traverse(doc, &mut filter)
+}
@@ -0,0 +1,72 @@
+/*
+ * id_prefix.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::filters::{Filter, FilterReturn::Unchanged, topdown_traverse_blocks};
+use crate::pandoc::{Attr, Pandoc};
+
+fn prefix_attr_id(attr: &mut Attr, prefix: &str) {
+    if !attr.0.is_empty() {
+        attr.0 = format!("{}{}", prefix, attr.0);
+    }
+}
+
+// Prefixes every id in the document, as well as every internal link target,
+// so that `{#id}` and `[link](#id)` become `{#prefix-id}` and
+// `[link](#prefix-id)` for a prefix of `prefix-`.
+//
+// This is what's needed to embed a document inside another one without
+// id collisions.
+pub fn prefix_ids(doc: Pandoc, prefix: &str) -> Pandoc {
+    if prefix.is_empty() {
+        return doc;
+    }
+    // In the handlers below, we return the modified element as `Unchanged` so
+    // that the traversal continues into its children without running the
+    // handler on the element again.
+    let mut filter = Filter::new()
+        .with_header(|mut header| {
+            prefix_attr_id(&mut header.attr, prefix);
+            Unchanged(header)
+        })
+        .with_div(|mut div| {
+            prefix_attr_id(&mut div.attr, prefix);
+            Unchanged(div)
+        })
+        .with_code_block(|mut code_block| {
+            prefix_attr_id(&mut code_block.attr, prefix);
+            Unchanged(code_block)
+        })
+        .with_figure(|mut figure| {
+            prefix_attr_id(&mut figure.attr, prefix);
+            Unchanged(figure)
+        })
+        .with_table(|mut table| {
+            prefix_attr_id(&mut table.attr, prefix);
+            Unchanged(table)
+        })
+        .with_span(|mut span| {
+            prefix_attr_id(&mut span.attr, prefix);
+            Unchanged(span)
+        })
+        .with_code(|mut code| {
+            prefix_attr_id(&mut code.attr, prefix);
+            Unchanged(code)
+        })
+        .with_image(|mut image| {
+            prefix_attr_id(&mut image.attr, prefix);
+            Unchanged(image)
+        })
+        .with_link(|mut link| {
+            prefix_attr_id(&mut link.attr, prefix);
+            if let Some(id) = link.target.0.strip_prefix('#').filter(|id| !id.is_empty()) {
+                link.target.0 = format!("#{}{}", prefix, id);
+            }
+            Unchanged(link)
+        });
+    Pandoc {
+        meta: doc.meta,
+        blocks: topdown_traverse_blocks(doc.blocks, &mut filter),
+    }
+}
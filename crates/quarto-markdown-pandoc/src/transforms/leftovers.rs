@@ -0,0 +1,94 @@
+/*
+ * leftovers.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::filters::{Filter, FilterReturn, FilterReturn::Unchanged, topdown_traverse};
+use crate::pandoc::location::empty_range;
+use crate::pandoc::{Block, Inline, Pandoc, Plain, Str};
+use crate::utils::diagnostics::{Diagnostic, Severity};
+use std::cell::RefCell;
+
+// The raw format the reader uses for content it didn't understand.
+pub const LEFTOVER_FORMAT: &str = "quarto-internal-leftover";
+
+#[derive(Debug, Clone, Default)]
+pub struct LeftoverOptions {
+    // report leftover nodes as errors instead of keeping their text
+    pub strict: bool,
+}
+
+// Removes the `quarto-internal-leftover` raw nodes that the reader produces
+// for content it didn't understand, so that they don't leak into the output.
+//
+// By default, the original text is kept as a plain Str (or a Plain block
+// holding one), with a warning for each leftover. In strict mode, each
+// leftover is an error instead.
+pub fn strip_leftovers(
+    doc: Pandoc,
+    options: &LeftoverOptions,
+) -> Result<(Pandoc, Vec<Diagnostic>), Vec<Diagnostic>> {
+    let severity = if options.strict {
+        Severity::Error
+    } else {
+        Severity::Warning
+    };
+    let diagnostics = RefCell::new(Vec::new());
+    let mut filter = Filter::new()
+        .with_raw_block(|rb| {
+            if rb.format != LEFTOVER_FORMAT {
+                return Unchanged(rb);
+            }
+            diagnostics.borrow_mut().push(
+                Diagnostic::new(
+                    format!("Content was not understood: {}", rb.text),
+                    rb.range.clone(),
+                    "The reader didn't understand this.",
+                )
+                .with_severity(severity),
+            );
+            if options.strict {
+                return FilterReturn::FilterResult(vec![], false);
+            }
+            FilterReturn::FilterResult(
+                vec![Block::Plain(Plain {
+                    content: vec![Inline::Str(Str {
+                        text: rb.text,
+                        filename: rb.filename.clone(),
+                        range: rb.range.clone(),
+                    })],
+                    filename: rb.filename,
+                    range: rb.range,
+                })],
+                false,
+            )
+        })
+        .with_raw_inline(|ri| {
+            if ri.format != LEFTOVER_FORMAT {
+                return Unchanged(ri);
+            }
+            // RawInline doesn't carry a location
+            diagnostics.borrow_mut().push(
+                Diagnostic::unlocated(format!("Content was not understood: {}", ri.text))
+                    .with_severity(severity),
+            );
+            if options.strict {
+                return FilterReturn::FilterResult(vec![], false);
+            }
+            FilterReturn::FilterResult(
+                vec![Inline::Str(Str {
+                    text: ri.text,
+                    filename: None,
+                    range: empty_range(),
+                })],
+                false,
+            )
+        });
+    let result = topdown_traverse(doc, &mut filter);
+    drop(filter);
+    let diagnostics = diagnostics.into_inner();
+    if options.strict && !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+    Ok((result, diagnostics))
+}
@@ -0,0 +1,91 @@
+/*
+ * legacy_figures.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::filters::{
+    Filter, FilterReturn::FilterResult, FilterReturn::Unchanged, topdown_traverse,
+};
+use crate::pandoc::{Block, Figure, Inline, Pandoc, Paragraph, pandoc_api_version_for};
+
+// Pandoc before 3.0 had no Figure block. It marked a paragraph holding only
+// a captioned image as a figure by prefixing the image title with `fig:`,
+// and the caption was the image's alt text.
+
+// The implicit figure for `figure`, if it is a lone image with an inline
+// caption. The figures the reader makes from image paragraphs always are.
+pub fn implicit_figure(figure: &Figure) -> Option<Paragraph> {
+    let caption = match (&figure.caption.short, figure.caption.long.as_deref()) {
+        (None, None | Some([])) => None,
+        (None, Some([Block::Plain(plain)])) => Some(plain.content.clone()),
+        (None, Some([Block::Paragraph(para)])) => Some(para.content.clone()),
+        _ => return None,
+    };
+    let [Block::Plain(plain)] = figure.content.as_slice() else {
+        return None;
+    };
+    let [Inline::Image(image)] = plain.content.as_slice() else {
+        return None;
+    };
+    let mut image = image.clone();
+    let (id, classes, attributes) = &figure.attr;
+    if !id.is_empty() {
+        image.attr.0.clone_from(id);
+    }
+    image.attr.1.extend(classes.iter().cloned());
+    for (key, value) in attributes {
+        image
+            .attr
+            .2
+            .entry(key.clone())
+            .or_insert_with(|| value.clone());
+    }
+    if let Some(caption) = caption {
+        image.content = caption;
+    }
+    if !image.target.1.starts_with("fig:") {
+        image.target.1 = format!("fig:{}", image.target.1);
+    }
+    Some(Paragraph {
+        content: vec![Inline::Image(image)],
+        filename: figure.filename.clone(),
+        range: figure.range.clone(),
+    })
+}
+
+// Turns every figure into an implicit figure. Figures that can't be one
+// have no equivalent before Pandoc 3.0, and are an error.
+pub fn to_implicit_figures(doc: Pandoc) -> Result<Pandoc, String> {
+    let mut unsupported = false;
+    let doc = {
+        let mut filter = Filter::new().with_figure(|figure| match implicit_figure(&figure) {
+            Some(para) => FilterResult(vec![Block::Paragraph(para)], true),
+            None => {
+                unsupported = true;
+                Unchanged(figure)
+            }
+        });
+        topdown_traverse(doc, &mut filter)
+    };
+    if unsupported {
+        return Err(
+            "Pandoc before 3.0 only has figures made of a single captioned image".to_string(),
+        );
+    }
+    Ok(doc)
+}
+
+// The document as Pandoc `version` (major, minor) reads it, or None if
+// that's the document as it is.
+pub fn for_pandoc_version(doc: &Pandoc, version: (u32, u32)) -> Result<Option<Pandoc>, String> {
+    if pandoc_api_version_for(version).is_none() {
+        return Err(format!(
+            "Pandoc {}.{} is not supported; the oldest supported release is 2.11",
+            version.0, version.1
+        ));
+    }
+    if version.0 >= 3 {
+        return Ok(None);
+    }
+    to_implicit_figures(doc.clone()).map(Some)
+}
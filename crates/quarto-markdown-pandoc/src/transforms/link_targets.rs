@@ -0,0 +1,44 @@
+/*
+ * link_targets.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::filters::{Filter, FilterReturn::Unchanged, topdown_traverse};
+use crate::pandoc::Pandoc;
+use crate::pandoc::inline::Target;
+use html_escape::decode_html_entities;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkTargetOptions {
+    // decode HTML entities (`&amp;`, `&#32;`, ...) in URLs and titles.
+    // The qmd reader already does this for link destinations, so this is
+    // off by default; turn it on for documents that come from elsewhere.
+    pub decode_entities: bool,
+    // replace spaces in URLs with `%20`
+    pub percent_encode_spaces: bool,
+}
+
+pub fn normalize_target(target: &mut Target, options: &LinkTargetOptions) {
+    if options.decode_entities {
+        target.0 = decode_html_entities(&target.0).into_owned();
+        target.1 = decode_html_entities(&target.1).into_owned();
+    }
+    if options.percent_encode_spaces && target.0.contains(' ') {
+        target.0 = target.0.replace(' ', "%20");
+    }
+}
+
+// Normalizes the targets of all links and images in the document,
+// including those in metadata.
+pub fn normalize_link_targets(doc: Pandoc, options: &LinkTargetOptions) -> Pandoc {
+    let mut filter = Filter::new()
+        .with_link(|mut link| {
+            normalize_target(&mut link.target, options);
+            Unchanged(link)
+        })
+        .with_image(|mut image| {
+            normalize_target(&mut image.target, options);
+            Unchanged(image)
+        });
+    topdown_traverse(doc, &mut filter)
+}
@@ -0,0 +1,44 @@
+/*
+ * merge_raw_blocks.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::filters::{
+    Filter, FilterReturn::FilterResult, FilterReturn::Unchanged, topdown_traverse,
+};
+use crate::pandoc::location::merge_ranges;
+use crate::pandoc::{Block, Blocks, Pandoc};
+
+fn has_adjacent_raw_blocks(blocks: &Blocks) -> bool {
+    blocks.windows(2).any(
+        |pair| matches!(pair, [Block::RawBlock(a), Block::RawBlock(b)] if a.format == b.format),
+    )
+}
+
+// Joins runs of consecutive RawBlocks with the same format into a single
+// RawBlock, with a newline between the texts, so that raw content written
+// as several blocks (an opening and a closing tag, say) reaches the output
+// in one piece, as Pandoc's readers produce it.
+pub fn merge_raw_blocks(doc: Pandoc) -> Pandoc {
+    let mut filter = Filter::new().with_blocks(|blocks| {
+        if !has_adjacent_raw_blocks(&blocks) {
+            return Unchanged(blocks);
+        }
+        let mut result: Blocks = vec![];
+        for block in blocks {
+            match (result.last_mut(), block) {
+                (Some(Block::RawBlock(prev)), Block::RawBlock(raw))
+                    if prev.format == raw.format =>
+                {
+                    prev.text.push('\n');
+                    prev.text.push_str(&raw.text);
+                    prev.range = merge_ranges(&prev.range, &raw.range);
+                }
+                (_, block) => result.push(block),
+            }
+        }
+        // the blocks still need to be visited for nested lists
+        FilterResult(result, true)
+    });
+    topdown_traverse(doc, &mut filter)
+}
@@ -0,0 +1,16 @@
+/*
+ * mod.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+// Document-level transformations that take a Pandoc AST and return a new one.
+// These run after the reader has finished desugaring.
+
+pub mod canonicalize_attrs;
+pub mod id_prefix;
+pub mod legacy_figures;
+pub mod leftovers;
+pub mod link_targets;
+pub mod merge_raw_blocks;
+pub mod number_sections;
+pub mod track_changes;
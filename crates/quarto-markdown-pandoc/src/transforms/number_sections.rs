@@ -0,0 +1,84 @@
+/*
+ * number_sections.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::filters::topdown_traverse_blocks;
+use crate::filters::{Filter, FilterReturn::FilterResult, FilterReturn::Unchanged};
+use crate::pandoc::location::empty_range;
+use crate::pandoc::{Block, Inline, Pandoc, Space, Span, Str};
+use indexmap::IndexMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberSectionsOptions {
+    // headers with a level lower than this are not numbered,
+    // and a header at this level is numbered as a top-level section
+    pub start_level: usize,
+    // how many levels, counting from start_level, receive numbers
+    pub depth: usize,
+}
+
+impl Default for NumberSectionsOptions {
+    fn default() -> Self {
+        NumberSectionsOptions {
+            start_level: 1,
+            depth: 6,
+        }
+    }
+}
+
+// Mimics Pandoc's --number-sections: every numbered header gets its section
+// number (`1`, `1.1`, `1.1.1`, ...) prepended to its content as a Span with
+// class `header-section-number`.
+//
+// Headers with the `unnumbered` class are left alone and don't advance the
+// counters.
+pub fn number_sections(doc: Pandoc, options: &NumberSectionsOptions) -> Pandoc {
+    let mut counters: Vec<usize> = vec![0; options.depth];
+    let mut filter = Filter::new().with_header(|mut header| {
+        if header.level < options.start_level
+            || header.level >= options.start_level + options.depth
+            || header.attr.1.iter().any(|class| class == "unnumbered")
+        {
+            return Unchanged(header);
+        }
+        let index = header.level - options.start_level;
+        counters[index] += 1;
+        for counter in counters.iter_mut().skip(index + 1) {
+            *counter = 0;
+        }
+        let number = counters[..=index]
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(".");
+
+        let mut content = vec![Inline::Span(Span {
+            attr: (
+                String::new(),
+                vec!["header-section-number".to_string()],
+                IndexMap::new(),
+            ),
+            content: vec![Inline::Str(Str {
+                text: number,
+                filename: None,
+                range: empty_range(),
+            })],
+        })];
+        if !header.content.is_empty() {
+            content.push(Inline::Space(Space {
+                filename: None,
+                range: empty_range(),
+            }));
+        }
+        content.append(&mut header.content);
+        header.content = content;
+        // don't recurse, or we would number this header again
+        FilterResult(vec![Block::Header(header)], false)
+    });
+    // metadata is deliberately not traversed: headers there are not sections
+    Pandoc {
+        meta: doc.meta,
+        blocks: topdown_traverse_blocks(doc.blocks, &mut filter),
+    }
+}
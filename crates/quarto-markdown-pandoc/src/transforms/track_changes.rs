@@ -0,0 +1,81 @@
+/*
+ * track_changes.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::filters::{
+    Filter, FilterReturn::FilterResult, FilterReturn::Unchanged, topdown_traverse,
+};
+use crate::pandoc::{Inline, Inlines, Pandoc};
+
+// Tracked changes are spans with the classes Pandoc's readers use for them:
+//
+// - `insertion` and `deletion` hold inserted and deleted text. A
+//   substitution is a deletion followed by an insertion.
+// - `comment-start` holds the text of a comment, and `comment-end` (empty)
+//   marks the end of the commented range, if there is one.
+//
+// Highlights (`mark` spans) are not changes, and are kept in every mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackChanges {
+    // keep inserted text, drop deleted text and comments
+    Accept,
+    // keep deleted text, drop inserted text and comments
+    Reject,
+    // keep the change spans as they are
+    All,
+}
+
+impl TrackChanges {
+    pub fn from_name(name: &str) -> Option<TrackChanges> {
+        match name {
+            "accept" => Some(TrackChanges::Accept),
+            "reject" => Some(TrackChanges::Reject),
+            "all" => Some(TrackChanges::All),
+            _ => None,
+        }
+    }
+}
+
+pub fn apply_track_changes(doc: Pandoc, mode: TrackChanges) -> Pandoc {
+    let (kept, dropped) = match mode {
+        TrackChanges::Accept => ("insertion", "deletion"),
+        TrackChanges::Reject => ("deletion", "insertion"),
+        TrackChanges::All => return doc,
+    };
+    let has_class = |classes: &[String], class: &str| classes.iter().any(|c| c == class);
+    let mut filter = Filter::new().with_span(|span| {
+        let classes = &span.attr.1;
+        if has_class(classes, kept) {
+            FilterResult(span.content, true)
+        } else if has_class(classes, dropped)
+            || has_class(classes, "comment-start")
+            || has_class(classes, "comment-end")
+        {
+            FilterResult(vec![], false)
+        } else {
+            Unchanged(span)
+        }
+    });
+    let doc = topdown_traverse(doc, &mut filter);
+    // dropping a change can leave the spaces around it next to each other
+    let mut filter = Filter::new().with_inlines(|inlines| {
+        let has_double_space = inlines
+            .windows(2)
+            .any(|pair| matches!(pair, [Inline::Space(_), Inline::Space(_)]));
+        if !has_double_space {
+            return Unchanged(inlines);
+        }
+        let mut result: Inlines = Vec::with_capacity(inlines.len());
+        for inline in inlines {
+            if !matches!(
+                (result.last(), &inline),
+                (Some(Inline::Space(_)), Inline::Space(_))
+            ) {
+                result.push(inline);
+            }
+        }
+        FilterResult(result, true)
+    });
+    topdown_traverse(doc, &mut filter)
+}
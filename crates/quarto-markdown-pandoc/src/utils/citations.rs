@@ -0,0 +1,32 @@
+/*
+ * citations.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::pandoc::{Inline, Pandoc};
+use crate::visitors::{Visitor, visit};
+use std::collections::HashSet;
+
+// Returns the ids of all citations in the document, without duplicates,
+// in the order in which they're first seen.
+//
+// Metadata is visited before the body, in source order, and the traversal
+// covers every place a Cite can appear: notes, captions, table cells, etc.
+pub fn collect_citation_ids(doc: &Pandoc) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    {
+        let mut visitor = Visitor::new().with_inline(|inline| {
+            let Inline::Cite(cite) = inline else {
+                return;
+            };
+            for citation in &cite.citations {
+                if seen.insert(citation.id.clone()) {
+                    ids.push(citation.id.clone());
+                }
+            }
+        });
+        visit(doc, &mut visitor);
+    }
+    ids
+}
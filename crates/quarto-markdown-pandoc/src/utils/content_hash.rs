@@ -0,0 +1,19 @@
+/*
+ * content_hash.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use xxhash_rust::xxh3::xxh3_64;
+
+// Hashes the bytes of a document so that parse caches can be keyed
+// by content rather than by pointer or string identity.
+//
+// This is a 64-bit XXH3 hash. It is fast and well-distributed, but it
+// is not cryptographic: two different inputs can in principle produce the
+// same value. The expected number of collisions among n distinct inputs
+// is roughly n^2 / 2^65, so a cache holding a million documents has about
+// a one-in-thirty-million chance of ever seeing one. Callers that cannot
+// tolerate even that should compare the original bytes on a hit.
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    xxh3_64(bytes)
+}
@@ -0,0 +1,120 @@
+/*
+ * corpus.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::readers;
+use crate::utils::encoding::decode_input;
+use crate::utils::node_stats::{NodeStats, count_nodes};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+// Aggregated counts over a set of documents, for checking how common a
+// construct is before changing how it's handled.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CorpusReport {
+    pub files: usize,
+    // documents the reader rejected, with their first error; these don't
+    // contribute to `stats`, but do to `patterns`
+    pub failures: Vec<(PathBuf, String)>,
+    pub stats: NodeStats,
+    pub patterns: BTreeMap<&'static str, usize>,
+}
+
+static BARE_URL: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(^|[^(<"'=])https?://"#).unwrap());
+static GRID_TABLE_BORDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^[ \t]*\+[-=:]+(\+[-=:]+)*\+[ \t]*$").unwrap());
+
+// Counts of source patterns that the node counts can't show, because the
+// reader either doesn't support them or treats them as ordinary text:
+// `$$` delimiters, URLs outside of link syntax, and grid table borders.
+pub fn count_patterns(input: &str) -> BTreeMap<&'static str, usize> {
+    let mut patterns = BTreeMap::new();
+    patterns.insert("dollar_dollar", input.matches("$$").count());
+    patterns.insert("bare_url", BARE_URL.find_iter(input).count());
+    patterns.insert(
+        "grid_table_border",
+        GRID_TABLE_BORDER.find_iter(input).count(),
+    );
+    patterns
+}
+
+impl CorpusReport {
+    pub fn add_document(&mut self, path: &Path, input: &str) {
+        self.files += 1;
+        for (name, count) in count_patterns(input) {
+            *self.patterns.entry(name).or_default() += count;
+        }
+        let mut input = input.to_string();
+        if !input.ends_with('\n') {
+            input.push('\n');
+        }
+        // the reader still panics on some constructs; one such document
+        // shouldn't end the scan
+        let result =
+            std::panic::catch_unwind(|| readers::qmd::read(input.as_bytes(), &mut std::io::sink()));
+        match result {
+            Ok(Ok(doc)) => self.stats.merge(&count_nodes(&doc)),
+            Ok(Err(errors)) => self.failures.push((
+                path.to_path_buf(),
+                errors.into_iter().next().unwrap_or_default(),
+            )),
+            Err(_) => self
+                .failures
+                .push((path.to_path_buf(), "the reader panicked".to_string())),
+        }
+    }
+
+    // One `kind,name,count` row per count, after a header row.
+    pub fn to_csv(&self) -> String {
+        let mut rows = vec!["kind,name,count".to_string()];
+        rows.push(format!("summary,files,{}", self.files));
+        rows.push(format!("summary,failures,{}", self.failures.len()));
+        for (name, count) in &self.stats.blocks {
+            rows.push(format!("block,{},{}", name, count));
+        }
+        for (name, count) in &self.stats.inlines {
+            rows.push(format!("inline,{},{}", name, count));
+        }
+        for (name, count) in &self.patterns {
+            rows.push(format!("pattern,{},{}", name, count));
+        }
+        rows.join("\n") + "\n"
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "files": self.files,
+            "failures": self.failures.iter().map(|(path, error)| serde_json::json!({
+                "path": path.display().to_string(),
+                "error": error,
+            })).collect::<Vec<_>>(),
+            "blocks": self.stats.blocks,
+            "inlines": self.stats.inlines,
+            "patterns": self.patterns,
+        })
+    }
+}
+
+// Scans every `.qmd` file below `dir`, in path order.
+pub fn scan_directory(dir: &Path) -> std::io::Result<CorpusReport> {
+    let pattern = format!("{}/**/*.qmd", dir.display());
+    let mut paths: Vec<PathBuf> = glob::glob(&pattern)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?
+        .filter_map(Result::ok)
+        .collect();
+    paths.sort();
+    let mut report = CorpusReport::default();
+    for path in paths {
+        match decode_input(std::fs::read(&path)?, None) {
+            Ok(input) => report.add_document(&path, &input),
+            Err(msg) => {
+                report.files += 1;
+                report.failures.push((path, msg));
+            }
+        }
+    }
+    Ok(report)
+}
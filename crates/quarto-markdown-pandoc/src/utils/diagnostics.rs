@@ -0,0 +1,161 @@
+/*
+ * diagnostics.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::pandoc::location::Range;
+use crate::utils::source::Source;
+use ariadne::{Color, Config, IndexType, Label as AriadneLabel, Report, ReportKind};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub range: Range,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    // the document was still read, but maybe not as the author meant
+    Warning,
+}
+
+// A problem in the input, with the source ranges that explain it. The
+// primary label is where the problem was found; secondary labels point at
+// related places (the opening of an unclosed construct, ...). Errors that
+// aren't about one place in the input (the nesting depth, ...) have no
+// labels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub title: String,
+    pub primary: Option<Label>,
+    pub secondary: Vec<Label>,
+    pub note: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(title: impl Into<String>, range: Range, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            title: title.into(),
+            primary: Some(Label {
+                range,
+                message: message.into(),
+            }),
+            secondary: vec![],
+            note: None,
+        }
+    }
+
+    pub fn warning(title: impl Into<String>, range: Range, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            ..Diagnostic::new(title, range, message)
+        }
+    }
+
+    pub fn unlocated(title: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            title: title.into(),
+            primary: None,
+            secondary: vec![],
+            note: None,
+        }
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    pub fn with_secondary(mut self, range: Range, message: impl Into<String>) -> Self {
+        self.secondary.push(Label {
+            range,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    // The one-line form, as `read` reports it. Locations are 1-based, like
+    // the ones `render` shows.
+    pub fn message(&self) -> String {
+        let severity = match self.severity {
+            Severity::Error => "Error",
+            Severity::Warning => "Warning",
+        };
+        match &self.primary {
+            Some(primary) => format!(
+                "{}: {} at {}:{}",
+                severity,
+                self.title,
+                primary.range.start.row + 1,
+                primary.range.start.column + 1
+            ),
+            None => format!("{}: {}", severity, self.title),
+        }
+    }
+
+    // Renders the diagnostic with the source lines it points at, labelled
+    // with carets.
+    pub fn render(&self, filename: &str, source: &Source, color: bool) -> String {
+        let Some(primary) = &self.primary else {
+            return format!("{}\n", self.message());
+        };
+        let span = |range: &Range| {
+            // ariadne doesn't draw empty spans, which is what missing nodes
+            // have, so we point at the character after them
+            let end = if range.end.offset > range.start.offset {
+                range.end.offset
+            } else {
+                range.start.offset + 1
+            };
+            let start = range.start.offset.min(source.text().len());
+            (filename, start..end.min(source.text().len() + 1))
+        };
+        let (kind, primary_color) = match self.severity {
+            Severity::Error => (ReportKind::Error, Color::Red),
+            Severity::Warning => (ReportKind::Warning, Color::Yellow),
+        };
+        let mut report = Report::build(kind, span(&primary.range))
+            .with_config(
+                Config::default()
+                    .with_color(color)
+                    .with_index_type(IndexType::Byte),
+            )
+            .with_message(&self.title)
+            .with_label(
+                AriadneLabel::new(span(&primary.range))
+                    .with_message(&primary.message)
+                    .with_color(primary_color)
+                    .with_order(0),
+            );
+        for (i, label) in self.secondary.iter().enumerate() {
+            report = report.with_label(
+                AriadneLabel::new(span(&label.range))
+                    .with_message(&label.message)
+                    .with_color(Color::Blue)
+                    .with_order(i as i32 + 1),
+            );
+        }
+        if let Some(note) = &self.note {
+            report = report.with_note(note);
+        }
+        let mut buf = Vec::new();
+        report
+            .finish()
+            .write((filename, ariadne::Source::from(source.text())), &mut buf)
+            .expect("Failed to render diagnostic");
+        String::from_utf8(buf).expect("Invalid UTF-8 in diagnostic")
+    }
+}
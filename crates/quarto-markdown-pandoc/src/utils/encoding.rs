@@ -0,0 +1,38 @@
+/*
+ * encoding.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use encoding_rs::Encoding;
+
+// The reader works on UTF-8 and assumes it throughout, so invalid input is
+// rejected up front with the offset of the first bad byte. The error
+// describes the problem; callers decide how to report it.
+pub fn check_utf8(input_bytes: &[u8]) -> Result<(), String> {
+    match std::str::from_utf8(input_bytes) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!(
+            "input is not valid UTF-8 at byte {}",
+            e.valid_up_to()
+        )),
+    }
+}
+
+// Decodes the input to a string. Without an encoding the input must be
+// UTF-8; otherwise it's transcoded from the encoding with the given label
+// (`latin1`, `windows-1252`, `shift_jis`, ...; see the WHATWG Encoding
+// Standard for the full list).
+pub fn decode_input(input_bytes: Vec<u8>, encoding: Option<&str>) -> Result<String, String> {
+    let Some(label) = encoding else {
+        check_utf8(&input_bytes).map_err(|e| format!("Error: {}", e))?;
+        return Ok(String::from_utf8(input_bytes).unwrap());
+    };
+    let Some(encoding) = Encoding::for_label(label.as_bytes()) else {
+        return Err(format!("Error: unknown input encoding {}", label));
+    };
+    let (text, _, had_errors) = encoding.decode(&input_bytes);
+    if had_errors {
+        return Err(format!("Error: input is not valid {}", encoding.name()));
+    }
+    Ok(text.into_owned())
+}
@@ -0,0 +1,58 @@
+/*
+ * line_ending.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+    // classic Mac OS
+    Cr,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+// The line ending of the input, as given by its first line break.
+// Input without line breaks is reported as `Lf`.
+pub fn infer_line_ending(input_bytes: &[u8]) -> LineEnding {
+    match memchr::memchr2(b'\n', b'\r', input_bytes) {
+        Some(i) if input_bytes[i] == b'\n' => LineEnding::Lf,
+        Some(i) if input_bytes.get(i + 1) == Some(&b'\n') => LineEnding::Crlf,
+        Some(_) => LineEnding::Cr,
+        None => LineEnding::Lf,
+    }
+}
+
+// Converts `\r\n` and lone `\r` line endings to `\n`. Input without `\r`
+// (the common case) is returned as is, without allocating.
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    let bytes = input.as_bytes();
+    if memchr::memchr(b'\r', bytes).is_none() {
+        return Cow::Borrowed(input);
+    }
+    let mut result = String::with_capacity(input.len());
+    let mut start = 0;
+    for i in memchr::memchr_iter(b'\r', bytes) {
+        result.push_str(&input[start..i]);
+        result.push('\n');
+        start = if bytes.get(i + 1) == Some(&b'\n') {
+            i + 2
+        } else {
+            i + 1
+        };
+    }
+    result.push_str(&input[start..]);
+    Cow::Owned(result)
+}
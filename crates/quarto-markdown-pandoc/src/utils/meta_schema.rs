@@ -0,0 +1,130 @@
+/*
+ * meta_schema.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::pandoc::{Meta, MetaValue};
+use indexmap::IndexMap;
+
+// The observed shape of a metadata value. This only describes what's in
+// the document; it isn't checked against anything.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetaType {
+    String,
+    Bool,
+    Inlines,
+    Blocks,
+    // the distinct shapes of the elements, in the order they first appear
+    List(Vec<MetaType>),
+    Map(IndexMap<String, MetaType>),
+}
+
+impl MetaType {
+    pub fn name(&self) -> String {
+        match self {
+            MetaType::String => "string".to_string(),
+            MetaType::Bool => "bool".to_string(),
+            MetaType::Inlines => "inlines".to_string(),
+            MetaType::Blocks => "blocks".to_string(),
+            MetaType::List(elements) if elements.is_empty() => "list".to_string(),
+            MetaType::List(elements) => format!(
+                "list of {}",
+                elements
+                    .iter()
+                    .map(MetaType::name)
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            ),
+            MetaType::Map(_) => "map".to_string(),
+        }
+    }
+}
+
+// Adds `ty` to the distinct element shapes of a list. Maps are merged
+// into a single map with the keys of all of them, and lists into a single
+// list; for a key seen with different shapes, the first one wins.
+fn merge_into(types: &mut Vec<MetaType>, ty: MetaType) {
+    for existing in types.iter_mut() {
+        match (existing, &ty) {
+            (MetaType::Map(a), MetaType::Map(b)) => {
+                for (key, value) in b {
+                    match a.get_mut(key) {
+                        Some(current) => {
+                            let mut merged = vec![current.clone()];
+                            merge_into(&mut merged, value.clone());
+                            *current = merged.swap_remove(0);
+                        }
+                        None => {
+                            a.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+                return;
+            }
+            (MetaType::List(a), MetaType::List(b)) => {
+                for element in b {
+                    merge_into(a, element.clone());
+                }
+                return;
+            }
+            (existing, ty) if existing == ty => return,
+            _ => {}
+        }
+    }
+    types.push(ty);
+}
+
+pub fn meta_value_type(value: &MetaValue) -> MetaType {
+    match value {
+        MetaValue::MetaString(_) => MetaType::String,
+        MetaValue::MetaBool(_) => MetaType::Bool,
+        MetaValue::MetaInlines(_) => MetaType::Inlines,
+        MetaValue::MetaBlocks(_) => MetaType::Blocks,
+        MetaValue::MetaList(items) => {
+            let mut elements = Vec::new();
+            for item in items {
+                merge_into(&mut elements, meta_value_type(item));
+            }
+            MetaType::List(elements)
+        }
+        MetaValue::MetaMap(map) => MetaType::Map(meta_map_types(map)),
+    }
+}
+
+fn meta_map_types(map: &IndexMap<String, MetaValue>) -> IndexMap<String, MetaType> {
+    map.iter()
+        .map(|(key, value)| (key.clone(), meta_value_type(value)))
+        .collect()
+}
+
+pub fn meta_schema(meta: &Meta) -> IndexMap<String, MetaType> {
+    meta_map_types(meta)
+}
+
+fn write_keys<T: std::io::Write>(
+    types: &IndexMap<String, MetaType>,
+    indent: usize,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    for (key, ty) in types {
+        writeln!(buf, "{}{}: {}", "  ".repeat(indent), key, ty.name())?;
+        match ty {
+            MetaType::Map(inner) => write_keys(inner, indent + 1, buf)?,
+            MetaType::List(elements) => {
+                for element in elements {
+                    if let MetaType::Map(inner) = element {
+                        write_keys(inner, indent + 1, buf)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// Writes one `key: type` line per metadata key, with the keys of maps
+// (including maps in lists) indented below them.
+pub fn write_meta_schema<T: std::io::Write>(meta: &Meta, buf: &mut T) -> std::io::Result<()> {
+    write_keys(&meta_schema(meta), 0, buf)
+}
@@ -4,5 +4,16 @@
  */
 
 pub mod autoid;
+pub mod citations;
 pub mod concrete_tree_depth;
+pub mod content_hash;
+pub mod corpus;
+pub mod diagnostics;
+pub mod encoding;
+pub mod line_ending;
+pub mod meta_schema;
+pub mod node_stats;
+pub mod range_tree;
+pub mod raw_formats;
+pub mod source;
 pub mod output;
@@ -0,0 +1,107 @@
+/*
+ * node_stats.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::pandoc::{Block, Inline, Pandoc};
+use crate::visitors::{Visitor, visit_blocks};
+use std::collections::BTreeMap;
+
+// How many of each block and inline variant a document has, keyed by the
+// variant name (`"Header"`, `"Link"`, ...). Useful for finding out how
+// common a construct is across a corpus.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeStats {
+    pub blocks: BTreeMap<&'static str, usize>,
+    pub inlines: BTreeMap<&'static str, usize>,
+}
+
+impl NodeStats {
+    pub fn block_count(&self, name: &str) -> usize {
+        self.blocks.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn inline_count(&self, name: &str) -> usize {
+        self.inlines.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn total_blocks(&self) -> usize {
+        self.blocks.values().sum()
+    }
+
+    pub fn total_inlines(&self) -> usize {
+        self.inlines.values().sum()
+    }
+
+    // adds the counts of `other` to these, as when scanning several documents
+    pub fn merge(&mut self, other: &NodeStats) {
+        for (name, count) in &other.blocks {
+            *self.blocks.entry(name).or_default() += count;
+        }
+        for (name, count) in &other.inlines {
+            *self.inlines.entry(name).or_default() += count;
+        }
+    }
+}
+
+pub fn block_name(block: &Block) -> &'static str {
+    match block {
+        Block::Plain(_) => "Plain",
+        Block::Paragraph(_) => "Para",
+        Block::LineBlock(_) => "LineBlock",
+        Block::CodeBlock(_) => "CodeBlock",
+        Block::RawBlock(_) => "RawBlock",
+        Block::BlockQuote(_) => "BlockQuote",
+        Block::OrderedList(_) => "OrderedList",
+        Block::BulletList(_) => "BulletList",
+        Block::DefinitionList(_) => "DefinitionList",
+        Block::Header(_) => "Header",
+        Block::HorizontalRule(_) => "HorizontalRule",
+        Block::Table(_) => "Table",
+        Block::Figure(_) => "Figure",
+        Block::Div(_) => "Div",
+        Block::BlockMetadata(_) => "BlockMetadata",
+    }
+}
+
+pub fn inline_name(inline: &Inline) -> &'static str {
+    match inline {
+        Inline::Str(_) => "Str",
+        Inline::Emph(_) => "Emph",
+        Inline::Underline(_) => "Underline",
+        Inline::Strong(_) => "Strong",
+        Inline::Strikeout(_) => "Strikeout",
+        Inline::Superscript(_) => "Superscript",
+        Inline::Subscript(_) => "Subscript",
+        Inline::SmallCaps(_) => "SmallCaps",
+        Inline::Quoted(_) => "Quoted",
+        Inline::Cite(_) => "Cite",
+        Inline::Code(_) => "Code",
+        Inline::Space(_) => "Space",
+        Inline::SoftBreak(_) => "SoftBreak",
+        Inline::LineBreak(_) => "LineBreak",
+        Inline::Math(_) => "Math",
+        Inline::RawInline(_) => "RawInline",
+        Inline::Link(_) => "Link",
+        Inline::Image(_) => "Image",
+        Inline::Note(_) => "Note",
+        Inline::Span(_) => "Span",
+        Inline::Shortcode(_) => "Shortcode",
+        Inline::NoteReference(_) => "NoteReference",
+        Inline::Attr(..) => "Attr",
+    }
+}
+
+// Counts the nodes in the document body, including those nested in lists,
+// tables, notes, captions and so on. Metadata isn't counted.
+pub fn count_nodes(doc: &Pandoc) -> NodeStats {
+    let mut stats = NodeStats::default();
+    let blocks = &mut stats.blocks;
+    let inlines = &mut stats.inlines;
+    let mut visitor = Visitor::new()
+        .with_block(|block| *blocks.entry(block_name(block)).or_default() += 1)
+        .with_inline(|inline| *inlines.entry(inline_name(inline)).or_default() += 1);
+    visit_blocks(&doc.blocks, &mut visitor);
+    drop(visitor);
+    stats
+}
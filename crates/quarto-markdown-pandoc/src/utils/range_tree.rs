@@ -0,0 +1,222 @@
+/*
+ * range_tree.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::pandoc::location::{Range, SourceLocation, is_empty_range};
+use crate::pandoc::table::Row;
+use crate::pandoc::{Block, Caption, Inline, Pandoc};
+use crate::utils::node_stats::{block_name, inline_name};
+use std::io::Write;
+
+// An indented outline of the document, one node per line, with the source
+// range of each node that tracks one:
+//
+//     Para 1:1-1:12
+//       Str "Hello" 1:1-1:6
+//       Space 1:6-1:7
+//       Emph
+//         Str "world" 1:8-1:13
+//
+// Rows and columns are 1-based, as in the reader's messages, and the end
+// is exclusive. Nodes with an empty range (made up by the reader or a
+// transform rather than read from the source) are marked `(no range)`.
+// Inlines that don't track a range get no annotation. This is for
+// checking source mapping; the native writer is the one to use for the
+// document itself.
+pub fn write_range_tree<T: Write>(pandoc: &Pandoc, buf: &mut T) -> std::io::Result<()> {
+    for block in &pandoc.blocks {
+        write_block(block, 0, buf)?;
+    }
+    Ok(())
+}
+
+pub fn format_range(range: &Range) -> String {
+    if is_empty_range(range) {
+        return "(no range)".to_string();
+    }
+    format!(
+        "{}:{}-{}:{}",
+        range.start.row + 1,
+        range.start.column + 1,
+        range.end.row + 1,
+        range.end.column + 1
+    )
+}
+
+fn block_range(block: &Block) -> Range {
+    match block {
+        Block::Plain(b) => b.range(),
+        Block::Paragraph(b) => b.range(),
+        Block::LineBlock(b) => b.range(),
+        Block::CodeBlock(b) => b.range(),
+        Block::RawBlock(b) => b.range(),
+        Block::BlockQuote(b) => b.range(),
+        Block::OrderedList(b) => b.range(),
+        Block::BulletList(b) => b.range(),
+        Block::DefinitionList(b) => b.range(),
+        Block::Header(b) => b.range(),
+        Block::HorizontalRule(b) => b.range(),
+        Block::Table(b) => b.range(),
+        Block::Figure(b) => b.range(),
+        Block::Div(b) => b.range(),
+        Block::BlockMetadata(b) => b.range(),
+    }
+}
+
+fn inline_range(inline: &Inline) -> Option<Range> {
+    match inline {
+        Inline::Str(i) => Some(i.range()),
+        Inline::Space(i) => Some(i.range()),
+        Inline::SoftBreak(i) => Some(i.range()),
+        Inline::LineBreak(i) => Some(i.range()),
+        _ => None,
+    }
+}
+
+fn write_line<T: Write>(
+    depth: usize,
+    label: &str,
+    range: Option<Range>,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    write!(buf, "{}{}", "  ".repeat(depth), label)?;
+    if let Some(range) = range {
+        write!(buf, " {}", format_range(&range))?;
+    }
+    writeln!(buf)
+}
+
+fn write_blocks<T: Write>(blocks: &[Block], depth: usize, buf: &mut T) -> std::io::Result<()> {
+    for block in blocks {
+        write_block(block, depth, buf)?;
+    }
+    Ok(())
+}
+
+fn write_inlines<T: Write>(inlines: &[Inline], depth: usize, buf: &mut T) -> std::io::Result<()> {
+    for inline in inlines {
+        write_inline(inline, depth, buf)?;
+    }
+    Ok(())
+}
+
+fn write_caption<T: Write>(caption: &Caption, depth: usize, buf: &mut T) -> std::io::Result<()> {
+    if caption.short.is_none() && caption.long.is_none() {
+        return Ok(());
+    }
+    write_line(depth, "Caption", None, buf)?;
+    if let Some(short) = &caption.short {
+        write_inlines(short, depth + 1, buf)?;
+    }
+    if let Some(long) = &caption.long {
+        write_blocks(long, depth + 1, buf)?;
+    }
+    Ok(())
+}
+
+fn write_rows<T: Write>(rows: &[Row], depth: usize, buf: &mut T) -> std::io::Result<()> {
+    for row in rows {
+        write_line(depth, "Row", None, buf)?;
+        for cell in &row.cells {
+            write_line(depth + 1, "Cell", None, buf)?;
+            write_blocks(&cell.content, depth + 2, buf)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_block<T: Write>(block: &Block, depth: usize, buf: &mut T) -> std::io::Result<()> {
+    let label = match block {
+        Block::CodeBlock(code) => format!("CodeBlock {:?}", code.text),
+        Block::RawBlock(raw) => format!("RawBlock {:?}", raw.format),
+        Block::Header(header) => format!("Header {}", header.level),
+        _ => block_name(block).to_string(),
+    };
+    write_line(depth, &label, Some(block_range(block)), buf)?;
+    let depth = depth + 1;
+    match block {
+        Block::Plain(plain) => write_inlines(&plain.content, depth, buf),
+        Block::Paragraph(para) => write_inlines(&para.content, depth, buf),
+        Block::Header(header) => write_inlines(&header.content, depth, buf),
+        Block::LineBlock(line_block) => {
+            for line in &line_block.content {
+                write_line(depth, "Line", None, buf)?;
+                write_inlines(line, depth + 1, buf)?;
+            }
+            Ok(())
+        }
+        Block::BlockQuote(quote) => write_blocks(&quote.content, depth, buf),
+        Block::Div(div) => write_blocks(&div.content, depth, buf),
+        Block::OrderedList(list) => {
+            for item in &list.content {
+                write_line(depth, "Item", None, buf)?;
+                write_blocks(item, depth + 1, buf)?;
+            }
+            Ok(())
+        }
+        Block::BulletList(list) => {
+            for item in &list.content {
+                write_line(depth, "Item", None, buf)?;
+                write_blocks(item, depth + 1, buf)?;
+            }
+            Ok(())
+        }
+        Block::DefinitionList(list) => {
+            for (term, definitions) in &list.content {
+                write_line(depth, "Term", None, buf)?;
+                write_inlines(term, depth + 1, buf)?;
+                for definition in definitions {
+                    write_line(depth, "Definition", None, buf)?;
+                    write_blocks(definition, depth + 1, buf)?;
+                }
+            }
+            Ok(())
+        }
+        Block::Figure(figure) => {
+            write_caption(&figure.caption, depth, buf)?;
+            write_blocks(&figure.content, depth, buf)
+        }
+        Block::Table(table) => {
+            write_caption(&table.caption, depth, buf)?;
+            write_rows(&table.head.rows, depth, buf)?;
+            for body in &table.bodies {
+                write_rows(&body.head, depth, buf)?;
+                write_rows(&body.body, depth, buf)?;
+            }
+            write_rows(&table.foot.rows, depth, buf)
+        }
+        Block::CodeBlock(_)
+        | Block::RawBlock(_)
+        | Block::HorizontalRule(_)
+        | Block::BlockMetadata(_) => Ok(()),
+    }
+}
+
+fn write_inline<T: Write>(inline: &Inline, depth: usize, buf: &mut T) -> std::io::Result<()> {
+    let label = match inline {
+        Inline::Str(s) => format!("Str {:?}", s.text),
+        Inline::Code(code) => format!("Code {:?}", code.text),
+        Inline::Math(math) => format!("Math {:?}", math.text),
+        Inline::RawInline(raw) => format!("RawInline {:?}", raw.format),
+        _ => inline_name(inline).to_string(),
+    };
+    write_line(depth, &label, inline_range(inline), buf)?;
+    let depth = depth + 1;
+    match inline {
+        Inline::Emph(i) => write_inlines(&i.content, depth, buf),
+        Inline::Underline(i) => write_inlines(&i.content, depth, buf),
+        Inline::Strong(i) => write_inlines(&i.content, depth, buf),
+        Inline::Strikeout(i) => write_inlines(&i.content, depth, buf),
+        Inline::Superscript(i) => write_inlines(&i.content, depth, buf),
+        Inline::Subscript(i) => write_inlines(&i.content, depth, buf),
+        Inline::SmallCaps(i) => write_inlines(&i.content, depth, buf),
+        Inline::Quoted(i) => write_inlines(&i.content, depth, buf),
+        Inline::Cite(i) => write_inlines(&i.content, depth, buf),
+        Inline::Link(i) => write_inlines(&i.content, depth, buf),
+        Inline::Image(i) => write_inlines(&i.content, depth, buf),
+        Inline::Span(i) => write_inlines(&i.content, depth, buf),
+        Inline::Note(i) => write_blocks(&i.content, depth, buf),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,30 @@
+/*
+ * raw_formats.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::pandoc::{Block, Inline, Pandoc};
+use crate::visitors::{Visitor, visit};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+// The formats of all RawBlock and RawInline nodes in the document,
+// including those in metadata. Drivers can use this to warn about raw
+// content the output format can't render.
+pub fn collect_raw_formats(doc: &Pandoc) -> BTreeSet<String> {
+    let formats = RefCell::new(BTreeSet::new());
+    let mut visitor = Visitor::new()
+        .with_block(|block| {
+            if let Block::RawBlock(rb) = block {
+                formats.borrow_mut().insert(rb.format.clone());
+            }
+        })
+        .with_inline(|inline| {
+            if let Inline::RawInline(ri) = inline {
+                formats.borrow_mut().insert(ri.format.clone());
+            }
+        });
+    visit(doc, &mut visitor);
+    drop(visitor);
+    formats.into_inner()
+}
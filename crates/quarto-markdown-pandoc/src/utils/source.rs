@@ -0,0 +1,96 @@
+/*
+ * source.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::pandoc::location::{Location, Range};
+
+// The input of a parse, with the offset of each line, so that diagnostics
+// can go from a `Range` to the source text and back.
+//
+// Rows and columns are 0-based, and columns count bytes, as in the ranges
+// the reader produces (they come from tree-sitter).
+#[derive(Debug, Clone)]
+pub struct Source<'a> {
+    text: &'a str,
+    // byte offset of the start of each line
+    line_starts: Vec<usize>,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(memchr::memchr_iter(b'\n', text.as_bytes()).map(|i| i + 1));
+        Source { text, line_starts }
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    // A trailing newline doesn't start another line.
+    pub fn line_count(&self) -> usize {
+        match self.line_starts.last() {
+            Some(&start) if start == self.text.len() && start > 0 => self.line_starts.len() - 1,
+            _ => self.line_starts.len(),
+        }
+    }
+
+    // The text of a line, without its line ending.
+    pub fn line(&self, row: usize) -> Option<&'a str> {
+        let start = *self.line_starts.get(row)?;
+        if start == self.text.len() && row > 0 {
+            return None;
+        }
+        let end = self
+            .line_starts
+            .get(row + 1)
+            .map_or(self.text.len(), |next| next - 1);
+        let line = &self.text[start..end];
+        Some(line.strip_suffix('\r').unwrap_or(line))
+    }
+
+    // The location of a byte offset; offsets past the end are clamped.
+    pub fn location(&self, offset: usize) -> Location {
+        let offset = offset.min(self.text.len());
+        let row = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        Location {
+            offset,
+            row,
+            column: offset - self.line_starts[row],
+        }
+    }
+
+    pub fn offset(&self, row: usize, column: usize) -> Option<usize> {
+        let offset = self.line_starts.get(row)? + column;
+        (offset <= self.text.len()).then_some(offset)
+    }
+
+    // The text covered by a range. Ranges that don't fit the source (as
+    // from a different input) give an empty string.
+    pub fn snippet(&self, range: &Range) -> &'a str {
+        self.text
+            .get(range.start.offset..range.end.offset)
+            .unwrap_or("")
+    }
+
+    // The full lines a range starts and ends on, for showing the range in
+    // context.
+    pub fn lines(&self, range: &Range) -> &'a str {
+        let start = self.location(range.start.offset);
+        let end = self.location(range.end.offset.max(range.start.offset));
+        // a range that ends at the start of a line (after a newline)
+        // doesn't cover that line
+        let end_row = if end.column == 0 && end.row > start.row {
+            end.row - 1
+        } else {
+            end.row
+        };
+        let first = self.line_starts[start.row];
+        let last = self
+            .line_starts
+            .get(end_row + 1)
+            .map_or(self.text.len(), |next| next - 1);
+        &self.text[first..last]
+    }
+}
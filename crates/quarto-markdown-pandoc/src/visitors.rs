@@ -0,0 +1,190 @@
+/*
+ * visitors.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::pandoc::table::Row;
+use crate::pandoc::{Block, Caption, Inline, MetaValue, Pandoc};
+
+// visitors are the read-only counterpart of filters: they borrow the
+// document and see every node in the same top-down order a filter does,
+// but can't change anything. Use them for code that only collects
+// information, so the document doesn't have to be cloned first.
+
+type BlockVisitorFn<'a> = Box<dyn FnMut(&Block) + 'a>;
+type InlineVisitorFn<'a> = Box<dyn FnMut(&Inline) + 'a>;
+
+#[derive(Default)]
+pub struct Visitor<'a> {
+    pub block: Option<BlockVisitorFn<'a>>,
+    pub inline: Option<InlineVisitorFn<'a>>,
+}
+
+impl<'a> Visitor<'a> {
+    pub fn new() -> Visitor<'a> {
+        Visitor::default()
+    }
+
+    pub fn with_block<F>(mut self, f: F) -> Visitor<'a>
+    where
+        F: FnMut(&Block) + 'a,
+    {
+        self.block = Some(Box::new(f));
+        self
+    }
+
+    pub fn with_inline<F>(mut self, f: F) -> Visitor<'a>
+    where
+        F: FnMut(&Inline) + 'a,
+    {
+        self.inline = Some(Box::new(f));
+        self
+    }
+}
+
+pub fn visit_inline(inline: &Inline, visitor: &mut Visitor) {
+    if let Some(f) = &mut visitor.inline {
+        f(inline);
+    }
+    match inline {
+        Inline::Emph(i) => visit_inlines(&i.content, visitor),
+        Inline::Underline(i) => visit_inlines(&i.content, visitor),
+        Inline::Strong(i) => visit_inlines(&i.content, visitor),
+        Inline::Strikeout(i) => visit_inlines(&i.content, visitor),
+        Inline::Superscript(i) => visit_inlines(&i.content, visitor),
+        Inline::Subscript(i) => visit_inlines(&i.content, visitor),
+        Inline::SmallCaps(i) => visit_inlines(&i.content, visitor),
+        Inline::Quoted(i) => visit_inlines(&i.content, visitor),
+        Inline::Cite(cite) => {
+            for citation in &cite.citations {
+                visit_inlines(&citation.prefix, visitor);
+                visit_inlines(&citation.suffix, visitor);
+            }
+            visit_inlines(&cite.content, visitor);
+        }
+        Inline::Link(i) => visit_inlines(&i.content, visitor),
+        Inline::Image(i) => visit_inlines(&i.content, visitor),
+        Inline::Note(note) => visit_blocks(&note.content, visitor),
+        Inline::Span(i) => visit_inlines(&i.content, visitor),
+        Inline::Str(_)
+        | Inline::Code(_)
+        | Inline::Space(_)
+        | Inline::SoftBreak(_)
+        | Inline::LineBreak(_)
+        | Inline::Math(_)
+        | Inline::RawInline(_)
+        | Inline::Shortcode(_)
+        | Inline::NoteReference(_)
+        | Inline::Attr(..) => {}
+    }
+}
+
+pub fn visit_inlines(inlines: &[Inline], visitor: &mut Visitor) {
+    for inline in inlines {
+        visit_inline(inline, visitor);
+    }
+}
+
+fn visit_caption(caption: &Caption, visitor: &mut Visitor) {
+    if let Some(short) = &caption.short {
+        visit_inlines(short, visitor);
+    }
+    if let Some(long) = &caption.long {
+        visit_blocks(long, visitor);
+    }
+}
+
+fn visit_rows(rows: &[Row], visitor: &mut Visitor) {
+    for row in rows {
+        for cell in &row.cells {
+            visit_blocks(&cell.content, visitor);
+        }
+    }
+}
+
+pub fn visit_block(block: &Block, visitor: &mut Visitor) {
+    if let Some(f) = &mut visitor.block {
+        f(block);
+    }
+    match block {
+        Block::Plain(plain) => visit_inlines(&plain.content, visitor),
+        Block::Paragraph(para) => visit_inlines(&para.content, visitor),
+        Block::LineBlock(line_block) => {
+            for line in &line_block.content {
+                visit_inlines(line, visitor);
+            }
+        }
+        Block::BlockQuote(quote) => visit_blocks(&quote.content, visitor),
+        Block::OrderedList(list) => {
+            for item in &list.content {
+                visit_blocks(item, visitor);
+            }
+        }
+        Block::BulletList(list) => {
+            for item in &list.content {
+                visit_blocks(item, visitor);
+            }
+        }
+        Block::DefinitionList(list) => {
+            for (term, definitions) in &list.content {
+                visit_inlines(term, visitor);
+                for definition in definitions {
+                    visit_blocks(definition, visitor);
+                }
+            }
+        }
+        Block::Header(header) => visit_inlines(&header.content, visitor),
+        Block::Table(table) => {
+            visit_caption(&table.caption, visitor);
+            visit_rows(&table.head.rows, visitor);
+            for body in &table.bodies {
+                visit_rows(&body.head, visitor);
+                visit_rows(&body.body, visitor);
+            }
+            visit_rows(&table.foot.rows, visitor);
+        }
+        Block::Figure(figure) => {
+            visit_caption(&figure.caption, visitor);
+            visit_blocks(&figure.content, visitor);
+        }
+        Block::Div(div) => visit_blocks(&div.content, visitor),
+        Block::BlockMetadata(meta) => {
+            for value in meta.meta.values() {
+                visit_meta_value(value, visitor);
+            }
+        }
+        Block::CodeBlock(_) | Block::RawBlock(_) | Block::HorizontalRule(_) => {}
+    }
+}
+
+pub fn visit_blocks(blocks: &[Block], visitor: &mut Visitor) {
+    for block in blocks {
+        visit_block(block, visitor);
+    }
+}
+
+pub fn visit_meta_value(value: &MetaValue, visitor: &mut Visitor) {
+    match value {
+        MetaValue::MetaMap(m) => {
+            for value in m.values() {
+                visit_meta_value(value, visitor);
+            }
+        }
+        MetaValue::MetaList(l) => {
+            for value in l {
+                visit_meta_value(value, visitor);
+            }
+        }
+        MetaValue::MetaBlocks(b) => visit_blocks(b, visitor),
+        MetaValue::MetaInlines(i) => visit_inlines(i, visitor),
+        MetaValue::MetaString(_) | MetaValue::MetaBool(_) => {}
+    }
+}
+
+// Visits the metadata, in the order of its keys, and then the body.
+pub fn visit(doc: &Pandoc, visitor: &mut Visitor) {
+    for value in doc.meta.values() {
+        visit_meta_value(value, visitor);
+    }
+    visit_blocks(&doc.blocks, visitor);
+}
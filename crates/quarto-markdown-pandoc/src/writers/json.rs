@@ -3,7 +3,12 @@
  * Copyright (c) 2025 Posit, PBC
  */
 
-use crate::pandoc::{Attr, Block, Caption, CitationMode, Inline, Inlines, ListAttributes, Pandoc};
+use crate::pandoc::attr::{attr_classes, attr_key_values};
+use crate::pandoc::{
+    Alignment, Attr, Block, Caption, Cell, CitationMode, ColSpec, ColWidth, Inline, Inlines,
+    ListAttributes, PANDOC_API_VERSION, Pandoc, Row, TableBody, pandoc_api_version_for,
+};
+use crate::transforms::legacy_figures::for_pandoc_version;
 use serde_json::{Value, json};
 
 fn write_location<T: crate::pandoc::location::SourceLocation>(item: &T) -> Value {
@@ -25,10 +30,9 @@ fn write_location<T: crate::pandoc::location::SourceLocation>(item: &T) -> Value
 
 fn write_attr(attr: &Attr) -> Value {
     json!([
-        attr.0, // id
-        attr.1, // classes
-        attr.2
-            .iter()
+        attr.0,                                 // id
+        attr_classes(attr).collect::<Vec<_>>(), // classes
+        attr_key_values(attr)
             .map(|(k, v)| json!([k, v]))
             .collect::<Vec<_>>()  // key-value pairs
     ])
@@ -129,22 +133,24 @@ fn write_inline(inline: &Inline) -> Value {
             "t": "Note",
             "c": write_blocks(&note.content)
         }),
-        // we can't test this just yet because
-        // our citationNoteNum counter doesn't match Pandoc's
+        // citationNoteNum doesn't match Pandoc's yet: we don't count notes
         Inline::Cite(cite) => json!({
             "t": "Cite",
-            "c": cite.citations.iter().map(|citation| {
-                json!({
-                    "citationId": citation.id.clone(),
-                    "citationPrefix": write_inlines(&citation.prefix),
-                    "citationSuffix": write_inlines(&citation.suffix),
-                    "citationMode": write_citation_mode(&citation.mode),
-                    "citationHash": citation.hash,
-                    "citationNoteNum": citation.note_num
-                })
-            }).collect::<Vec<_>>()
-        }),
-        Inline::Shortcode(_) | Inline::NoteReference(_) | Inline::Attr(_) => {
+            "c": [
+                cite.citations.iter().map(|citation| {
+                    json!({
+                        "citationId": citation.id.clone(),
+                        "citationPrefix": write_inlines(&citation.prefix),
+                        "citationSuffix": write_inlines(&citation.suffix),
+                        "citationMode": write_citation_mode(&citation.mode),
+                        "citationHash": citation.hash,
+                        "citationNoteNum": citation.note_num
+                    })
+                }).collect::<Vec<_>>(),
+                write_inlines(&cite.content)
+            ]
+        }),
+        Inline::Shortcode(_) | Inline::NoteReference(_) | Inline::Attr(..) => {
             panic!("Unsupported inline type: {:?}", inline)
         }
     }
@@ -184,7 +190,55 @@ fn write_blockss(blockss: &[Vec<Block>]) -> Value {
 fn write_caption(caption: &Caption) -> Value {
     json!([
         &caption.short.as_ref().map(|s| write_inlines(&s)),
-        &caption.long.as_ref().map(|l| write_blocks(&l)),
+        // Pandoc captions always have a (possibly empty) list of blocks
+        &caption.long.as_ref().map_or(json!([]), |l| write_blocks(l)),
+    ])
+}
+
+fn write_alignment(alignment: &Alignment) -> Value {
+    match alignment {
+        Alignment::Left => json!({"t": "AlignLeft"}),
+        Alignment::Right => json!({"t": "AlignRight"}),
+        Alignment::Center => json!({"t": "AlignCenter"}),
+        Alignment::Default => json!({"t": "AlignDefault"}),
+    }
+}
+
+fn write_colspec(colspec: &ColSpec) -> Value {
+    let width = match colspec.1 {
+        ColWidth::Default => json!({"t": "ColWidthDefault"}),
+        ColWidth::Percentage(percentage) => json!({"t": "ColWidth", "c": percentage}),
+    };
+    json!([write_alignment(&colspec.0), width])
+}
+
+fn write_cell(cell: &Cell) -> Value {
+    json!([
+        write_attr(&cell.attr),
+        write_alignment(&cell.alignment),
+        cell.row_span,
+        cell.col_span,
+        write_blocks(&cell.content),
+    ])
+}
+
+fn write_rows(rows: &[Row]) -> Value {
+    json!(
+        rows.iter()
+            .map(|row| json!([
+                write_attr(&row.attr),
+                row.cells.iter().map(write_cell).collect::<Vec<_>>()
+            ]))
+            .collect::<Vec<_>>()
+    )
+}
+
+fn write_table_body(body: &TableBody) -> Value {
+    json!([
+        write_attr(&body.attr),
+        body.rowhead_columns,
+        write_rows(&body.head),
+        write_rows(&body.body),
     ])
 }
 
@@ -229,7 +283,18 @@ fn write_block(block: &Block) -> Value {
             "t": "HorizontalRule",
             "l": write_location(block),
         }),
-        Block::Table(_) => panic!("unimplemented block: Table"),
+        Block::Table(table) => json!({
+            "t": "Table",
+            "c": [
+                write_attr(&table.attr),
+                write_caption(&table.caption),
+                table.colspec.iter().map(write_colspec).collect::<Vec<_>>(),
+                [write_attr(&table.head.attr), write_rows(&table.head.rows)],
+                table.bodies.iter().map(write_table_body).collect::<Vec<_>>(),
+                [write_attr(&table.foot.attr), write_rows(&table.foot.rows)],
+            ],
+            "l": write_location(table),
+        }),
 
         Block::Div(div) => json!({
             "t": "Div",
@@ -322,16 +387,63 @@ fn write_blocks(blocks: &[Block]) -> Value {
     json!(blocks.iter().map(write_block).collect::<Vec<_>>())
 }
 
-fn write_pandoc(pandoc: &Pandoc) -> Value {
+fn write_pandoc(pandoc: &Pandoc, api_version: [u32; 3]) -> Value {
     json!({
-        "pandoc-api-version": [1, 23, 1],
+        "pandoc-api-version": api_version,
         "meta": write_meta(&pandoc.meta),
         "blocks": write_blocks(&pandoc.blocks),
     })
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct JsonWriterOptions {
+    // Write non-ASCII characters as `\u` escapes.
+    pub ascii: bool,
+    // The Pandoc release (major, minor) the output is for. This sets the
+    // API version, and before 3.0, figures are written as implicit figures.
+    pub pandoc_version: Option<(u32, u32)>,
+}
+
+// Outside of strings, JSON is all ASCII, so the escapes can be applied to
+// the whole output. Characters beyond the BMP become surrogate pairs.
+fn escape_non_ascii(json: &str) -> String {
+    let mut result = String::with_capacity(json.len());
+    for ch in json.chars() {
+        if ch.is_ascii() {
+            result.push(ch);
+            continue;
+        }
+        let mut units = [0; 2];
+        for unit in ch.encode_utf16(&mut units) {
+            result.push_str(&format!("\\u{:04x}", unit));
+        }
+    }
+    result
+}
+
 pub fn write<W: std::io::Write>(pandoc: &Pandoc, writer: &mut W) -> std::io::Result<()> {
-    let json = write_pandoc(pandoc);
-    serde_json::to_writer(writer, &json)?;
-    Ok(())
+    write_with_options(pandoc, &JsonWriterOptions::default(), writer)
+}
+
+pub fn write_with_options<W: std::io::Write>(
+    pandoc: &Pandoc,
+    options: &JsonWriterOptions,
+    writer: &mut W,
+) -> std::io::Result<()> {
+    let converted = match options.pandoc_version {
+        Some(version) => for_pandoc_version(pandoc, version)
+            .map_err(|message| std::io::Error::new(std::io::ErrorKind::InvalidInput, message))?,
+        None => None,
+    };
+    let api_version = options
+        .pandoc_version
+        .and_then(pandoc_api_version_for)
+        .unwrap_or(PANDOC_API_VERSION);
+    let json = write_pandoc(converted.as_ref().unwrap_or(pandoc), api_version);
+    if !options.ascii {
+        serde_json::to_writer(writer, &json)?;
+        return Ok(());
+    }
+    let text = serde_json::to_string(&json)?;
+    write!(writer, "{}", escape_non_ascii(&text))
 }
@@ -0,0 +1,1552 @@
+/*
+ * markdown.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+use crate::filters::{Filter, FilterReturn::FilterResult, topdown_traverse_blocks};
+use crate::pandoc::attr::{attr_classes, attr_key_values, is_empty_attr};
+use crate::pandoc::location::empty_range;
+use crate::pandoc::{
+    Alignment, Attr, Block, Cell, Citation, CitationMode, Code, ColWidth, Inline, ListNumberDelim,
+    ListNumberStyle, MathType, Meta, Pandoc, QuoteType, Row, Shortcode, Space, Span, Table,
+    shortcode::ShortcodeArg,
+};
+use crate::utils::node_stats::block_name;
+use crate::writers::yaml::write_meta;
+
+// Whether `text`, at the start of a line, would be read as an ordered list
+// marker.
+fn is_ordered_list_marker(text: &str) -> bool {
+    let digits = text.trim_end_matches(['.', ')']);
+    text.len() == digits.len() + 1
+        && (1..=9).contains(&digits.len())
+        && digits.chars().all(|ch| ch.is_ascii_digit())
+}
+
+// Braces and angle brackets are always escaped, so text that looks like a
+// shortcode (`{{< x >}}`) or an escaped shortcode isn't read back as one.
+// Other characters are escaped where they would mean something: at the
+// start of a line (`line_start`), characters that start a block; in runs of
+// `-` and `.`, the characters after the first, so they aren't read as
+// dashes or an ellipsis; and quotes, unless they are inside a word.
+fn write_escaped_str<T: std::io::Write>(
+    text: &str,
+    line_start: bool,
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    let chars: Vec<char> = text.chars().collect();
+    let list_marker = line_start && is_ordered_list_marker(text);
+    for (i, &ch) in chars.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|j| chars[j]);
+        let next = chars.get(i + 1).copied();
+        match ch {
+            // `&` is only escaped where it would start a character reference
+            '&' if next.is_some_and(|next| next == '#' || next.is_ascii_alphanumeric()) => {
+                write!(buf, "\\&")?
+            }
+            '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '$' | '^' | '~' | '@' | '{' | '}'
+            | '#' | '|' => write!(buf, "\\{}", ch)?,
+            '"' | '\''
+                if !(prev.is_some_and(char::is_alphanumeric)
+                    && next.is_some_and(char::is_alphanumeric)) =>
+            {
+                write!(buf, "\\{}", ch)?
+            }
+            '+' | '-' | ':' | '=' if line_start && i == 0 => write!(buf, "\\{}", ch)?,
+            '.' | ')' if list_marker && i == chars.len() - 1 => write!(buf, "\\{}", ch)?,
+            '-' | '.' if prev == Some(ch) => write!(buf, "\\{}", ch)?,
+            _ if options.ascii && !ch.is_ascii() => write!(buf, "&#{};", ch as u32)?,
+            _ => write!(buf, "{}", ch)?,
+        }
+    }
+    Ok(())
+}
+
+// TeX commands for the non-ASCII characters that are common in math.
+fn tex_command(ch: char) -> Option<&'static str> {
+    Some(match ch {
+        'α' => "\\alpha",
+        'β' => "\\beta",
+        'γ' => "\\gamma",
+        'δ' => "\\delta",
+        'ε' => "\\epsilon",
+        'ζ' => "\\zeta",
+        'η' => "\\eta",
+        'θ' => "\\theta",
+        'ι' => "\\iota",
+        'κ' => "\\kappa",
+        'λ' => "\\lambda",
+        'μ' => "\\mu",
+        'ν' => "\\nu",
+        'ξ' => "\\xi",
+        'π' => "\\pi",
+        'ρ' => "\\rho",
+        'σ' => "\\sigma",
+        'τ' => "\\tau",
+        'υ' => "\\upsilon",
+        'φ' => "\\phi",
+        'χ' => "\\chi",
+        'ψ' => "\\psi",
+        'ω' => "\\omega",
+        'Γ' => "\\Gamma",
+        'Δ' => "\\Delta",
+        'Θ' => "\\Theta",
+        'Λ' => "\\Lambda",
+        'Ξ' => "\\Xi",
+        'Π' => "\\Pi",
+        'Σ' => "\\Sigma",
+        'Υ' => "\\Upsilon",
+        'Φ' => "\\Phi",
+        'Ψ' => "\\Psi",
+        'Ω' => "\\Omega",
+        '±' => "\\pm",
+        '×' => "\\times",
+        '÷' => "\\div",
+        '·' => "\\cdot",
+        '≤' => "\\leq",
+        '≥' => "\\geq",
+        '≠' => "\\neq",
+        '≈' => "\\approx",
+        '≡' => "\\equiv",
+        '∞' => "\\infty",
+        '→' => "\\to",
+        '←' => "\\leftarrow",
+        '↔' => "\\leftrightarrow",
+        '⇒' => "\\Rightarrow",
+        '⇐' => "\\Leftarrow",
+        '⇔' => "\\Leftrightarrow",
+        '∈' => "\\in",
+        '∉' => "\\notin",
+        '⊂' => "\\subset",
+        '⊆' => "\\subseteq",
+        '∪' => "\\cup",
+        '∩' => "\\cap",
+        '∅' => "\\emptyset",
+        '∀' => "\\forall",
+        '∃' => "\\exists",
+        '∂' => "\\partial",
+        '∇' => "\\nabla",
+        '∑' => "\\sum",
+        '∏' => "\\prod",
+        '∫' => "\\int",
+        '…' => "\\ldots",
+        _ => return None,
+    })
+}
+
+// Math is TeX, where character references mean nothing, so with `ascii`
+// its non-ASCII characters are written as the equivalent TeX commands, or
+// as `\char` with their code point when there is none.
+fn write_math_text<T: std::io::Write>(
+    text: &str,
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    if !options.ascii {
+        return write!(buf, "{}", text);
+    }
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch.is_ascii() {
+            write!(buf, "{}", ch)?;
+            continue;
+        }
+        // a letter would otherwise become part of the command name, and a
+        // hexadecimal digit part of the code point
+        let ends_before: fn(&char) -> bool = match tex_command(ch) {
+            Some(command) => {
+                write!(buf, "{}", command)?;
+                char::is_ascii_alphabetic
+            }
+            None => {
+                write!(buf, "\\char\"{:X}", ch as u32)?;
+                |next| next.is_ascii_digit() || ('A'..='F').contains(next)
+            }
+        };
+        if chars.peek().is_some_and(ends_before) {
+            write!(buf, " ")?;
+        }
+    }
+    Ok(())
+}
+
+fn write_quoted_attr_value<T: std::io::Write>(value: &str, buf: &mut T) -> std::io::Result<()> {
+    write!(buf, "\"")?;
+    for ch in value.chars() {
+        match ch {
+            '\\' | '"' => write!(buf, "\\{}", ch)?,
+            _ => write!(buf, "{}", ch)?,
+        }
+    }
+    write!(buf, "\"")?;
+    Ok(())
+}
+
+fn write_attr<T: std::io::Write>(attr: &Attr, buf: &mut T) -> std::io::Result<()> {
+    let mut parts: Vec<String> = Vec::new();
+    if !attr.0.is_empty() {
+        parts.push(format!("#{}", attr.0));
+    }
+    for class in attr_classes(attr) {
+        parts.push(format!(".{}", class));
+    }
+    for (key, value) in attr_key_values(attr) {
+        let value = render(|b| write_quoted_attr_value(value, b))?;
+        parts.push(format!("{}={}", key, value));
+    }
+    write!(buf, "{{{}}}", parts.join(" "))?;
+    Ok(())
+}
+
+fn write_inlines<T: std::io::Write>(
+    inlines: &[Inline],
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    write_inlines_from(inlines, false, options, buf)
+}
+
+// Writes inlines that start a line, such as the content of a paragraph.
+fn write_line_inlines<T: std::io::Write>(
+    inlines: &[Inline],
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    write_inlines_from(inlines, true, options, buf)
+}
+
+fn write_inlines_from<T: std::io::Write>(
+    inlines: &[Inline],
+    mut line_start: bool,
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    for inline in inlines {
+        match inline {
+            Inline::Str(s) => write_escaped_str(&s.text, line_start, options, buf)?,
+            _ => write_inline(inline, options, buf)?,
+        }
+        line_start = matches!(inline, Inline::SoftBreak(_) | Inline::LineBreak(_));
+    }
+    Ok(())
+}
+
+fn backtick_runs(text: &str) -> std::collections::HashSet<usize> {
+    let mut runs = std::collections::HashSet::new();
+    let mut current = 0;
+    for ch in text.chars() {
+        if ch == '`' {
+            current += 1;
+        } else if current > 0 {
+            runs.insert(current);
+            current = 0;
+        }
+    }
+    if current > 0 {
+        runs.insert(current);
+    }
+    runs
+}
+
+// writes `text` between backtick fences, as in inline code and raw inlines
+// Code and raw content are written verbatim, so with `ascii` their
+// non-ASCII characters can't be written at all.
+fn check_verbatim_ascii(
+    text: &str,
+    what: &str,
+    options: &MarkdownWriterOptions,
+) -> std::io::Result<()> {
+    match text.chars().find(|ch| !ch.is_ascii()) {
+        Some(ch) if options.ascii => Err(unsupported(format!("{:?} in {} as ASCII", ch, what))),
+        _ => Ok(()),
+    }
+}
+
+fn write_code_span<T: std::io::Write>(text: &str, buf: &mut T) -> std::io::Result<()> {
+    // the fence is the shortest run of backticks that doesn't appear in the text
+    let runs = backtick_runs(text);
+    let fence = "`".repeat((1..).find(|n| !runs.contains(n)).unwrap());
+    // CommonMark strips one space on each side, so that the text can
+    // start or end with a backtick without extending the fence
+    let padding = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    write!(buf, "{}{}{}{}{}", fence, padding, text, padding, fence)
+}
+
+fn write_code<T: std::io::Write>(
+    code: &Code,
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    check_verbatim_ascii(&code.text, "code", options)?;
+    write_code_span(&code.text, buf)?;
+    if !is_empty_attr(&code.attr) {
+        write_attr(&code.attr, buf)?;
+    }
+    Ok(())
+}
+
+fn write_target<T: std::io::Write>(target: &(String, String), buf: &mut T) -> std::io::Result<()> {
+    let (url, title) = target;
+    write!(buf, "(")?;
+    if url.is_empty() || url.contains(' ') {
+        write!(buf, "<{}>", url)?;
+    } else {
+        write!(buf, "{}", url)?;
+    }
+    if !title.is_empty() {
+        write!(buf, " ")?;
+        write_quoted_attr_value(title, buf)?;
+    }
+    write!(buf, ")")?;
+    Ok(())
+}
+
+fn write_citation<T: std::io::Write>(
+    citation: &Citation,
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    write_inlines(&citation.prefix, options, buf)?;
+    if citation.mode == CitationMode::SuppressAuthor {
+        write!(buf, "-")?;
+    }
+    write!(buf, "@{}", citation.id)?;
+    write_inlines(&citation.suffix, options, buf)?;
+    Ok(())
+}
+
+fn write_shortcode_arg<T: std::io::Write>(arg: &ShortcodeArg, buf: &mut T) -> std::io::Result<()> {
+    match arg {
+        ShortcodeArg::String(s) => {
+            if s.is_empty() || s.contains(char::is_whitespace) || s.contains('"') {
+                write_quoted_attr_value(s, buf)?;
+            } else {
+                write!(buf, "{}", s)?;
+            }
+        }
+        ShortcodeArg::Number(n) => write!(buf, "{}", n)?,
+        ShortcodeArg::Boolean(b) => write!(buf, "{}", b)?,
+        ShortcodeArg::Shortcode(shortcode) => write_shortcode(shortcode, buf)?,
+        ShortcodeArg::KeyValue(spec) => {
+            for (i, (key, value)) in spec.iter().enumerate() {
+                if i > 0 {
+                    write!(buf, " ")?;
+                }
+                write!(buf, "{}=", key)?;
+                write_shortcode_arg(value, buf)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_shortcode<T: std::io::Write>(shortcode: &Shortcode, buf: &mut T) -> std::io::Result<()> {
+    let (open, close) = if shortcode.is_escaped {
+        ("{{{<", ">}}}")
+    } else {
+        ("{{<", ">}}")
+    };
+    write!(buf, "{} {}", open, shortcode.name)?;
+    for arg in &shortcode.args {
+        write!(buf, " ")?;
+        write_shortcode_arg(arg, buf)?;
+    }
+    write!(buf, " {}", close)?;
+    Ok(())
+}
+
+fn is_shortcode_span(span: &Span) -> bool {
+    span.attr.1 == ["quarto-shortcode__"]
+}
+
+// The reader desugars shortcodes into spans (see `shortcode_to_span`),
+// which we write back as shortcodes.
+fn write_shortcode_span<T: std::io::Write>(span: &Span, buf: &mut T) -> std::io::Result<()> {
+    write!(buf, "{{{{<")?;
+    for param in &span.content {
+        let Inline::Span(param) = param else {
+            continue;
+        };
+        write!(buf, " ")?;
+        if is_shortcode_span(param) {
+            write_shortcode_span(param, buf)?;
+            continue;
+        }
+        let attrs = &param.attr.2;
+        if let Some(key) = attrs.get("data-key") {
+            write!(buf, "{}=", key)?;
+        }
+        let value = attrs.get("data-value").cloned().unwrap_or_default();
+        write_shortcode_arg(&ShortcodeArg::String(value), buf)?;
+    }
+    write!(buf, " >}}}}")?;
+    Ok(())
+}
+
+fn write_inline<T: std::io::Write>(
+    inline: &Inline,
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    match inline {
+        Inline::Str(s) => write_escaped_str(&s.text, false, options, buf)?,
+        Inline::Space(_) => write!(buf, " ")?,
+        Inline::SoftBreak(_) => writeln!(buf)?,
+        Inline::LineBreak(_) => writeln!(buf, "\\")?,
+        Inline::Emph(e) => {
+            write!(buf, "*")?;
+            write_inlines(&e.content, options, buf)?;
+            write!(buf, "*")?;
+        }
+        Inline::Strong(s) => {
+            write!(buf, "**")?;
+            write_inlines(&s.content, options, buf)?;
+            write!(buf, "**")?;
+        }
+        Inline::Strikeout(s) => {
+            write!(buf, "~~")?;
+            write_inlines(&s.content, options, buf)?;
+            write!(buf, "~~")?;
+        }
+        Inline::Superscript(s) => {
+            write!(buf, "^")?;
+            write_inlines(&s.content, options, buf)?;
+            write!(buf, "^")?;
+        }
+        Inline::Subscript(s) => {
+            write!(buf, "~")?;
+            write_inlines(&s.content, options, buf)?;
+            write!(buf, "~")?;
+        }
+        Inline::Underline(u) => {
+            write!(buf, "[")?;
+            write_inlines(&u.content, options, buf)?;
+            write!(buf, "]{{.underline}}")?;
+        }
+        Inline::SmallCaps(s) => {
+            write!(buf, "[")?;
+            write_inlines(&s.content, options, buf)?;
+            write!(buf, "]{{.smallcaps}}")?;
+        }
+        Inline::Quoted(q) => {
+            let quote = match q.quote_type {
+                QuoteType::SingleQuote => "'",
+                QuoteType::DoubleQuote => "\"",
+            };
+            write!(buf, "{}", quote)?;
+            write_inlines(&q.content, options, buf)?;
+            write!(buf, "{}", quote)?;
+        }
+        Inline::Cite(cite) => match cite.citations.as_slice() {
+            [citation] if citation.mode == CitationMode::AuthorInText => {
+                write!(buf, "@{}", citation.id)?;
+                if !citation.suffix.is_empty() {
+                    write!(buf, " [")?;
+                    write_inlines(&citation.suffix, options, buf)?;
+                    write!(buf, "]")?;
+                }
+            }
+            citations => {
+                write!(buf, "[")?;
+                for (i, citation) in citations.iter().enumerate() {
+                    if i > 0 {
+                        write!(buf, ";")?;
+                    }
+                    write_citation(citation, options, buf)?;
+                }
+                write!(buf, "]")?;
+            }
+        },
+        Inline::Code(code) => write_code(code, options, buf)?,
+        Inline::Math(math) => {
+            let delimiter = match math.math_type {
+                MathType::InlineMath => "$",
+                MathType::DisplayMath => "$$",
+            };
+            write!(buf, "{}", delimiter)?;
+            write_math_text(&math.text, options, buf)?;
+            write!(buf, "{}", delimiter)?;
+        }
+        Inline::RawInline(raw) => {
+            if raw.format == "quarto-internal-leftover" {
+                write!(buf, "{}", raw.text)?;
+            } else {
+                check_verbatim_ascii(&raw.text, "raw content", options)?;
+                write_code_span(&raw.text, buf)?;
+                write!(buf, "{{={}}}", raw.format)?;
+            }
+        }
+        Inline::Link(link) => {
+            let is_autolink = link.attr.1 == vec!["uri".to_string()]
+                && link.attr.0.is_empty()
+                && link.attr.2.is_empty()
+                && matches!(link.content.as_slice(), [Inline::Str(s)] if s.text == link.target.0);
+            if is_autolink {
+                write!(buf, "<{}>", link.target.0)?;
+            } else {
+                write!(buf, "[")?;
+                write_inlines(&link.content, options, buf)?;
+                write!(buf, "]")?;
+                write_target(&link.target, buf)?;
+                if !is_empty_attr(&link.attr) {
+                    write_attr(&link.attr, buf)?;
+                }
+            }
+        }
+        Inline::Image(image) => {
+            write!(buf, "![")?;
+            write_inlines(&image.content, options, buf)?;
+            write!(buf, "]")?;
+            write_target(&image.target, buf)?;
+            if !is_empty_attr(&image.attr) {
+                write_attr(&image.attr, buf)?;
+            }
+        }
+        Inline::Note(note) => {
+            // inline notes can only hold a single paragraph
+            write!(buf, "^[")?;
+            match note.content.as_slice() {
+                [] => {}
+                [
+                    Block::Paragraph(crate::pandoc::Paragraph { content, .. })
+                    | Block::Plain(crate::pandoc::Plain { content, .. }),
+                ] => write_inlines(content, options, buf)?,
+                [block] => {
+                    return Err(unsupported(format!(
+                        "a note holding a {} block",
+                        block_name(block)
+                    )));
+                }
+                _ => return Err(unsupported("a note with more than one block".to_string())),
+            }
+            write!(buf, "]")?;
+        }
+        Inline::Span(span) if is_shortcode_span(span) => write_shortcode_span(span, buf)?,
+        // like Pandoc, a span without attributes is written as its content
+        Inline::Span(span) if is_empty_attr(&span.attr) => {
+            write_inlines(&span.content, options, buf)?
+        }
+        Inline::Span(span) => {
+            write!(buf, "[")?;
+            write_inlines(&span.content, options, buf)?;
+            write!(buf, "]")?;
+            write_attr(&span.attr, buf)?;
+        }
+        Inline::Shortcode(shortcode) => write_shortcode(shortcode, buf)?,
+        Inline::NoteReference(note_reference) => write!(buf, "[^{}]", note_reference.id)?,
+        Inline::Attr(attr, _) => write_attr(attr, buf)?,
+    }
+    Ok(())
+}
+
+// The error for content that has no markdown syntax the reader would read
+// back as the same thing.
+fn unsupported(what: String) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("The markdown writer can't write {}", what),
+    )
+}
+
+fn write_front_matter<T: std::io::Write>(meta: &Meta, buf: &mut T) -> std::io::Result<()> {
+    writeln!(buf, "---")?;
+    write_meta(meta, buf)?;
+    writeln!(buf, "---")
+}
+
+fn render<F>(f: F) -> std::io::Result<String>
+where
+    F: FnOnce(&mut Vec<u8>) -> std::io::Result<()>,
+{
+    let mut buf = Vec::new();
+    f(&mut buf)?;
+    Ok(String::from_utf8(buf).expect("Invalid UTF-8 in markdown output"))
+}
+
+// Writes `text` with `first_prefix` before its first line and `prefix`
+// before the others. Prefixes are trimmed on empty lines.
+fn write_prefixed<T: std::io::Write>(
+    text: &str,
+    first_prefix: &str,
+    prefix: &str,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    for (i, line) in text.lines().enumerate() {
+        let line_prefix = if i == 0 { first_prefix } else { prefix };
+        if line.is_empty() {
+            writeln!(buf, "{}", line_prefix.trim_end())?;
+        } else {
+            writeln!(buf, "{}{}", line_prefix, line)?;
+        }
+    }
+    Ok(())
+}
+
+// The reader uses Plain for the items of tight lists, except for items
+// where the text is followed by another block (e.g. a nested list), which
+// get a Paragraph. A list is loose only if none of its items use Plain.
+fn is_tight_list(items: &[Vec<Block>]) -> bool {
+    items
+        .iter()
+        .any(|item| item.iter().any(|block| matches!(block, Block::Plain(_))))
+        || items.iter().all(|item| {
+            !matches!(item.as_slice(), [Block::Paragraph(_)])
+                && !item
+                    .windows(2)
+                    .any(|pair| matches!(pair, [Block::Paragraph(_), Block::Paragraph(_)]))
+        })
+}
+
+fn write_list_items<T: std::io::Write>(
+    items: &[Vec<Block>],
+    markers: &[String],
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    let tight = is_tight_list(items);
+    for (i, (item, marker)) in items.iter().zip(markers).enumerate() {
+        if i > 0 && !tight {
+            writeln!(buf)?;
+        }
+        let text = render(|b| write_blocks_with_spacing(item, tight, options, b))?;
+        let first_prefix = format!("{} ", marker);
+        let prefix = " ".repeat(first_prefix.len());
+        if text.is_empty() {
+            writeln!(buf, "{}", marker)?;
+        } else {
+            write_prefixed(&text, &first_prefix, &prefix, buf)?;
+        }
+    }
+    Ok(())
+}
+
+// Pandoc's definition list syntax: the term on its own line, then each
+// definition's blocks after a `:   ` marker, indented by four spaces. As with
+// lists, definitions are tight when they use Plain, and then there's no
+// blank line between the term and its definitions.
+//
+// The qmd reader doesn't parse definition lists, but documents from other
+// readers can have them.
+fn write_definition_list<T: std::io::Write>(
+    items: &[(Vec<Inline>, Vec<Vec<Block>>)],
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    for (i, (term, definitions)) in items.iter().enumerate() {
+        if i > 0 {
+            writeln!(buf)?;
+        }
+        write_line_inlines(term, options, buf)?;
+        writeln!(buf)?;
+        let tight = is_tight_list(definitions);
+        for definition in definitions {
+            if !tight {
+                writeln!(buf)?;
+            }
+            let text = render(|b| write_blocks_with_spacing(definition, tight, options, b))?;
+            if text.is_empty() {
+                writeln!(buf, ":")?;
+            } else {
+                write_prefixed(&text, ":   ", "    ", buf)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn to_roman(mut number: usize) -> String {
+    let numerals = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+    let mut result = String::new();
+    for (value, numeral) in numerals {
+        while number >= value {
+            result.push_str(numeral);
+            number -= value;
+        }
+    }
+    result
+}
+
+fn to_alpha(number: usize) -> String {
+    let mut result = String::new();
+    let mut n = number;
+    while n > 0 {
+        n -= 1;
+        result.insert(0, (b'a' + (n % 26) as u8) as char);
+        n /= 26;
+    }
+    result
+}
+
+fn ordered_list_marker(number: usize, style: &ListNumberStyle, delim: &ListNumberDelim) -> String {
+    let number = match style {
+        ListNumberStyle::Decimal | ListNumberStyle::Default => number.to_string(),
+        ListNumberStyle::LowerAlpha => to_alpha(number),
+        ListNumberStyle::UpperAlpha => to_alpha(number).to_uppercase(),
+        ListNumberStyle::LowerRoman => to_roman(number),
+        ListNumberStyle::UpperRoman => to_roman(number).to_uppercase(),
+    };
+    match delim {
+        ListNumberDelim::Period | ListNumberDelim::Default => format!("{}.", number),
+        ListNumberDelim::OneParen => format!("{})", number),
+        ListNumberDelim::TwoParens => format!("({})", number),
+    }
+}
+
+// code block fences are at least three backticks, and longer than any
+// backtick run in the content so that the content can't close them
+fn code_block_fence(text: &str) -> String {
+    let longest = backtick_runs(text).into_iter().max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+// the longest fence among the divs nested anywhere in `blocks`, or 0
+fn nested_div_fence_length(blocks: &[Block]) -> usize {
+    blocks
+        .iter()
+        .map(|block| match block {
+            Block::Div(div) => div_fence_length(&div.content),
+            Block::BlockQuote(quote) => nested_div_fence_length(&quote.content),
+            Block::Figure(figure) => nested_div_fence_length(&figure.content),
+            Block::BulletList(list) => list
+                .content
+                .iter()
+                .map(|item| nested_div_fence_length(item))
+                .max()
+                .unwrap_or(0),
+            Block::OrderedList(list) => list
+                .content
+                .iter()
+                .map(|item| nested_div_fence_length(item))
+                .max()
+                .unwrap_or(0),
+            Block::DefinitionList(list) => list
+                .content
+                .iter()
+                .flat_map(|(_, definitions)| definitions)
+                .map(|definition| nested_div_fence_length(definition))
+                .max()
+                .unwrap_or(0),
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+// like code fences, div fences are at least three colons, and longer than
+// the fences of any div nested inside
+fn div_fence_length(content: &[Block]) -> usize {
+    (nested_div_fence_length(content) + 1).max(3)
+}
+
+// Leading spaces in line blocks are significant. Pandoc represents them
+// as non-breaking spaces at the start of the line, which we write as `\ `.
+fn write_line_block_line<T: std::io::Write>(
+    line: &[Inline],
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    if line.is_empty() {
+        return writeln!(buf, "|");
+    }
+    let mut indent = 0;
+    let mut rest = line;
+    while let [Inline::Space(_), tail @ ..] = rest {
+        indent += 1;
+        rest = tail;
+    }
+    let mut first_text = "";
+    if let [Inline::Str(s), tail @ ..] = rest {
+        let trimmed = s.text.trim_start_matches(['\u{a0}', ' ']);
+        indent += s.text[..s.text.len() - trimmed.len()].chars().count();
+        first_text = trimmed;
+        rest = tail;
+    }
+    write!(buf, "| {}", "\\ ".repeat(indent))?;
+    write_escaped_str(first_text, false, options, buf)?;
+    write_inlines(rest, options, buf)?;
+    writeln!(buf)
+}
+
+fn write_code_block_info<T: std::io::Write>(attr: &Attr, buf: &mut T) -> std::io::Result<()> {
+    match attr {
+        (id, classes, attrs) if id.is_empty() && attrs.is_empty() && classes.len() <= 1 => {
+            if let Some(class) = classes.first() {
+                write!(buf, "{}", class)?;
+            }
+        }
+        _ => write_attr(attr, buf)?,
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableStyle {
+    // a GFM pipe table
+    Pipe,
+    // a grid table, for tables with block content, captions, row or column
+    // spans, etc.
+    Grid,
+}
+
+// the inline content of a cell, if the cell is simple enough for a pipe table
+fn pipe_table_cell_inlines(cell: &Cell) -> Option<&[Inline]> {
+    if cell.row_span != 1 || cell.col_span != 1 || !is_empty_attr(&cell.attr) {
+        return None;
+    }
+    let inlines: &[Inline] = match cell.content.as_slice() {
+        [] => &[],
+        [Block::Plain(plain)] => &plain.content,
+        _ => return None,
+    };
+    // a pipe table row is a single line
+    if inlines
+        .iter()
+        .any(|inline| matches!(inline, Inline::SoftBreak(_) | Inline::LineBreak(_)))
+    {
+        return None;
+    }
+    Some(inlines)
+}
+
+pub fn table_style(table: &Table) -> TableStyle {
+    let rows = || {
+        table
+            .head
+            .rows
+            .iter()
+            .chain(
+                table
+                    .bodies
+                    .iter()
+                    .flat_map(|body| body.head.iter().chain(&body.body)),
+            )
+            .chain(&table.foot.rows)
+    };
+    let is_pipe = is_empty_attr(&table.attr)
+        && table.caption.short.is_none()
+        && table
+            .caption
+            .long
+            .as_ref()
+            .is_none_or(|long| long.is_empty())
+        && table
+            .colspec
+            .iter()
+            .all(|(_, width)| *width == ColWidth::Default)
+        && table.head.rows.len() == 1
+        && table.foot.rows.is_empty()
+        && matches!(table.bodies.as_slice(), [body] if body.head.is_empty() && body.rowhead_columns == 0)
+        && rows().all(|row| {
+            is_empty_attr(&row.attr)
+                && row.cells.len() == table.colspec.len()
+                && row
+                    .cells
+                    .iter()
+                    .all(|cell| pipe_table_cell_inlines(cell).is_some())
+        });
+    if is_pipe {
+        TableStyle::Pipe
+    } else {
+        TableStyle::Grid
+    }
+}
+
+fn pipe_table_row_text(row: &Row, options: &MarkdownWriterOptions) -> std::io::Result<Vec<String>> {
+    row.cells
+        .iter()
+        .map(|cell| {
+            let mut inlines = pipe_table_cell_inlines(cell).unwrap_or(&[]);
+            while let [Inline::Space(_), rest @ ..] = inlines {
+                inlines = rest;
+            }
+            while let [rest @ .., Inline::Space(_)] = inlines {
+                inlines = rest;
+            }
+            render(|b| write_inlines(inlines, options, b))
+        })
+        .collect()
+}
+
+fn write_pipe_table_row<T: std::io::Write>(
+    cells: &[String],
+    widths: &[usize],
+    buf: &mut T,
+) -> std::io::Result<()> {
+    write!(buf, "|")?;
+    for (text, width) in cells.iter().zip(widths) {
+        let padding = width - text.chars().count();
+        write!(buf, " {}{} |", text, " ".repeat(padding))?;
+    }
+    writeln!(buf)
+}
+
+fn write_pipe_table<T: std::io::Write>(
+    table: &Table,
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    let head = pipe_table_row_text(&table.head.rows[0], options)?;
+    let body = table.bodies[0]
+        .body
+        .iter()
+        .map(|row| pipe_table_row_text(row, options))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let widths: Vec<usize> = (0..table.colspec.len())
+        .map(|i| {
+            std::iter::once(&head)
+                .chain(&body)
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(3)
+        })
+        .collect();
+    write_pipe_table_row(&head, &widths, buf)?;
+    write!(buf, "|")?;
+    for ((alignment, _), width) in table.colspec.iter().zip(&widths) {
+        let dashes = match alignment {
+            Alignment::Left => format!(":{}", "-".repeat(width - 1)),
+            Alignment::Center => format!(":{}:", "-".repeat(width - 2)),
+            Alignment::Right => format!("{}:", "-".repeat(width - 1)),
+            Alignment::Default => "-".repeat(*width),
+        };
+        write!(buf, " {} |", dashes)?;
+    }
+    writeln!(buf)?;
+    for row in &body {
+        write_pipe_table_row(row, &widths, buf)?;
+    }
+    Ok(())
+}
+
+// Breaks `inlines` into lines of at most `width` columns by turning spaces
+// into soft breaks. Other inlines (e.g. code, links) are never broken, so
+// words longer than `width` overflow.
+fn wrap_inlines(
+    inlines: &[Inline],
+    width: usize,
+    options: &MarkdownWriterOptions,
+) -> std::io::Result<Vec<Inline>> {
+    let mut words: Vec<&[Inline]> = Vec::new();
+    let mut rest = inlines;
+    while let Some(i) = rest
+        .iter()
+        .position(|inline| matches!(inline, Inline::Space(_) | Inline::SoftBreak(_)))
+    {
+        words.push(&rest[..i]);
+        rest = &rest[i + 1..];
+    }
+    words.push(rest);
+
+    let mut result = Vec::new();
+    let mut line_width = 0;
+    for word in words.into_iter().filter(|word| !word.is_empty()) {
+        let word_width = render(|b| write_inlines(word, options, b))?.chars().count();
+        if line_width > 0 {
+            let break_line = line_width + 1 + word_width > width;
+            result.push(if break_line {
+                Inline::SoftBreak(crate::pandoc::SoftBreak {
+                    filename: None,
+                    range: empty_range(),
+                })
+            } else {
+                Inline::Space(crate::pandoc::Space {
+                    filename: None,
+                    range: empty_range(),
+                })
+            });
+            line_width = if break_line { 0 } else { line_width + 1 };
+        }
+        result.extend_from_slice(word);
+        line_width += word_width;
+    }
+    Ok(result)
+}
+
+// the lines of a grid table cell's content
+fn grid_table_cell_lines(
+    cell: &Cell,
+    options: &MarkdownWriterOptions,
+) -> std::io::Result<Vec<String>> {
+    let text = match options.max_table_column_width {
+        None => render(|b| write_blocks(&cell.content, options, b))?,
+        Some(width) => {
+            let mut content = cell.content.clone();
+            for block in &mut content {
+                if let Block::Plain(crate::pandoc::Plain { content, .. })
+                | Block::Paragraph(crate::pandoc::Paragraph { content, .. }) = block
+                {
+                    *content = wrap_inlines(content, width, options)?;
+                }
+            }
+            render(|b| write_blocks(&content, options, b))?
+        }
+    };
+    Ok(text
+        .trim_end()
+        .lines()
+        .map(|line| line.trim_end().to_string())
+        .collect())
+}
+
+// A grid table cell, at the top left slot of the grid it covers.
+struct GridCell {
+    lines: Vec<String>,
+    row: usize,
+    col: usize,
+    row_span: usize,
+    col_span: usize,
+}
+
+// Places the cells of `rows`, the first of which is grid row `first_row`,
+// recording in `slots` the index of the cell covering each slot. Spans are
+// cut short where they would leave `rows` or overlap another cell, and the
+// slots no cell covers get an empty cell.
+fn place_grid_cells(
+    rows: &[Row],
+    first_row: usize,
+    column_count: usize,
+    options: &MarkdownWriterOptions,
+    cells: &mut Vec<GridCell>,
+    slots: &mut Vec<Vec<usize>>,
+) -> std::io::Result<()> {
+    let mut placed: Vec<Vec<Option<usize>>> = vec![vec![None; column_count]; rows.len()];
+    for (i, row) in rows.iter().enumerate() {
+        let mut col = 0;
+        for cell in &row.cells {
+            while col < column_count && placed[i][col].is_some() {
+                col += 1;
+            }
+            if col == column_count {
+                break;
+            }
+            let col_span = (col..column_count)
+                .take(cell.col_span.max(1))
+                .take_while(|c| placed[i][*c].is_none())
+                .count();
+            let row_span = cell.row_span.clamp(1, rows.len() - i);
+            for placed_row in &mut placed[i..i + row_span] {
+                for slot in &mut placed_row[col..col + col_span] {
+                    *slot = Some(cells.len());
+                }
+            }
+            cells.push(GridCell {
+                lines: grid_table_cell_lines(cell, options)?,
+                row: first_row + i,
+                col,
+                row_span,
+                col_span,
+            });
+            col += col_span;
+        }
+        for (col, slot) in placed[i].iter_mut().enumerate() {
+            if slot.is_none() {
+                *slot = Some(cells.len());
+                cells.push(GridCell {
+                    lines: Vec::new(),
+                    row: first_row + i,
+                    col,
+                    row_span: 1,
+                    col_span: 1,
+                });
+            }
+        }
+    }
+    slots.extend(
+        placed
+            .into_iter()
+            .map(|row| row.into_iter().map(Option::unwrap).collect()),
+    );
+    Ok(())
+}
+
+// The cells of a grid table, where they are, and the size of its columns
+// and rows (not counting the border lines).
+struct GridLayout {
+    cells: Vec<GridCell>,
+    slots: Vec<Vec<usize>>,
+    widths: Vec<usize>,
+    heights: Vec<usize>,
+}
+
+impl GridLayout {
+    // Writes line `line` of `cell`, padded to the width of the columns it
+    // spans. The lines of a cell that spans rows go on through the border
+    // lines between them.
+    fn write_cell_line<T: std::io::Write>(
+        &self,
+        cell: &GridCell,
+        line: usize,
+        buf: &mut T,
+    ) -> std::io::Result<()> {
+        let columns = cell.col..cell.col + cell.col_span;
+        let width = self.widths[columns].iter().sum::<usize>() + 3 * (cell.col_span - 1);
+        let text = cell.lines.get(line).map_or("", |text| text.as_str());
+        write!(
+            buf,
+            " {}{} ",
+            text,
+            " ".repeat(width - text.chars().count())
+        )
+    }
+
+    // how many lines of `cell` come before the rows from `row` on
+    fn lines_before(&self, cell: &GridCell, row: usize) -> usize {
+        self.heights[cell.row..row].iter().map(|h| h + 1).sum()
+    }
+
+    // A border line such as `+:----+-----:+`, above grid row `row` (or
+    // below the last one). Cells that span the rows on both sides go through
+    // it. Alignments are only marked on the line below the header, or on the
+    // top line of a headless table.
+    fn write_border<T: std::io::Write>(
+        &self,
+        row: usize,
+        fill: char,
+        alignments: Option<&[Alignment]>,
+        buf: &mut T,
+    ) -> std::io::Result<()> {
+        let spanning = |col: usize| match (row.checked_sub(1), self.slots.get(row)) {
+            (Some(above), Some(below)) if self.slots[above][col] == below[col] => {
+                Some(&self.cells[below[col]])
+            }
+            _ => None,
+        };
+        let mut col = 0;
+        let mut after_border = false;
+        while col < self.widths.len() {
+            match spanning(col) {
+                Some(cell) => {
+                    write!(buf, "{}", if after_border { '+' } else { '|' })?;
+                    let line = self.lines_before(cell, row) - 1;
+                    self.write_cell_line(cell, line, buf)?;
+                    col += cell.col_span;
+                    after_border = false;
+                }
+                None => {
+                    let (left, right) = match alignments.map(|alignments| &alignments[col]) {
+                        Some(Alignment::Left) => (':', fill),
+                        Some(Alignment::Center) => (':', ':'),
+                        Some(Alignment::Right) => (fill, ':'),
+                        _ => (fill, fill),
+                    };
+                    let segment = fill.to_string().repeat(self.widths[col]);
+                    write!(buf, "+{}{}{}", left, segment, right)?;
+                    col += 1;
+                    after_border = true;
+                }
+            }
+        }
+        writeln!(buf, "{}", if after_border { '+' } else { '|' })
+    }
+
+    fn write_row<T: std::io::Write>(&self, row: usize, buf: &mut T) -> std::io::Result<()> {
+        for line in 0..self.heights[row] {
+            let mut col = 0;
+            while col < self.widths.len() {
+                let cell = &self.cells[self.slots[row][col]];
+                write!(buf, "|")?;
+                self.write_cell_line(cell, self.lines_before(cell, row) + line, buf)?;
+                col += cell.col_span;
+            }
+            writeln!(buf, "|")?;
+        }
+        Ok(())
+    }
+}
+
+// Grid tables can't represent multiple bodies, so their rows are written
+// one after the other, with each body's intermediate head rows as plain
+// rows. Row spans end with the part of the table they are in.
+fn write_grid_table<T: std::io::Write>(
+    table: &Table,
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    let column_count = table.colspec.len();
+    let mut cells = Vec::new();
+    let mut slots = Vec::new();
+    let mut place = |rows: &[Row], slots: &mut Vec<Vec<usize>>| {
+        place_grid_cells(rows, slots.len(), column_count, options, &mut cells, slots)
+    };
+    place(&table.head.rows, &mut slots)?;
+    let head_count = slots.len();
+    for table_body in &table.bodies {
+        place(&table_body.head, &mut slots)?;
+        place(&table_body.body, &mut slots)?;
+    }
+    let body_count = slots.len() - head_count;
+    place(&table.foot.rows, &mut slots)?;
+    let row_count = slots.len();
+    let foot_count = row_count - head_count - body_count;
+
+    let natural_width = |cell: &GridCell| {
+        cell.lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+    let mut widths: Vec<usize> = (0..column_count)
+        .map(|i| {
+            let natural = cells
+                .iter()
+                .filter(|cell| cell.col == i && cell.col_span == 1)
+                .map(natural_width)
+                .max()
+                .unwrap_or(0);
+            // keep relative column widths, as a share of a 72 column table
+            let relative = match table.colspec[i].1 {
+                ColWidth::Percentage(p) => ((p * 72.0).round() as usize).saturating_sub(3),
+                ColWidth::Default => 0,
+            };
+            natural.max(relative).max(3)
+        })
+        .collect();
+    let mut heights: Vec<usize> = (0..row_count)
+        .map(|i| {
+            cells
+                .iter()
+                .filter(|cell| cell.row == i && cell.row_span == 1)
+                .map(|cell| cell.lines.len())
+                .max()
+                .unwrap_or(0)
+                .max(1)
+        })
+        .collect();
+    // spanning cells also have the room between the columns and rows they
+    // span; the last one grows to fit whatever doesn't fit there
+    for cell in &cells {
+        let columns = cell.col..cell.col + cell.col_span;
+        let width = widths[columns.clone()].iter().sum::<usize>() + 3 * (cell.col_span - 1);
+        widths[columns.end - 1] += natural_width(cell).saturating_sub(width);
+        let rows = cell.row..cell.row + cell.row_span;
+        let height = heights[rows.clone()].iter().sum::<usize>() + cell.row_span - 1;
+        heights[rows.end - 1] += cell.lines.len().saturating_sub(height);
+    }
+    let alignments: Vec<Alignment> = table.colspec.iter().map(|(a, _)| a.clone()).collect();
+
+    let layout = GridLayout {
+        cells,
+        slots,
+        widths,
+        heights,
+    };
+
+    // the head is closed by a `=` line, and the foot enclosed in them
+    for row in 0..=row_count {
+        let fill = if (head_count > 0 && row == head_count)
+            || (foot_count > 0 && (row == head_count + body_count || row == row_count))
+        {
+            '='
+        } else {
+            '-'
+        };
+        let aligned = (row == head_count).then_some(alignments.as_slice());
+        layout.write_border(row, fill, aligned, buf)?;
+        if row < row_count {
+            layout.write_row(row, buf)?;
+        }
+    }
+    if let Some(long) = &table.caption.long {
+        let caption: Vec<&[Inline]> = long
+            .iter()
+            .filter_map(|block| match block {
+                Block::Plain(crate::pandoc::Plain { content, .. })
+                | Block::Paragraph(crate::pandoc::Paragraph { content, .. }) => {
+                    Some(content.as_slice())
+                }
+                _ => None,
+            })
+            .collect();
+        if !caption.is_empty() {
+            write!(buf, "\n:")?;
+            for inlines in caption {
+                write!(buf, " ")?;
+                write_inlines(inlines, options, buf)?;
+            }
+            writeln!(buf)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_table<T: std::io::Write>(
+    table: &Table,
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    match table_style(table) {
+        TableStyle::Pipe => write_pipe_table(table, options, buf),
+        TableStyle::Grid => write_grid_table(table, options, buf),
+    }
+}
+
+fn write_block_with_bullet<T: std::io::Write>(
+    bullet: &str,
+    block: &Block,
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    match block {
+        Block::Plain(crate::pandoc::Plain { content, .. })
+        | Block::Paragraph(crate::pandoc::Paragraph { content, .. }) => {
+            write_line_inlines(content, options, buf)?;
+            writeln!(buf)?;
+        }
+        Block::Header(crate::pandoc::Header {
+            level,
+            attr,
+            content,
+            ..
+        }) => {
+            write!(buf, "{}", "#".repeat(*level))?;
+            if !content.is_empty() {
+                write!(buf, " ")?;
+                write_inlines(content, options, buf)?;
+            }
+            if !is_empty_attr(attr) {
+                write!(buf, " ")?;
+                write_attr(attr, buf)?;
+            }
+            writeln!(buf)?;
+        }
+        Block::CodeBlock(crate::pandoc::CodeBlock { attr, text, .. }) => {
+            let expanded;
+            let text = match options.expand_tabs {
+                Some(tab_stop) => {
+                    expanded = expand_tabs(text, tab_stop);
+                    &expanded
+                }
+                None => text,
+            };
+            check_verbatim_ascii(text, "code", options)?;
+            let fence = code_block_fence(text);
+            write!(buf, "{}", fence)?;
+            write_code_block_info(attr, buf)?;
+            writeln!(buf)?;
+            if !text.is_empty() {
+                writeln!(buf, "{}", text)?;
+            }
+            writeln!(buf, "{}", fence)?;
+        }
+        Block::RawBlock(crate::pandoc::RawBlock { format, text, .. }) => {
+            check_verbatim_ascii(text, "raw content", options)?;
+            let fence = code_block_fence(text);
+            writeln!(buf, "{}{{={}}}", fence, format)?;
+            if !text.is_empty() {
+                writeln!(buf, "{}", text)?;
+            }
+            writeln!(buf, "{}", fence)?;
+        }
+        Block::BlockQuote(crate::pandoc::BlockQuote { content, .. }) => {
+            let text = render(|b| write_blocks(content, options, b))?;
+            write_prefixed(&text, "> ", "> ", buf)?;
+        }
+        Block::BulletList(crate::pandoc::BulletList { content, .. }) => {
+            let markers = vec![bullet.to_string(); content.len()];
+            write_list_items(content, &markers, options, buf)?;
+        }
+        Block::OrderedList(crate::pandoc::OrderedList { attr, content, .. }) => {
+            let (start, style, delim) = attr;
+            let markers: Vec<String> = (0..content.len())
+                .map(|i| ordered_list_marker(start + i, style, delim))
+                .collect();
+            write_list_items(content, &markers, options, buf)?;
+        }
+        Block::LineBlock(crate::pandoc::LineBlock { content, .. }) => {
+            for line in content {
+                write_line_block_line(line, options, buf)?;
+            }
+        }
+        Block::DefinitionList(crate::pandoc::DefinitionList { content, .. }) => {
+            write_definition_list(content, options, buf)?;
+        }
+        Block::HorizontalRule(_) => writeln!(buf, "***")?,
+        Block::Div(crate::pandoc::Div { attr, content, .. }) => {
+            let fence = ":".repeat(div_fence_length(content));
+            write!(buf, "{} ", fence)?;
+            write_attr(attr, buf)?;
+            writeln!(buf)?;
+            write_blocks(content, options, buf)?;
+            writeln!(buf, "{}", fence)?;
+        }
+        Block::Figure(crate::pandoc::Figure {
+            attr,
+            caption,
+            content,
+            ..
+        }) => match content.as_slice() {
+            // the reader's implicit figures: a lone image whose description is the caption
+            [Block::Plain(plain)] if matches!(plain.content.as_slice(), [Inline::Image(_)]) => {
+                let Inline::Image(image) = &plain.content[0] else {
+                    unreachable!()
+                };
+                let mut image_attr = image.attr.clone();
+                image_attr.0.clone_from(&attr.0);
+                write!(buf, "![")?;
+                match caption.long.as_deref() {
+                    Some([Block::Plain(p)]) => write_inlines(&p.content, options, buf)?,
+                    _ => write_inlines(&image.content, options, buf)?,
+                }
+                write!(buf, "]")?;
+                write_target(&image.target, buf)?;
+                if !is_empty_attr(&image_attr) {
+                    write_attr(&image_attr, buf)?;
+                }
+                writeln!(buf)?;
+            }
+            _ => {
+                return Err(unsupported(
+                    "a figure whose content isn't a single image".to_string(),
+                ));
+            }
+        },
+        Block::Table(table) => write_table(table, options, buf)?,
+        Block::BlockMetadata(meta) => write_front_matter(&meta.meta, buf)?,
+    }
+    Ok(())
+}
+
+// In tight list items, the text is directly followed by e.g. a nested list;
+// a blank line there would make the list loose.
+fn write_blocks_with_spacing<T: std::io::Write>(
+    blocks: &[Block],
+    tight: bool,
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    let mut bullet = "*";
+    for (i, block) in blocks.iter().enumerate() {
+        if i > 0 {
+            // adjacent bullet lists with the same marker would be read as one list
+            if matches!(
+                (&blocks[i - 1], block),
+                (Block::BulletList(_), Block::BulletList(_))
+            ) {
+                bullet = if bullet == "*" { "-" } else { "*" };
+            }
+            let follows_text = match blocks[i - 1] {
+                Block::Plain(_) => true,
+                Block::Paragraph(_) => tight,
+                _ => false,
+            };
+            if !follows_text || matches!(block, Block::Plain(_) | Block::Paragraph(_)) {
+                writeln!(buf)?;
+            }
+        }
+        write_block_with_bullet(bullet, block, options, buf)?;
+    }
+    Ok(())
+}
+
+fn write_blocks<T: std::io::Write>(
+    blocks: &[Block],
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    write_blocks_with_spacing(blocks, false, options, buf)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownWriterOptions {
+    // Expand tabs in code blocks to spaces, with tab stops every this
+    // many columns. Tabs are written as they are when this is None.
+    pub expand_tabs: Option<usize>,
+    // Wrap the content of grid table cells at this many columns, at word
+    // boundaries. Cells are as wide as their longest line when this is None.
+    pub max_table_column_width: Option<usize>,
+    // Write non-ASCII characters in text as numeric character references,
+    // and in math as TeX commands. Code and raw content are verbatim in
+    // markdown, so non-ASCII characters there are an error.
+    pub ascii: bool,
+    // Write soft breaks as spaces, so each paragraph ends up on a single
+    // line. Grid table cells wrapped by `max_table_column_width` still get
+    // their line breaks, since that wrapping happens after this.
+    pub soft_break_as_space: bool,
+}
+
+fn expand_tabs(text: &str, tab_stop: usize) -> String {
+    let tab_stop = tab_stop.max(1);
+    let mut result = String::with_capacity(text.len());
+    let mut column = 0;
+    for ch in text.chars() {
+        match ch {
+            '\t' => {
+                let width = tab_stop - column % tab_stop;
+                result.push_str(&" ".repeat(width));
+                column += width;
+            }
+            '\n' => {
+                result.push(ch);
+                column = 0;
+            }
+            _ => {
+                result.push(ch);
+                column += 1;
+            }
+        }
+    }
+    result
+}
+
+// Markdown text for inline content, e.g. for metadata values.
+pub fn inlines_to_markdown(inlines: &[Inline]) -> String {
+    render(|b| write_line_inlines(inlines, &MarkdownWriterOptions::default(), b))
+        .expect("writing to a Vec can't fail")
+}
+
+pub fn blocks_to_markdown(blocks: &[Block]) -> String {
+    render(|b| write_blocks(blocks, &MarkdownWriterOptions::default(), b))
+        .expect("writing to a Vec can't fail")
+}
+
+pub fn write<T: std::io::Write>(pandoc: &Pandoc, buf: &mut T) -> std::io::Result<()> {
+    write_with_options(pandoc, &MarkdownWriterOptions::default(), buf)
+}
+
+pub fn write_with_options<T: std::io::Write>(
+    pandoc: &Pandoc,
+    options: &MarkdownWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    if !pandoc.meta.is_empty() {
+        write_front_matter(&pandoc.meta, buf)?;
+        if !pandoc.blocks.is_empty() {
+            writeln!(buf)?;
+        }
+    }
+    if !options.soft_break_as_space {
+        return write_blocks(&pandoc.blocks, options, buf);
+    }
+    let mut filter = Filter::new().with_soft_break(|sb| {
+        FilterResult(
+            vec![Inline::Space(Space {
+                filename: sb.filename,
+                range: sb.range,
+            })],
+            false,
+        )
+    });
+    let blocks = topdown_traverse_blocks(pandoc.blocks.clone(), &mut filter);
+    write_blocks(&blocks, options, buf)
+}
@@ -4,4 +4,6 @@
  */
 
 pub mod json;
+pub mod markdown;
 pub mod native;
+pub mod yaml;
@@ -3,9 +3,11 @@
  * Copyright (c) 2025 Posit, PBC
  */
 
+use crate::pandoc::attr::{attr_classes, attr_key_values};
 use crate::pandoc::{
     Attr, Block, Citation, CitationMode, Inline, ListNumberDelim, MathType, Pandoc, QuoteType,
 };
+use crate::transforms::legacy_figures::for_pandoc_version;
 
 fn write_safe_string<T: std::io::Write>(text: &str, buf: &mut T) -> std::io::Result<()> {
     write!(buf, "\"")?;
@@ -22,12 +24,11 @@ fn write_safe_string<T: std::io::Write>(text: &str, buf: &mut T) -> std::io::Res
 }
 
 fn write_native_attr<T: std::io::Write>(attr: &Attr, buf: &mut T) -> std::io::Result<()> {
-    let (id, classes, attrs) = attr;
     write!(buf, "( ")?;
-    write_safe_string(&id, buf)?;
+    write_safe_string(&attr.0, buf)?;
     write!(buf, " , [")?;
 
-    for (i, class) in classes.iter().enumerate() {
+    for (i, class) in attr_classes(attr).enumerate() {
         if i > 0 {
             write!(buf, ", ")?;
         }
@@ -36,7 +37,7 @@ fn write_native_attr<T: std::io::Write>(attr: &Attr, buf: &mut T) -> std::io::Re
 
     write!(buf, "] , [")?;
 
-    for (i, (k, v)) in attrs.iter().enumerate() {
+    for (i, (k, v)) in attr_key_values(attr).enumerate() {
         if i > 0 {
             write!(buf, ", ")?;
         }
@@ -568,7 +569,59 @@ fn write_block<T: std::io::Write>(block: &Block, buf: &mut T) -> std::io::Result
     Ok(())
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct NativeWriterOptions {
+    // Write non-ASCII characters as numeric escapes, as Haskell's `show` does.
+    pub ascii: bool,
+    // The Pandoc release (major, minor) the output is for. Before 3.0,
+    // figures are written as implicit figures.
+    pub pandoc_version: Option<(u32, u32)>,
+}
+
+// Outside of strings, the native format is all ASCII, so the escapes can be
+// applied to the whole output. `\&` ends an escape that a digit follows.
+fn escape_non_ascii(native: &str) -> String {
+    let mut result = String::with_capacity(native.len());
+    let mut after_escape = false;
+    for ch in native.chars() {
+        if ch.is_ascii() {
+            if after_escape && ch.is_ascii_digit() {
+                result.push_str("\\&");
+            }
+            result.push(ch);
+            after_escape = false;
+        } else {
+            result.push_str(&format!("\\{}", ch as u32));
+            after_escape = true;
+        }
+    }
+    result
+}
+
+pub fn write_with_options<T: std::io::Write>(
+    pandoc: &Pandoc,
+    options: &NativeWriterOptions,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    let converted = match options.pandoc_version {
+        Some(version) => for_pandoc_version(pandoc, version)
+            .map_err(|message| std::io::Error::new(std::io::ErrorKind::InvalidInput, message))?,
+        None => None,
+    };
+    let pandoc = converted.as_ref().unwrap_or(pandoc);
+    if !options.ascii {
+        return write(pandoc, buf);
+    }
+    let mut native = Vec::new();
+    write(pandoc, &mut native)?;
+    let native = String::from_utf8(native).expect("Invalid UTF-8 in native output");
+    write!(buf, "{}", escape_non_ascii(&native))
+}
+
 pub fn write<T: std::io::Write>(pandoc: &Pandoc, mut buf: &mut T) -> std::io::Result<()> {
+    if pandoc.blocks.is_empty() {
+        return write!(buf, "[]");
+    }
     write!(buf, "[ ")?;
     for (i, block) in pandoc.blocks.iter().enumerate() {
         if i > 0 {
@@ -0,0 +1,157 @@
+/*
+ * yaml.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+// Writes document metadata as YAML, inverting the front matter parse.
+// Inline and block values are written back as markdown.
+
+use crate::pandoc::{Meta, MetaValue};
+use crate::writers::markdown::{blocks_to_markdown, inlines_to_markdown};
+use indexmap::IndexMap;
+
+fn needs_quotes(text: &str) -> bool {
+    let Some(first) = text.chars().next() else {
+        return true;
+    };
+    // scalars that YAML would read as something other than a string
+    let lower = text.to_lowercase();
+    if matches!(
+        lower.as_str(),
+        "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "~"
+    ) {
+        return true;
+    }
+    "-?:,[]{}#&*!|>'\"%@`".contains(first)
+        || text.starts_with(char::is_whitespace)
+        || text.ends_with(char::is_whitespace)
+        || text.ends_with(':')
+        || text.contains(": ")
+        || text.contains(" #")
+        || text.contains(char::is_control)
+}
+
+fn write_scalar<T: std::io::Write>(text: &str, buf: &mut T) -> std::io::Result<()> {
+    if !needs_quotes(text) {
+        return write!(buf, "{}", text);
+    }
+    write!(buf, "\"")?;
+    for ch in text.chars() {
+        match ch {
+            '\\' => write!(buf, "\\\\")?,
+            '"' => write!(buf, "\\\"")?,
+            '\n' => write!(buf, "\\n")?,
+            '\t' => write!(buf, "\\t")?,
+            _ => write!(buf, "{}", ch)?,
+        }
+    }
+    write!(buf, "\"")
+}
+
+// A literal block scalar (`|`), with its lines indented by `indent`.
+fn write_block_scalar<T: std::io::Write>(
+    text: &str,
+    indent: usize,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    // the indentation must be explicit if the first line starts with spaces
+    if text.starts_with(' ') {
+        writeln!(buf, "|{}", indent)?;
+    } else {
+        writeln!(buf, "|")?;
+    }
+    for line in text.trim_end_matches('\n').lines() {
+        if line.is_empty() {
+            writeln!(buf)?;
+        } else {
+            writeln!(buf, "{}{}", " ".repeat(indent), line)?;
+        }
+    }
+    Ok(())
+}
+
+// Writes `value` after a `key:` or `-`, starting with the separating
+// space or newline.
+fn write_value<T: std::io::Write>(
+    value: &MetaValue,
+    indent: usize,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    match value {
+        MetaValue::MetaString(text) => {
+            write!(buf, " ")?;
+            write_scalar(text, buf)?;
+            writeln!(buf)
+        }
+        MetaValue::MetaBool(b) => writeln!(buf, " {}", b),
+        MetaValue::MetaInlines(inlines) => {
+            write!(buf, " ")?;
+            write_scalar(&inlines_to_markdown(inlines), buf)?;
+            writeln!(buf)
+        }
+        MetaValue::MetaBlocks(blocks) => {
+            write!(buf, " ")?;
+            write_block_scalar(&blocks_to_markdown(blocks), indent + 2, buf)
+        }
+        MetaValue::MetaList(list) if list.is_empty() => writeln!(buf, " []"),
+        MetaValue::MetaMap(map) if map.is_empty() => writeln!(buf, " {{}}"),
+        MetaValue::MetaList(list) => {
+            writeln!(buf)?;
+            write_list(list, indent + 2, buf)
+        }
+        MetaValue::MetaMap(map) => {
+            writeln!(buf)?;
+            write_map(map, indent + 2, buf)
+        }
+    }
+}
+
+fn write_map<T: std::io::Write>(
+    map: &IndexMap<String, MetaValue>,
+    indent: usize,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    for (key, value) in map {
+        write!(buf, "{}", " ".repeat(indent))?;
+        write_scalar(key, buf)?;
+        write!(buf, ":")?;
+        write_value(value, indent, buf)?;
+    }
+    Ok(())
+}
+
+fn write_list<T: std::io::Write>(
+    list: &[MetaValue],
+    indent: usize,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    for item in list {
+        match item {
+            // nested collections start on the same line as their `-`
+            MetaValue::MetaMap(map) if !map.is_empty() => {
+                let mut nested = Vec::new();
+                write_map(map, indent + 2, &mut nested)?;
+                write!(buf, "{}- ", " ".repeat(indent))?;
+                buf.write_all(&nested[indent + 2..])?;
+            }
+            MetaValue::MetaList(list) if !list.is_empty() => {
+                let mut nested = Vec::new();
+                write_list(list, indent + 2, &mut nested)?;
+                write!(buf, "{}- ", " ".repeat(indent))?;
+                buf.write_all(&nested[indent + 2..])?;
+            }
+            _ => {
+                write!(buf, "{}-", " ".repeat(indent))?;
+                write_value(item, indent, buf)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn write_meta<T: std::io::Write>(meta: &Meta, buf: &mut T) -> std::io::Result<()> {
+    if meta.is_empty() {
+        return writeln!(buf, "{{}}");
+    }
+    write_map(meta, 0, buf)
+}
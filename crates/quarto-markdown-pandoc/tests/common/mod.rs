@@ -0,0 +1,39 @@
+/*
+ * mod.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+// Helpers shared by the integration tests. Each test binary only uses some
+// of them.
+#![allow(dead_code)]
+
+use quarto_markdown_pandoc::pandoc::location::empty_range;
+use quarto_markdown_pandoc::pandoc::{Block, Inline, Pandoc, RawBlock};
+use quarto_markdown_pandoc::{readers, writers};
+
+pub fn read(input: &str) -> Pandoc {
+    readers::qmd::read(input.as_bytes(), &mut std::io::sink()).unwrap()
+}
+
+// the content of the first block, which has to be a paragraph
+pub fn read_inlines(input: &str) -> Vec<Inline> {
+    let Some(Block::Paragraph(para)) = read(input).blocks.into_iter().next() else {
+        panic!("Expected a Paragraph for {:?}", input);
+    };
+    para.content
+}
+
+pub fn write_markdown(doc: &Pandoc) -> String {
+    let mut buf = Vec::new();
+    writers::markdown::write(doc, &mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+pub fn raw_block(format: &str, text: &str) -> Block {
+    Block::RawBlock(RawBlock {
+        format: format.to_string(),
+        text: text.to_string(),
+        filename: None,
+        range: empty_range(),
+    })
+}
@@ -0,0 +1,75 @@
+/*
+ * roundtrip.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+// For every document in tests/roundtrip, checks that writing it as markdown
+// and reading it back gives the same document (ignoring source locations).
+
+use glob::glob;
+use quarto_markdown_pandoc::pandoc::Pandoc;
+use quarto_markdown_pandoc::{readers, writers};
+
+fn read(input: &str) -> Result<Pandoc, Vec<String>> {
+    readers::qmd::read(input.as_bytes(), &mut std::io::sink())
+}
+
+fn remove_location_fields(json: &mut serde_json::Value) {
+    if let Some(obj) = json.as_object_mut() {
+        obj.remove("l");
+        for value in obj.values_mut() {
+            remove_location_fields(value);
+        }
+    } else if let Some(array) = json.as_array_mut() {
+        for item in array.iter_mut() {
+            remove_location_fields(item);
+        }
+    }
+}
+
+// the document as JSON, without the parts that a round trip doesn't preserve
+fn structure(doc: &Pandoc) -> serde_json::Value {
+    let mut buf = Vec::new();
+    writers::json::write(doc, &mut buf).unwrap();
+    let mut value: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+    remove_location_fields(&mut value);
+    value
+}
+
+#[test]
+fn roundtrip_through_markdown_writer() {
+    let mut file_count = 0;
+    for entry in glob("tests/roundtrip/*.qmd").expect("Failed to read glob pattern") {
+        let path = entry.expect("Error reading glob entry");
+        let input = std::fs::read_to_string(&path).expect("Failed to read file");
+        let doc = read(&input)
+            .unwrap_or_else(|errors| panic!("Failed to read {}: {:?}", path.display(), errors));
+
+        let mut buf = Vec::new();
+        writers::markdown::write(&doc, &mut buf).unwrap();
+        let markdown = String::from_utf8(buf).expect("Invalid UTF-8 in markdown output");
+        let reread = read(&markdown).unwrap_or_else(|errors| {
+            panic!(
+                "Failed to read the markdown written for {}: {:?}\n{}",
+                path.display(),
+                errors,
+                markdown
+            )
+        });
+
+        assert_eq!(
+            structure(&doc),
+            structure(&reread),
+            "Round trip changed {}.\nMarkdown:\n{}\nBefore:\n{}\nAfter:\n{}",
+            path.display(),
+            markdown,
+            doc,
+            reread
+        );
+        file_count += 1;
+    }
+    assert!(
+        file_count > 0,
+        "No files found in tests/roundtrip directory"
+    );
+}
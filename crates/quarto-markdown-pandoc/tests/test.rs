@@ -4,9 +4,8 @@
  */
 
 use glob::glob;
-use quarto_markdown_pandoc::errors::parse_is_good;
-use quarto_markdown_pandoc::pandoc::treesitter_to_pandoc;
-use quarto_markdown_pandoc::{readers, writers};
+use quarto_markdown_pandoc::errors::collect_error_nodes;
+use quarto_markdown_pandoc::{readers, treesitter_to_pandoc, writers};
 use std::io::Write;
 use std::process::{Command, Stdio};
 use tree_sitter_qmd::MarkdownParser;
@@ -20,18 +19,59 @@ fn unit_test_simple_qmd_parses() {
         let tree = parser
             .parse(input_bytes, None)
             .expect("Failed to parse input");
-        let mut buf = Vec::new();
-        writers::native::write(
-            &treesitter_to_pandoc(&mut std::io::sink(), &tree, &input_bytes).unwrap(),
-            &mut buf,
-        )
-        .unwrap();
-        let ast = String::from_utf8(buf).expect("Invalid UTF-8 in output");
-        println!("{}", &ast);
+        let ast = treesitter_to_pandoc(&mut std::io::sink(), &tree, &input_bytes).unwrap();
+        println!("{}", ast);
         assert!(true, "Parsed successfully");
     }
 }
 
+#[test]
+fn unit_test_dump_intermediate() {
+    let entry_for = |dump: &serde_json::Value, kind: &str| {
+        dump.as_array()
+            .unwrap()
+            .iter()
+            .find(|entry| entry["kind"] == kind)
+            .cloned()
+    };
+    let input = "_hello_ \"world\"\n";
+    let dump = readers::qmd::dump_intermediate(input, &Default::default());
+    // nodes are in post-order, so the document comes last
+    let entries = dump.as_array().unwrap();
+    assert_eq!(entries.last().unwrap()["kind"], "document");
+    assert_eq!(
+        entries.last().unwrap()["intermediate"]["variant"],
+        "IntermediatePandoc"
+    );
+
+    let emphasis = entry_for(&dump, "emphasis").expect("Expected an emphasis node");
+    assert_eq!(emphasis["start"], serde_json::json!([0, 0]));
+    assert_eq!(emphasis["end"], serde_json::json!([0, 7]));
+    assert_eq!(emphasis["intermediate"]["variant"], "IntermediateInline");
+    assert!(
+        emphasis["intermediate"]["value"]
+            .as_str()
+            .unwrap()
+            .starts_with("IntermediateInline(Emph(")
+    );
+    let quoted = entry_for(&dump, "quoted_span").expect("Expected a quoted_span node");
+    assert!(
+        quoted["intermediate"]["value"]
+            .as_str()
+            .unwrap()
+            .contains("Quoted(")
+    );
+
+    // the reader options apply
+    let options = readers::qmd::MarkdownParserOptions {
+        no_smart: true,
+        ..Default::default()
+    };
+    let dump = readers::qmd::dump_intermediate(input, &options);
+    assert!(entry_for(&dump, "emphasis").is_some());
+    assert!(entry_for(&dump, "quoted_span").is_none());
+}
+
 fn has_good_pandoc_version() -> bool {
     let output = Command::new("pandoc")
         .arg("--version")
@@ -82,12 +122,10 @@ fn matches_pandoc_markdown_reader(input: &str) -> bool {
     if !has_good_pandoc_version() {
         return true; // Skip test if pandoc version is not suitable
     }
-    let mut buf1 = Vec::new();
     let mut buf2 = Vec::new();
 
     let doc = readers::qmd::read(input.as_bytes(), &mut std::io::sink()).unwrap();
-    writers::native::write(&doc, &mut buf1).unwrap();
-    let native_output = String::from_utf8(buf1).expect("Invalid UTF-8 in output");
+    let native_output = doc.to_string();
     writers::json::write(&doc, &mut buf2).unwrap();
     let json_output = String::from_utf8(buf2).expect("Invalid UTF-8 in output");
 
@@ -335,7 +373,7 @@ fn ensure_file_does_not_parse(path: &std::path::Path) {
         .parse(input_bytes, None)
         .expect("Failed to parse input");
 
-    let errors = parse_is_good(&tree);
+    let errors = collect_error_nodes(&tree, input_bytes);
     if errors.is_empty() {
         panic!(
             "File {} should not parse but it did: {:?}",
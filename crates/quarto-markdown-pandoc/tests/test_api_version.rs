@@ -0,0 +1,52 @@
+use quarto_markdown_pandoc::pandoc::{
+    PANDOC_API_VERSION, pandoc_api_version, pandoc_api_version_for,
+};
+use quarto_markdown_pandoc::readers;
+use quarto_markdown_pandoc::writers;
+
+#[test]
+fn test_json_writer_uses_the_api_version() {
+    let doc = readers::qmd::read(b"Hello.\n", &mut std::io::sink()).unwrap();
+    let mut buf = Vec::new();
+    writers::json::write(&doc, &mut buf).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+    assert_eq!(
+        json["pandoc-api-version"],
+        serde_json::json!(PANDOC_API_VERSION)
+    );
+}
+
+#[test]
+fn test_api_version_accessor() {
+    assert_eq!(pandoc_api_version(), PANDOC_API_VERSION);
+}
+
+#[test]
+fn test_api_version_of_pandoc_releases() {
+    assert_eq!(pandoc_api_version_for((3, 1)), Some(PANDOC_API_VERSION));
+    assert_eq!(pandoc_api_version_for((2, 19)), Some([1, 22, 2]));
+    assert_eq!(pandoc_api_version_for((2, 11)), Some([1, 22, 2]));
+    assert_eq!(pandoc_api_version_for((2, 10)), None);
+    assert_eq!(pandoc_api_version_for((1, 19)), None);
+}
+
+#[test]
+fn test_json_writer_uses_the_api_version_of_the_pandoc_version() {
+    let doc = readers::qmd::read(b"Hello.\n", &mut std::io::sink()).unwrap();
+    let write = |version| {
+        let options = writers::json::JsonWriterOptions {
+            pandoc_version: Some(version),
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        writers::json::write_with_options(&doc, &options, &mut buf).map(|_| buf)
+    };
+    let json: serde_json::Value = serde_json::from_slice(&write((2, 19)).unwrap()).unwrap();
+    assert_eq!(json["pandoc-api-version"], serde_json::json!([1, 22, 2]));
+    let json: serde_json::Value = serde_json::from_slice(&write((3, 0)).unwrap()).unwrap();
+    assert_eq!(
+        json["pandoc-api-version"],
+        serde_json::json!(PANDOC_API_VERSION)
+    );
+    assert!(write((2, 10)).is_err());
+}
@@ -0,0 +1,54 @@
+use quarto_markdown_pandoc::readers;
+use quarto_markdown_pandoc::writers;
+
+mod common;
+use common::read;
+
+const INPUT: &str = "Café 😀1 `naïve` $α$\n";
+
+#[test]
+fn test_json_ascii_escapes() {
+    let doc = read(INPUT);
+    let options = writers::json::JsonWriterOptions {
+        ascii: true,
+        ..Default::default()
+    };
+    let mut buf = Vec::new();
+    writers::json::write_with_options(&doc, &options, &mut buf).unwrap();
+    let json = String::from_utf8(buf).unwrap();
+    assert!(json.is_ascii());
+    assert!(json.contains("\"Caf\\u00e9\""));
+    assert!(json.contains("\"\\ud83d\\ude001\""));
+
+    let mut plain = Vec::new();
+    writers::json::write(&doc, &mut plain).unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        parsed,
+        serde_json::from_slice::<serde_json::Value>(&plain).unwrap()
+    );
+}
+
+#[test]
+fn test_native_ascii_escapes() {
+    let doc = read(INPUT);
+    let options = writers::native::NativeWriterOptions {
+        ascii: true,
+        ..Default::default()
+    };
+    let mut buf = Vec::new();
+    writers::native::write_with_options(&doc, &options, &mut buf).unwrap();
+    let native = String::from_utf8(buf).unwrap();
+    assert!(native.is_ascii());
+    assert!(native.contains("Str \"Caf\\233\""));
+    // a digit after an escape is separated from it
+    assert!(native.contains("Str \"\\128512\\&1\""));
+    assert!(native.contains("Code ( \"\" , [] , [] ) \"na\\239ve\""));
+    assert!(native.contains("\"\\945\""));
+    assert_eq!(
+        readers::native::read(native.as_bytes())
+            .unwrap()
+            .to_string(),
+        doc.to_string()
+    );
+}
@@ -0,0 +1,158 @@
+use quarto_markdown_pandoc::pandoc::{Block, Inline, Pandoc};
+use quarto_markdown_pandoc::readers::qmd::MarkdownParserOptions;
+use quarto_markdown_pandoc::{readers, writers};
+
+mod common;
+use common::read;
+
+fn para_text(block: &Block) -> String {
+    let Block::Paragraph(para) = block else {
+        panic!("Expected a Paragraph, got {:?}", block);
+    };
+    para.content
+        .iter()
+        .map(|inline| match inline {
+            Inline::Str(s) => s.text.as_str(),
+            Inline::Space(_) | Inline::SoftBreak(_) => " ",
+            other => panic!("Unexpected inline {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_attr_line_after_paragraph_wraps_it_in_a_div() {
+    let doc = read("some text\n{#an-id .cls key=val}\n");
+    let [Block::Div(div)] = doc.blocks.as_slice() else {
+        panic!("Expected a single Div, got {:?}", doc.blocks);
+    };
+    assert_eq!(div.attr.0, "an-id");
+    assert_eq!(div.attr.1, vec!["cls"]);
+    assert_eq!(div.attr.2.get("key").map(String::as_str), Some("val"));
+    let [para] = div.content.as_slice() else {
+        panic!("Expected a single block in the Div, got {:?}", div.content);
+    };
+    assert_eq!(para_text(para), "some text");
+}
+
+#[test]
+fn test_attr_paragraph_attaches_to_preceding_paragraph() {
+    let doc = read("first\n\nsecond\nline\n\n{.cls}\n\nthird\n");
+    let [first, Block::Div(div), third] = doc.blocks.as_slice() else {
+        panic!(
+            "Expected a Paragraph, a Div and a Paragraph, got {:?}",
+            doc.blocks
+        );
+    };
+    assert_eq!(para_text(first), "first");
+    assert_eq!(div.attr.1, vec!["cls"]);
+    let [para] = div.content.as_slice() else {
+        panic!("Expected a single block in the Div, got {:?}", div.content);
+    };
+    assert_eq!(para_text(para), "second line");
+    assert_eq!(para_text(third), "third");
+}
+
+#[test]
+fn test_attr_line_after_figure_paragraph_labels_the_figure() {
+    for input in [
+        "![caption](image.png)\n{#fig-a .cls}\n",
+        "![caption](image.png)\n\n{#fig-a .cls}\n",
+    ] {
+        let doc = read(input);
+        let [Block::Figure(figure)] = doc.blocks.as_slice() else {
+            panic!("Expected a single Figure, got {:?}", doc.blocks);
+        };
+        assert_eq!(figure.attr.0, "fig-a");
+        assert_eq!(figure.attr.1, vec!["cls"]);
+    }
+}
+
+// the (row, column) of the error for each attribute nothing takes
+fn attr_error_locations(input: &str) -> Vec<(usize, usize)> {
+    let options = MarkdownParserOptions::default();
+    let Err(diagnostics) =
+        readers::qmd::read_with_options(input.as_bytes(), &options, &mut std::io::sink())
+    else {
+        panic!("Expected an error for {:?}", input);
+    };
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.title, "Unattached attributes");
+            let start = &diagnostic.primary.as_ref().unwrap().range.start;
+            (start.row, start.column)
+        })
+        .collect()
+}
+
+#[test]
+fn test_attr_without_a_target_is_a_located_error() {
+    assert_eq!(attr_error_locations("{.cls}\n"), vec![(0, 0)]);
+    assert_eq!(attr_error_locations("{.cls}\n\npara\n"), vec![(0, 0)]);
+    assert_eq!(attr_error_locations("# heading\n\n{.cls}\n"), vec![(2, 0)]);
+    assert_eq!(attr_error_locations("- item\n\n{.cls}\n"), vec![(2, 0)]);
+    assert_eq!(attr_error_locations("text {.cls}\n"), vec![(0, 5)]);
+    assert_eq!(attr_error_locations("x {-} y\n"), vec![(0, 2)]);
+    // a paragraph takes one set of attributes
+    assert_eq!(attr_error_locations("p\n\n{.a}\n\n{.b}\n"), vec![(4, 0)]);
+    // without a blank line, the attributes are a table row
+    assert!(readers::qmd::read(b"| a |\n|---|\n| 1 |\n{.cls}\n", &mut std::io::sink()).is_err());
+}
+
+const TABLE: &str = "| a | b |\n|---|---|\n| 1 | 2 |\n";
+
+fn table_id(doc: &Pandoc) -> &str {
+    let [Block::Table(table)] = doc.blocks.as_slice() else {
+        panic!("Expected a single Table, got {:?}", doc.blocks);
+    };
+    &table.attr.0
+}
+
+#[test]
+fn test_attr_paragraph_after_table_sets_its_attr() {
+    let doc = read(&format!("{}\n{{#tbl-foo .striped}}\n", TABLE));
+    let [Block::Table(table)] = doc.blocks.as_slice() else {
+        panic!("Expected a single Table, got {:?}", doc.blocks);
+    };
+    assert_eq!(table.attr.0, "tbl-foo");
+    assert_eq!(table.attr.1, vec!["striped"]);
+}
+
+#[test]
+fn test_attr_line_before_table_sets_its_attr() {
+    assert_eq!(
+        table_id(&read(&format!("{{#tbl-foo}}\n{}", TABLE))),
+        "tbl-foo"
+    );
+    assert_eq!(
+        table_id(&read(&format!("{{#tbl-foo}}\n\n{}", TABLE))),
+        "tbl-foo"
+    );
+}
+
+#[test]
+fn test_attr_paragraph_between_paragraph_and_table_labels_the_table() {
+    let doc = read(&format!("Intro text.\n\n{{#tbl-foo}}\n\n{}", TABLE));
+    let [para, Block::Table(table)] = doc.blocks.as_slice() else {
+        panic!("Expected a Paragraph and a Table, got {:?}", doc.blocks);
+    };
+    assert_eq!(para_text(para), "Intro text.");
+    assert_eq!(table.attr.0, "tbl-foo");
+}
+
+#[test]
+fn test_table_attr_survives_to_json() {
+    let doc = read(&format!("{}\n{{#tbl-foo}}\n", TABLE));
+    let mut buf = Vec::new();
+    writers::json::write(&doc, &mut buf).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+    let table = &json["blocks"][0];
+    assert_eq!(table["t"], "Table");
+    assert_eq!(table["c"][0], serde_json::json!(["tbl-foo", [], []]));
+}
+
+#[test]
+fn test_table_takes_one_set_of_attrs() {
+    let input = format!("{{#tbl-a}}\n\n{}\n{{#tbl-b}}\n", TABLE);
+    assert!(readers::qmd::read(input.as_bytes(), &mut std::io::sink()).is_err());
+}
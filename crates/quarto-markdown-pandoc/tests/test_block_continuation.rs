@@ -0,0 +1,65 @@
+use quarto_markdown_pandoc::readers;
+
+// The document as native, and whatever the reader reported about nodes it
+// didn't expect.
+fn read_with_log(input: &str) -> (String, String) {
+    let mut log = Vec::new();
+    let doc = readers::qmd::read(input.as_bytes(), &mut log).unwrap();
+    (doc.to_string(), String::from_utf8(log).unwrap())
+}
+
+fn assert_reads_cleanly(input: &str, expected: &str) {
+    let (native, log) = read_with_log(input);
+    assert_eq!(native, expected, "{:?}", input);
+    assert!(!log.contains("Will ignore"), "{}", log);
+    assert!(!log.contains("in block_quote"), "{}", log);
+}
+
+#[test]
+fn test_paragraph_in_quote_in_list_item() {
+    assert_reads_cleanly(
+        "- > one\n  > two\n  > three\n",
+        "[ BulletList [[BlockQuote [Para [Str \"one\", SoftBreak, Str \"two\", SoftBreak, Str \"three\"]]]] ]",
+    );
+}
+
+#[test]
+fn test_paragraphs_in_quote_in_ordered_list_item() {
+    assert_reads_cleanly(
+        "1. > a\n   > b\n   >\n   > c\n",
+        "[ OrderedList (1, Decimal, Period) [[BlockQuote [Para [Str \"a\", SoftBreak, Str \"b\"], Para [Str \"c\"]]]] ]",
+    );
+}
+
+#[test]
+fn test_lazy_continuation_in_quote_in_list_item() {
+    assert_reads_cleanly(
+        "- item\n\n  > one\n  continued\n\n  after\n",
+        "[ BulletList [[Para [Str \"item\"], BlockQuote [Para [Str \"one\", SoftBreak, Str \"continued\"]], Para [Str \"after\"]]] ]",
+    );
+}
+
+#[test]
+fn test_heading_in_quote() {
+    assert_reads_cleanly(
+        "> # Title\n> text\n",
+        "[ BlockQuote [Header 1 ( \"title\" , [] , [] ) [Str \"Title\"], Para [Str \"text\"]] ]",
+    );
+}
+
+#[test]
+fn test_heading_in_list_item() {
+    let (native, _) = read_with_log("- # Title\n  text\n");
+    assert_eq!(
+        native,
+        "[ BulletList [[Header 1 ( \"title\" , [] , [] ) [Str \"Title\"], Para [Str \"text\"]]] ]"
+    );
+}
+
+#[test]
+fn test_div_in_quote_in_list_item() {
+    assert_reads_cleanly(
+        "- > ::: note\n  > inside\n  > :::\n",
+        "[ BulletList [[BlockQuote [Div ( \"\" , [\"note\"] , [] ) [Para [Str \"inside\"]]]]] ]",
+    );
+}
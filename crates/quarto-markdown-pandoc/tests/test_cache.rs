@@ -0,0 +1,70 @@
+use quarto_markdown_pandoc::readers::qmd::{ParseCache, read_with_cache};
+use quarto_markdown_pandoc::utils::content_hash::content_hash;
+
+#[test]
+fn test_content_hash_depends_only_on_content() {
+    let a = String::from("# Hello\n");
+    let b = "# Hello\n".to_string();
+    assert_ne!(a.as_ptr(), b.as_ptr());
+    assert_eq!(content_hash(a.as_bytes()), content_hash(b.as_bytes()));
+    assert_ne!(
+        content_hash(a.as_bytes()),
+        content_hash("# Goodbye\n".as_bytes())
+    );
+}
+
+#[test]
+fn test_parse_cache_hits_on_equal_content() {
+    let mut cache = ParseCache::new();
+    let first = String::from("Some *text*.\n");
+    let second = "Some *text*.\n".to_string();
+
+    let r1 = read_with_cache(first.as_bytes(), &mut cache, &mut std::io::sink()).unwrap();
+    assert_eq!(cache.hits(), 0);
+    let r2 = read_with_cache(second.as_bytes(), &mut cache, &mut std::io::sink()).unwrap();
+    assert_eq!(cache.hits(), 1);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(r1, r2);
+}
+
+#[test]
+fn test_parse_cache_misses_on_different_content() {
+    let mut cache = ParseCache::new();
+    read_with_cache(
+        "Some *text*.\n".as_bytes(),
+        &mut cache,
+        &mut std::io::sink(),
+    )
+    .unwrap();
+    read_with_cache(
+        "Other *text*.\n".as_bytes(),
+        &mut cache,
+        &mut std::io::sink(),
+    )
+    .unwrap();
+    assert_eq!(cache.hits(), 0);
+    assert_eq!(cache.len(), 2);
+    assert!(cache.contains("Some *text*.\n".as_bytes()));
+    assert!(!cache.contains("Third *text*.\n".as_bytes()));
+}
+
+#[test]
+fn test_parse_cache_ignores_line_endings() {
+    let mut cache = ParseCache::new();
+    let lf = read_with_cache(b"Some *text*.\n", &mut cache, &mut std::io::sink()).unwrap();
+    let crlf = read_with_cache(b"Some *text*.\r\n", &mut cache, &mut std::io::sink()).unwrap();
+    assert_eq!(cache.hits(), 1);
+    assert_eq!(lf, crlf);
+}
+
+#[test]
+fn test_parse_cache_replays_output_on_hit() {
+    let mut cache = ParseCache::new();
+    let mut first = Vec::new();
+    read_with_cache(b"Some *text*.\n", &mut cache, &mut first).unwrap();
+    let mut second = Vec::new();
+    read_with_cache(b"Some *text*.\n", &mut cache, &mut second).unwrap();
+    assert_eq!(cache.hits(), 1);
+    assert!(!first.is_empty());
+    assert_eq!(first, second);
+}
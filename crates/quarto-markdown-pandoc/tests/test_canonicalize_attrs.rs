@@ -0,0 +1,75 @@
+use quarto_markdown_pandoc::filters::{Filter, FilterReturn::Unchanged, topdown_traverse};
+use quarto_markdown_pandoc::pandoc::attr::is_empty_attr;
+use quarto_markdown_pandoc::pandoc::{Block, Inline, Pandoc};
+use quarto_markdown_pandoc::transforms::canonicalize_attrs::canonicalize_attrs;
+use quarto_markdown_pandoc::writers;
+
+mod common;
+use common::read;
+
+// The same document, with an empty class and an empty key added to every
+// span and div attr.
+fn with_empty_attr_components(doc: Pandoc) -> Pandoc {
+    let mut filter = Filter::new()
+        .with_span(|mut span| {
+            span.attr.1.push(String::new());
+            span.attr.2.insert(String::new(), "x".to_string());
+            Unchanged(span)
+        })
+        .with_div(|mut div| {
+            div.attr.1.insert(0, String::new());
+            Unchanged(div)
+        });
+    topdown_traverse(doc, &mut filter)
+}
+
+const INPUT: &str = "::: {.note}\nSome [text]{.a key=value} and [more]{}.\n:::\n";
+
+#[test]
+fn test_attrs_compare_equal_after_canonicalization() {
+    let doc = read(INPUT);
+    let other = with_empty_attr_components(doc.clone());
+    assert_ne!(doc, other);
+    assert_eq!(canonicalize_attrs(doc.clone()), canonicalize_attrs(other));
+    assert_eq!(canonicalize_attrs(doc.clone()), doc);
+}
+
+#[test]
+fn test_empty_attr_components_are_empty() {
+    let doc = with_empty_attr_components(read("[text]{}\n"));
+    let Some(Block::Paragraph(para)) = doc.blocks.first() else {
+        panic!("Expected a Paragraph first, got {}", doc);
+    };
+    let Some(Inline::Span(span)) = para.content.first() else {
+        panic!("Expected a Span first, got {:?}", para.content);
+    };
+    assert!(!span.attr.1.is_empty());
+    assert!(is_empty_attr(&span.attr));
+}
+
+#[test]
+fn test_writers_ignore_empty_attr_components() {
+    let doc = read(INPUT);
+    let other = with_empty_attr_components(doc.clone());
+
+    let mut expected = Vec::new();
+    let mut actual = Vec::new();
+    writers::markdown::write(&doc, &mut expected).unwrap();
+    writers::markdown::write(&other, &mut actual).unwrap();
+    assert_eq!(
+        String::from_utf8(actual).unwrap(),
+        String::from_utf8(expected).unwrap()
+    );
+
+    let mut expected = Vec::new();
+    let mut actual = Vec::new();
+    writers::native::write(&doc, &mut expected).unwrap();
+    writers::native::write(&other, &mut actual).unwrap();
+    assert_eq!(actual, expected);
+
+    let mut expected = Vec::new();
+    let mut actual = Vec::new();
+    writers::json::write(&doc, &mut expected).unwrap();
+    writers::json::write(&other, &mut actual).unwrap();
+    assert_eq!(actual, expected);
+}
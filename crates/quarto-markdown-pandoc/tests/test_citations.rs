@@ -0,0 +1,120 @@
+use quarto_markdown_pandoc::pandoc::{Block, Cite, Inline, Pandoc, Plain};
+use quarto_markdown_pandoc::utils::citations::collect_citation_ids;
+use quarto_markdown_pandoc::writers;
+
+mod common;
+use common::read;
+
+#[test]
+fn test_collect_citation_ids_in_notes_and_captions() {
+    let mut doc = read(
+        r#"Hello [@doe; @roe, p. 3] and @doe.^[See @noted and @roe.]
+
+| a | b |
+|---|---|
+| 1 | 2 |
+"#,
+    );
+    // pipe table captions aren't parsed yet, so we attach one by hand
+    let caption_content = read("Table about [@captioned].\n").blocks;
+    let Some(Block::Table(table)) = doc.blocks.get_mut(1) else {
+        panic!("Expected a Table");
+    };
+    let Some(Block::Paragraph(para)) = caption_content.into_iter().next() else {
+        panic!("Expected a Paragraph");
+    };
+    table.caption.long = Some(vec![Block::Plain(Plain {
+        content: para.content,
+        filename: None,
+        range: para.range,
+    })]);
+
+    assert_eq!(
+        collect_citation_ids(&doc),
+        vec!["doe", "roe", "noted", "captioned"]
+    );
+}
+
+#[test]
+fn test_collect_citation_ids_in_metadata() {
+    let doc = read("---\ntitle: On @meta\n---\n\nBody with @body and @meta.\n");
+    assert_eq!(collect_citation_ids(&doc), vec!["meta", "body"]);
+}
+
+#[test]
+fn test_collect_citation_ids_in_metadata_source_order() {
+    let doc = read("---\ntitle: On @zeta\nabstract: On @alpha\n---\n\nBody.\n");
+    assert_eq!(collect_citation_ids(&doc), vec!["zeta", "alpha"]);
+}
+
+fn first_cite(doc: &Pandoc) -> &Cite {
+    let Some(Block::Paragraph(para)) = doc.blocks.first() else {
+        panic!("Expected a Paragraph first");
+    };
+    para.content
+        .iter()
+        .find_map(|inline| match inline {
+            Inline::Cite(cite) => Some(cite),
+            _ => None,
+        })
+        .expect("Expected a Cite")
+}
+
+fn content_text(cite: &Cite) -> String {
+    cite.content
+        .iter()
+        .map(|inline| match inline {
+            Inline::Str(s) => s.text.as_str(),
+            Inline::Space(_) => " ",
+            other => panic!("Unexpected inline in Cite content: {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_unresolved_citation_content_is_the_source_text() {
+    assert_eq!(content_text(first_cite(&read("See @foo.\n"))), "@foo");
+    assert_eq!(
+        content_text(first_cite(&read("See [-@foo, p. 3; also @bar].\n"))),
+        "[-@foo, p. 3; also @bar]"
+    );
+}
+
+#[test]
+fn test_markdown_writer_writes_citations_not_content() {
+    let mut doc = read("See @foo and [@bar, p. 3].\n");
+    // as if a citation processor had resolved them
+    let Some(Block::Paragraph(para)) = doc.blocks.first_mut() else {
+        panic!("Expected a Paragraph first");
+    };
+    for inline in para.content.iter_mut() {
+        if let Inline::Cite(cite) = inline {
+            cite.content = vec![Inline::from("(Foo 2020)")];
+        }
+    }
+    let mut buf = Vec::new();
+    writers::markdown::write(&doc, &mut buf).unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        "See @foo and [@bar, p. 3].\n"
+    );
+}
+
+#[test]
+fn test_json_writer_cite_matches_pandoc() {
+    let doc = read("[@foo, p. 1]\n");
+    let mut buf = Vec::new();
+    writers::json::write(&doc, &mut buf).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+    let mut cite = json["blocks"][0]["c"][0].clone();
+    // our note numbers don't count like Pandoc's yet
+    assert_eq!(cite["c"][0][0]["citationNoteNum"], serde_json::json!(0));
+    cite["c"][0][0]["citationNoteNum"] = serde_json::json!(1);
+
+    // `pandoc -f markdown -t json`
+    let expected: serde_json::Value = serde_json::from_str(
+        r#"{"t":"Cite","c":[[{"citationId":"foo","citationPrefix":[],"citationSuffix":[{"t":"Str","c":","},{"t":"Space"},{"t":"Str","c":"p."},{"t":"Space"},{"t":"Str","c":"1"}],"citationMode":{"t":"NormalCitation"},"citationNoteNum":1,"citationHash":0}],[{"t":"Str","c":"[@foo,"},{"t":"Space"},{"t":"Str","c":"p."},{"t":"Space"},{"t":"Str","c":"1]"}]]}"#,
+    )
+    .unwrap();
+    assert_eq!(cite, expected);
+}
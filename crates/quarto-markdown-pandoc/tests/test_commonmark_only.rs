@@ -0,0 +1,126 @@
+use quarto_markdown_pandoc::pandoc::{Block, Inline, Pandoc};
+use quarto_markdown_pandoc::readers::qmd::{MarkdownParserOptions, read_with_options};
+
+fn read_commonmark(input: &str) -> Pandoc {
+    let options = MarkdownParserOptions {
+        commonmark_only: true,
+        ..Default::default()
+    };
+    read_with_options(input.as_bytes(), &options, &mut std::io::sink())
+        .unwrap()
+        .0
+}
+
+#[test]
+fn test_shortcode_stays_literal_text() {
+    let doc = read_commonmark("{{< video https://example.com >}}\n");
+    let Some(Block::Paragraph(para)) = doc.blocks.first() else {
+        panic!("Expected a Paragraph first, got {}", doc);
+    };
+    let strs: Vec<&str> = para
+        .content
+        .iter()
+        .filter_map(|inline| match inline {
+            Inline::Str(s) => Some(s.text.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(strs, vec!["{{<", "video", "https://example.com", ">}}"]);
+    assert!(
+        !para
+            .content
+            .iter()
+            .any(|inline| matches!(inline, Inline::Span(_)))
+    );
+}
+
+#[test]
+fn test_fenced_div_becomes_paragraph_text() {
+    let doc = read_commonmark("::: {.callout-note}\nHello\n:::\n");
+    assert_eq!(
+        doc.to_string(),
+        "[ Para [Str \":::\", Space, Str \"{.callout-note}\", SoftBreak, Str \"Hello\", SoftBreak, Str \":::\"] ]"
+    );
+}
+
+#[test]
+fn test_fenced_div_with_blank_lines_becomes_separate_paragraphs() {
+    let doc = read_commonmark("::: note\n\nHello\n\n:::\n");
+    assert_eq!(
+        doc.to_string(),
+        "[ Para [Str \":::\", Space, Str \"note\"], Para [Str \"Hello\"], Para [Str \":::\"] ]"
+    );
+}
+
+#[test]
+fn test_inline_extensions_stay_literal_text() {
+    let doc = read_commonmark("^sup^ ~sub~ ~~gone~~ ^[note] $x + 1$ @doe [@doe, p. 3]\n");
+    assert_eq!(
+        doc.to_string(),
+        "[ Para [Str \"^sup^\", Space, Str \"~sub~\", Space, Str \"~~gone~~\", Space, Str \"^[note]\", Space, Str \"$x\", Space, Str \"+\", Space, Str \"1$\", Space, Str \"@doe\", Space, Str \"[@doe,\", Space, Str \"p.\", Space, Str \"3]\"] ]"
+    );
+}
+
+#[test]
+fn test_extension_content_is_still_read() {
+    let doc = read_commonmark("^[a *note*] and [a *span*]{.c}\n");
+    assert_eq!(
+        doc.to_string(),
+        "[ Para [Str \"^[a\", Space, Emph [Str \"note\"], Str \"]\", Space, Str \"and\", Space, Str \"[a\", Space, Emph [Str \"span\"], Str \"]{.c}\"] ]"
+    );
+}
+
+#[test]
+fn test_attributes_stay_literal_text() {
+    let doc = read_commonmark("[l](u){.c} `y`{=html} `z`{.r}\n\n{.c}\n");
+    assert_eq!(
+        doc.to_string(),
+        "[ Para [Link ( \"\" , [] , [] ) [Str \"l\"] (\"u\" , \"\"), Str \"{.c}\", Space, Code ( \"\" , [] , [] ) \"y\", Str \"{=html}\", Space, Code ( \"\" , [] , [] ) \"z\", Str \"{.r}\"], Para [Str \"{.c}\"] ]"
+    );
+    let doc = read_commonmark("# Title {#id}\n");
+    let Some(Block::Header(header)) = doc.blocks.first() else {
+        panic!("Expected a Header first, got {}", doc);
+    };
+    assert_eq!(header.attr.1, Vec::<String>::new());
+    assert!(
+        matches!(header.content.last(), Some(Inline::Str(s)) if s.text == "{#id}"),
+        "Expected the attribute as text, got {}",
+        doc
+    );
+}
+
+#[test]
+fn test_code_block_info_string_is_the_language() {
+    let doc = read_commonmark("```{=html}\n<b>\n```\n\n```{r}\nx\n```\n");
+    assert_eq!(
+        doc.to_string(),
+        "[ CodeBlock ( \"\" , [\"{=html}\"] , [] ) \"<b>\", CodeBlock ( \"\" , [\"{r}\"] , [] ) \"x\" ]"
+    );
+}
+
+#[test]
+fn test_no_smart_punctuation() {
+    let doc = read_commonmark("\"quoted\" 'single' -- --- ...\n");
+    assert_eq!(
+        doc.to_string(),
+        "[ Para [Str \"\\\"quoted\\\"\", Space, Str \"'single'\", Space, Str \"--\", Space, Str \"---\", Space, Str \"...\"] ]"
+    );
+}
+
+#[test]
+fn test_markup_in_block_quotes_keeps_continuations_out() {
+    let doc = read_commonmark("> a ^[b\n> c]\n");
+    assert_eq!(
+        doc.to_string(),
+        "[ BlockQuote [Para [Str \"a\", Space, Str \"^[b\", SoftBreak, Str \"c]\"]] ]"
+    );
+}
+
+#[test]
+fn test_escaped_space_is_text() {
+    let doc = read_commonmark("a\\ b\n");
+    assert_eq!(
+        doc.to_string(),
+        "[ Para [Str \"a\\\\\", Space, Str \"b\"] ]"
+    );
+}
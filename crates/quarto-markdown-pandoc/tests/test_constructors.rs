@@ -0,0 +1,38 @@
+use quarto_markdown_pandoc::pandoc::location::empty_range;
+use quarto_markdown_pandoc::pandoc::{Block, Emph, HorizontalRule, Inline, Paragraph, Str};
+
+#[test]
+fn test_inline_from() {
+    let expected = Inline::Str(Str {
+        text: "x".to_string(),
+        filename: None,
+        range: empty_range(),
+    });
+    assert_eq!(Inline::from("x"), expected);
+    assert_eq!(Inline::from("x".to_string()), expected);
+
+    let emph: Inline = Emph {
+        content: vec!["x".into()],
+    }
+    .into();
+    assert!(matches!(emph, Inline::Emph(Emph { content }) if content == vec![expected]));
+}
+
+#[test]
+fn test_block_from() {
+    let para: Block = vec![Inline::from("a"), Inline::from("b")].into();
+    assert_eq!(
+        para,
+        Block::Paragraph(Paragraph {
+            content: vec!["a".into(), "b".into()],
+            filename: None,
+            range: empty_range(),
+        })
+    );
+
+    let rule = HorizontalRule {
+        filename: None,
+        range: empty_range(),
+    };
+    assert_eq!(Block::from(rule.clone()), Block::HorizontalRule(rule));
+}
@@ -0,0 +1,61 @@
+use quarto_markdown_pandoc::utils::corpus::{CorpusReport, count_patterns, scan_directory};
+use std::path::Path;
+
+#[test]
+fn test_count_patterns() {
+    let input = concat!(
+        "$$\nx^2\n$$\n",
+        "\n",
+        "See https://example.com and [a](https://example.org) or <https://x.y>.\n",
+        "\n",
+        "+---+---+\n",
+        "| a | b |\n",
+        "+===+===+\n",
+    );
+    let patterns = count_patterns(input);
+    assert_eq!(patterns["dollar_dollar"], 2);
+    assert_eq!(patterns["bare_url"], 1);
+    assert_eq!(patterns["grid_table_border"], 2);
+}
+
+#[test]
+fn test_scan_directory_aggregates_node_counts() {
+    let report = scan_directory(Path::new("tests/node-stats")).unwrap();
+    assert_eq!(report.files, 1);
+    assert!(report.failures.is_empty());
+    assert_eq!(report.stats.block_count("Header"), 2);
+    assert_eq!(report.stats.inline_count("Link"), 2);
+}
+
+#[test]
+fn test_documents_that_fail_to_parse_are_reported() {
+    let mut report = CorpusReport::default();
+    report.add_document(Path::new("good.qmd"), "# Title\n\nhttps://example.com\n");
+    report.add_document(Path::new("bad.qmd"), "![Caption][logo] $$\n");
+    assert_eq!(report.files, 2);
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].0, Path::new("bad.qmd"));
+    assert_eq!(report.stats.block_count("Header"), 1);
+    // the source patterns of a failed document are still counted
+    assert_eq!(report.patterns["dollar_dollar"], 1);
+    assert_eq!(report.patterns["bare_url"], 1);
+}
+
+#[test]
+fn test_csv_report() {
+    let mut report = CorpusReport::default();
+    report.add_document(Path::new("a.qmd"), "# Title\n");
+    assert_eq!(
+        report.to_csv(),
+        concat!(
+            "kind,name,count\n",
+            "summary,files,1\n",
+            "summary,failures,0\n",
+            "block,Header,1\n",
+            "inline,Str,1\n",
+            "pattern,bare_url,0\n",
+            "pattern,dollar_dollar,0\n",
+            "pattern,grid_table_border,0\n",
+        )
+    );
+}
@@ -0,0 +1,46 @@
+/*
+ * test_determinism.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+// Attributes, shortcode arguments and metadata are maps. Their iteration
+// order must come from the source and not from hashing, so that reading
+// and writing the same document always gives the same bytes.
+
+use glob::glob;
+use quarto_markdown_pandoc::{readers, writers};
+
+fn serialize(input: &str) -> (Vec<u8>, Vec<u8>) {
+    let doc = readers::qmd::read(input.as_bytes(), &mut std::io::sink()).unwrap();
+    let mut json = Vec::new();
+    writers::json::write(&doc, &mut json).unwrap();
+    let mut native = Vec::new();
+    writers::native::write(&doc, &mut native).unwrap();
+    (json, native)
+}
+
+#[test]
+fn serialization_is_deterministic() {
+    let mut file_count = 0;
+    for entry in glob("tests/determinism/*.qmd").expect("Failed to read glob pattern") {
+        let path = entry.expect("Error reading glob entry");
+        let input = std::fs::read_to_string(&path).expect("Failed to read file");
+        let (json, native) = serialize(&input);
+        // each read builds new maps, so hash-ordered maps would differ here
+        for _ in 0..4 {
+            let (other_json, other_native) = serialize(&input);
+            assert!(
+                json == other_json,
+                "JSON output of {} is not deterministic",
+                path.display()
+            );
+            assert!(
+                native == other_native,
+                "native output of {} is not deterministic",
+                path.display()
+            );
+        }
+        file_count += 1;
+    }
+    assert!(file_count > 0, "No files found in tests/determinism");
+}
@@ -0,0 +1,114 @@
+use quarto_markdown_pandoc::readers::qmd::{MarkdownParserOptions, read_with_options};
+use quarto_markdown_pandoc::utils::diagnostics::{Diagnostic, Label};
+use quarto_markdown_pandoc::utils::source::Source;
+
+const UNCLOSED_SINGLE_QUOTE: &str = "Hello 'world of\nquotes.\n\nNext.\n";
+
+// the only quote that could close the opening one is inside a link
+const SINGLE_QUOTE_CLOSED_IN_LINK: &str = "Hello 'world of\n[quotes'](u).\n\nNext.\n";
+
+fn diagnostics(input: &str) -> Vec<Diagnostic> {
+    let options = MarkdownParserOptions::default();
+    match read_with_options(input.as_bytes(), &options, &mut std::io::sink()) {
+        Ok((_, warnings)) => warnings,
+        Err(diagnostics) => diagnostics,
+    }
+}
+
+fn primary(diagnostic: &Diagnostic) -> &Label {
+    diagnostic.primary.as_ref().unwrap()
+}
+
+fn only_diagnostic(input: &str) -> Diagnostic {
+    let mut diagnostics = diagnostics(input);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    diagnostics.pop().unwrap()
+}
+
+#[test]
+fn test_unclosed_single_quote_is_an_apostrophe() {
+    // with nothing that could close it, the quote is an apostrophe, as in
+    // `'tis`, so there's no error
+    assert!(diagnostics(UNCLOSED_SINGLE_QUOTE).is_empty());
+}
+
+#[test]
+fn test_single_quote_closed_in_code_span_is_an_apostrophe() {
+    // a quote in a code span can't close one outside of it
+    assert!(diagnostics("Hello 'world of\n`quotes'`.\n\nNext.\n").is_empty());
+}
+
+#[test]
+fn test_single_quote_closed_in_link_labels() {
+    let diagnostic = only_diagnostic(SINGLE_QUOTE_CLOSED_IN_LINK);
+    assert_eq!(diagnostic.title, "Unclosed Single Quote");
+    // the opening quote
+    assert_eq!(primary(&diagnostic).range.start.row, 0);
+    assert_eq!(primary(&diagnostic).range.start.column, 6);
+    // the end of the paragraph
+    assert_eq!(diagnostic.secondary.len(), 1);
+    assert_eq!(diagnostic.secondary[0].range.start.row, 1);
+    assert_eq!(diagnostic.secondary[0].range.start.column, 13);
+    assert_eq!(
+        diagnostic.secondary[0].message,
+        "This is where the block ended."
+    );
+}
+
+#[test]
+fn test_single_quote_closed_in_link_rendering() {
+    let diagnostic = only_diagnostic(SINGLE_QUOTE_CLOSED_IN_LINK);
+    let source = Source::new(SINGLE_QUOTE_CLOSED_IN_LINK);
+    assert_eq!(
+        diagnostic.render("doc.qmd", &source, false),
+        concat!(
+            "Error: Unclosed Single Quote\n",
+            "   ╭─[ doc.qmd:1:7 ]\n",
+            "   │\n",
+            " 1 │ Hello 'world of\n",
+            "   │       ┬  \n",
+            "   │       ╰── This is the opening quote. If you need an apostrophe, escape it with a backslash.\n",
+            " 2 │ [quotes'](u).\n",
+            "   │              ┬  \n",
+            "   │              ╰── This is where the block ended.\n",
+            "───╯\n",
+        )
+    );
+}
+
+#[test]
+fn test_unclosed_double_quote() {
+    let diagnostic = only_diagnostic("Say \"hi.\n");
+    assert_eq!(diagnostic.title, "Unclosed Double Quote");
+    assert_eq!(primary(&diagnostic).range.start.column, 4);
+}
+
+#[test]
+fn test_unexpected_text() {
+    let diagnostic = only_diagnostic("a ![x][y] b\n");
+    assert_eq!(diagnostic.title, "Unexpected ![x]");
+    assert_eq!(diagnostic.message(), "Error: Unexpected ![x] at 1:3");
+    assert!(diagnostic.secondary.is_empty());
+}
+
+#[test]
+fn test_no_diagnostics_for_valid_input() {
+    assert!(diagnostics("Hello 'world'.\n").is_empty());
+}
+
+#[test]
+fn test_errors_without_a_location() {
+    let diagnostics = diagnostics(&format!("{}x\n", "> ".repeat(120)));
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].primary.is_none());
+    assert!(
+        diagnostics[0]
+            .message()
+            .starts_with("Error: The input document is too deeply nested")
+    );
+    let source = Source::new("");
+    assert_eq!(
+        diagnostics[0].render("doc.qmd", &source, false),
+        format!("{}\n", diagnostics[0].message())
+    );
+}
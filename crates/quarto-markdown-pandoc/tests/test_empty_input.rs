@@ -0,0 +1,36 @@
+use quarto_markdown_pandoc::pandoc::Pandoc;
+use quarto_markdown_pandoc::writers;
+
+mod common;
+use common::read;
+
+#[test]
+fn test_empty_input() {
+    assert_eq!(read(""), Pandoc::default());
+}
+
+#[test]
+fn test_blank_lines_input() {
+    assert_eq!(read("\n\n"), Pandoc::default());
+}
+
+#[test]
+fn test_spaces_only_input() {
+    assert_eq!(read("   "), Pandoc::default());
+    assert_eq!(read(" \t \n  \n"), Pandoc::default());
+}
+
+#[test]
+fn test_empty_document_output() {
+    let doc = read("");
+    assert_eq!(doc.to_string(), "[]");
+
+    let mut buf = Vec::new();
+    writers::json::write(&doc, &mut buf).unwrap();
+    let json = String::from_utf8(buf).unwrap();
+    assert!(json.starts_with("{\"blocks\":[],\"meta\":{}"), "{}", json);
+
+    let mut buf = Vec::new();
+    writers::markdown::write(&doc, &mut buf).unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), "");
+}
@@ -0,0 +1,46 @@
+use quarto_markdown_pandoc::readers;
+use quarto_markdown_pandoc::utils::encoding::decode_input;
+
+// "café" in Latin-1
+const LATIN1: &[u8] = b"caf\xe9 au lait\n";
+
+#[test]
+fn test_reader_rejects_invalid_utf8() {
+    let result = readers::qmd::read(LATIN1, &mut std::io::sink());
+    assert_eq!(
+        result.unwrap_err(),
+        vec!["Error: input is not valid UTF-8 at byte 3".to_string()]
+    );
+}
+
+#[test]
+fn test_invalid_utf8_is_reported_without_an_encoding() {
+    assert_eq!(
+        decode_input(LATIN1.to_vec(), None).unwrap_err(),
+        "Error: input is not valid UTF-8 at byte 3"
+    );
+}
+
+#[test]
+fn test_input_is_transcoded_from_the_given_encoding() {
+    let input = decode_input(LATIN1.to_vec(), Some("latin1")).unwrap();
+    assert_eq!(input, "café au lait\n");
+    let doc = readers::qmd::read(input.as_bytes(), &mut std::io::sink()).unwrap();
+    assert!(doc.to_string().contains("café"));
+}
+
+#[test]
+fn test_unknown_encoding() {
+    assert_eq!(
+        decode_input(LATIN1.to_vec(), Some("no-such-encoding")).unwrap_err(),
+        "Error: unknown input encoding no-such-encoding"
+    );
+}
+
+#[test]
+fn test_utf8_input_is_unchanged() {
+    assert_eq!(
+        decode_input("café\n".as_bytes().to_vec(), None).unwrap(),
+        "café\n"
+    );
+}
@@ -0,0 +1,29 @@
+use quarto_markdown_pandoc::errors::{TreeSitterError, collect_error_nodes};
+use quarto_markdown_pandoc::treesitter_to_pandoc;
+use tree_sitter_qmd::MarkdownParser;
+
+const INPUT: &str = "Fine paragraph.\n\n# Title {!}\n";
+
+#[test]
+fn test_error_nodes_are_collected_with_range_and_snippet() {
+    let tree = MarkdownParser::default()
+        .parse(INPUT.as_bytes(), None)
+        .unwrap();
+    let error_nodes = collect_error_nodes(&tree, INPUT.as_bytes());
+    assert_eq!(error_nodes.len(), 1);
+    let error_node = &error_nodes[0];
+    assert_eq!(error_node.error, TreeSitterError::UnexpectedNode);
+    assert_eq!(error_node.text, "!");
+    assert_eq!(error_node.range.start.row, 2);
+    assert_eq!(error_node.range.start.column, 9);
+    assert_eq!(error_node.snippet, "# Title {!}");
+}
+
+#[test]
+fn test_conversion_reports_error_nodes_instead_of_leftovers() {
+    let tree = MarkdownParser::default()
+        .parse(INPUT.as_bytes(), None)
+        .unwrap();
+    let result = treesitter_to_pandoc(&mut std::io::sink(), &tree, INPUT.as_bytes());
+    assert_eq!(result, Err(vec!["Error: Unexpected ! at 3:10".to_string()]));
+}
@@ -0,0 +1,82 @@
+use quarto_markdown_pandoc::pandoc::{Pandoc, parse_pandoc_version};
+use quarto_markdown_pandoc::readers;
+use quarto_markdown_pandoc::readers::qmd::{MarkdownParserOptions, read_with_options};
+use quarto_markdown_pandoc::writers;
+
+const IMAGE_PARAGRAPH: &str = "![A cat](cat.png \"Tabby\"){#fig-cat width=50%}\n";
+
+fn read(input: &str, legacy_figures: bool) -> Pandoc {
+    let options = MarkdownParserOptions {
+        legacy_figures,
+        ..Default::default()
+    };
+    read_with_options(input.as_bytes(), &options, &mut std::io::sink())
+        .unwrap()
+        .0
+}
+
+#[test]
+fn test_image_paragraph_becomes_figure() {
+    assert_eq!(
+        read(IMAGE_PARAGRAPH, false).to_string(),
+        "[ Figure ( \"fig-cat\" , [] , [] ) (Caption Nothing [ Plain [Str \"A\", Space, Str \"cat\"] ]) [Plain [Image ( \"\" , [] , [(\"width\", \"50%\")] ) [Str \"A\", Space, Str \"cat\"] (\"cat.png\" , \"Tabby\")]] ]"
+    );
+}
+
+#[test]
+fn test_image_paragraph_as_legacy_figure() {
+    assert_eq!(
+        read(IMAGE_PARAGRAPH, true).to_string(),
+        "[ Para [Image ( \"fig-cat\" , [] , [(\"width\", \"50%\")] ) [Str \"A\", Space, Str \"cat\"] (\"cat.png\" , \"fig:Tabby\")] ]"
+    );
+}
+
+#[test]
+fn test_legacy_figures_leave_other_images_alone() {
+    let input = "An inline ![cat](cat.png) image.\n\n![](cat.png)\n";
+    assert_eq!(
+        read(input, true).to_string(),
+        read(input, false).to_string()
+    );
+}
+
+#[test]
+fn test_parse_pandoc_version() {
+    assert_eq!(parse_pandoc_version("3.1.2"), Some((3, 1)));
+    assert_eq!(parse_pandoc_version("2.19"), Some((2, 19)));
+    assert_eq!(parse_pandoc_version("3"), Some((3, 0)));
+    assert_eq!(parse_pandoc_version("2.x"), None);
+    assert_eq!(parse_pandoc_version(""), None);
+}
+
+fn write_native(doc: &Pandoc, pandoc_version: (u32, u32)) -> std::io::Result<String> {
+    let options = writers::native::NativeWriterOptions {
+        pandoc_version: Some(pandoc_version),
+        ..Default::default()
+    };
+    let mut buf = Vec::new();
+    writers::native::write_with_options(doc, &options, &mut buf)?;
+    Ok(String::from_utf8(buf).unwrap())
+}
+
+#[test]
+fn test_writers_use_implicit_figures_before_pandoc_3() {
+    // as for `-f native`, the document has a Figure block
+    let doc = read(IMAGE_PARAGRAPH, false);
+    assert_eq!(
+        write_native(&doc, (2, 19)).unwrap(),
+        read(IMAGE_PARAGRAPH, true).to_string()
+    );
+    assert_eq!(write_native(&doc, (3, 1)).unwrap(), doc.to_string());
+}
+
+#[test]
+fn test_figures_without_a_lone_image_are_an_error_before_pandoc_3() {
+    let doc = readers::native::read(
+        b"[ Figure (\"fig-x\",[],[]) (Caption Nothing [Plain [Str \"x\"]]) [Para [Str \"y\"]] ]",
+    )
+    .unwrap();
+    let error = write_native(&doc, (2, 19)).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(write_native(&doc, (3, 0)).is_ok());
+}
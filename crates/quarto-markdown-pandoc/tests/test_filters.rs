@@ -0,0 +1,37 @@
+use quarto_markdown_pandoc::filters::{Filter, FilterReturn, FilterReturn::Unchanged, run_filters};
+use quarto_markdown_pandoc::pandoc::{Emph, Inline};
+
+mod common;
+use common::read;
+
+#[test]
+fn test_filters_run_in_sequence() {
+    let doc = read("a b\n");
+    let mut filters = [
+        Filter::new().with_str(|mut s| {
+            s.text = s.text.to_uppercase();
+            Unchanged(s)
+        }),
+        // sees the output of the first filter
+        Filter::new().with_str(|s| {
+            if s.text != "A" {
+                return Unchanged(s);
+            }
+            FilterReturn::FilterResult(
+                vec![Inline::Emph(Emph {
+                    content: vec![Inline::Str(s)],
+                })],
+                false,
+            )
+        }),
+    ];
+    let result = run_filters(doc, &mut filters);
+    assert_eq!(result.to_string(), read("*A* B\n").to_string());
+}
+
+#[test]
+fn test_no_filters() {
+    let doc = read("a *b*\n");
+    let expected = doc.to_string();
+    assert_eq!(run_filters(doc, &mut []).to_string(), expected);
+}
@@ -0,0 +1,175 @@
+use quarto_markdown_pandoc::pandoc::{Block, Header, Inline, Pandoc};
+use quarto_markdown_pandoc::readers;
+
+mod common;
+use common::read;
+
+fn first_header(doc: &Pandoc) -> &Header {
+    match doc.blocks.first() {
+        Some(Block::Header(header)) => header,
+        _ => panic!("Expected a Header first, got {}", doc),
+    }
+}
+
+#[test]
+fn test_dash_class_becomes_unnumbered() {
+    let doc = read("# T {.-}\n");
+    assert_eq!(first_header(&doc).attr.1, vec!["unnumbered".to_string()]);
+}
+
+#[test]
+fn test_dash_shorthand_becomes_unnumbered() {
+    let doc = read("# T {-}\n");
+    let header = first_header(&doc);
+    assert_eq!(header.attr.0, "t");
+    assert_eq!(header.attr.1, vec!["unnumbered".to_string()]);
+
+    let doc = read("# T {#t - .unlisted key=v}\n");
+    let header = first_header(&doc);
+    assert_eq!(header.attr.0, "t");
+    assert_eq!(
+        header.attr.1,
+        vec!["unnumbered".to_string(), "unlisted".to_string()]
+    );
+    assert_eq!(header.attr.2.get("key").map(String::as_str), Some("v"));
+}
+
+#[test]
+fn test_unnumbered_class_is_kept() {
+    let doc = read("# T {.unnumbered}\n");
+    assert_eq!(first_header(&doc).attr.1, vec!["unnumbered".to_string()]);
+}
+
+#[test]
+fn test_unnumbered_and_unlisted_classes_are_kept() {
+    let doc = read("# T {.unnumbered .unlisted}\n");
+    assert_eq!(
+        first_header(&doc).attr.1,
+        vec!["unnumbered".to_string(), "unlisted".to_string()]
+    );
+
+    let doc = read("# T {.- .unlisted}\n");
+    assert_eq!(
+        first_header(&doc).attr.1,
+        vec!["unnumbered".to_string(), "unlisted".to_string()]
+    );
+}
+
+#[test]
+fn test_dash_class_does_not_duplicate_unnumbered() {
+    let doc = read("# T {#t .- .unnumbered}\n");
+    let header = first_header(&doc);
+    assert_eq!(header.attr.0, "t");
+    assert_eq!(header.attr.1, vec!["unnumbered".to_string()]);
+}
+
+#[test]
+fn test_setext_heading_attributes() {
+    let doc = read("Title {#id .cls}\n=====\n");
+    let header = first_header(&doc);
+    assert_eq!(header.level, 1);
+    assert_eq!(header.attr.0, "id");
+    assert_eq!(header.attr.1, vec!["cls".to_string()]);
+    assert_eq!(doc.to_string(), read("# Title {#id .cls}\n").to_string());
+
+    let doc = read("Sub title {#sub key=\"v\"}\n---\n");
+    let header = first_header(&doc);
+    assert_eq!(header.level, 2);
+    assert_eq!(header.attr.0, "sub");
+    assert_eq!(header.attr.2.get("key").map(String::as_str), Some("v"));
+}
+
+#[test]
+fn test_setext_heading_attributes_after_the_underline() {
+    let doc = read("Title\n=====  {#id .cls}\n");
+    let header = first_header(&doc);
+    assert_eq!(header.level, 1);
+    assert_eq!(header.attr.0, "id");
+    assert_eq!(header.attr.1, vec!["cls".to_string()]);
+    assert_eq!(doc.to_string(), read("# Title {#id .cls}\n").to_string());
+
+    let doc = read("Sub title\n---  {#sub}\n");
+    let header = first_header(&doc);
+    assert_eq!(header.level, 2);
+    assert_eq!(header.attr.0, "sub");
+
+    let doc = read("> Quoted\n> ===  {.c}\n");
+    let Block::BlockQuote(quote) = &doc.blocks[0] else {
+        panic!("Expected a BlockQuote, got {}", doc);
+    };
+    let Some(Block::Header(header)) = quote.content.first() else {
+        panic!("Expected a Header in the BlockQuote, got {}", doc);
+    };
+    assert_eq!(header.attr.1, vec!["c".to_string()]);
+}
+
+#[test]
+fn test_setext_heading_without_attributes_gets_an_id() {
+    let doc = read("Some Title\n==========\n");
+    assert_eq!(first_header(&doc).attr.0, "some-title");
+}
+
+#[test]
+fn test_atx_closing_hashes_are_stripped() {
+    let expected = read("## Title\n").to_string();
+    assert_eq!(read("## Title ##\n").to_string(), expected);
+    assert_eq!(read("## Title #########\n").to_string(), expected);
+    assert_eq!(read("## Title ##   \n").to_string(), expected);
+
+    let doc = read("## Title ## {#x}\n");
+    assert_eq!(first_header(&doc).attr.0, "x");
+    assert_eq!(doc.to_string(), read("## Title {#x}\n").to_string());
+
+    // a heading made only of the closing sequence is empty
+    assert!(first_header(&read("# ##\n")).content.is_empty());
+}
+
+#[test]
+fn test_atx_hashes_that_are_content() {
+    let doc = read("## Title # not-closing\n");
+    assert_eq!(
+        doc.to_string(),
+        "[ Header 2 ( \"title-not-closing\" , [] , [] ) [Str \"Title\", Space, Str \"#\", Space, Str \"not-closing\"] ]"
+    );
+    // not preceded by a space
+    assert!(doc_text(&read("## Title#\n")).ends_with("Title#"));
+    // escaped
+    assert!(doc_text(&read("## Title \\#\n")).ends_with("Title #"));
+    assert!(doc_text(&read("## Title \\##\n")).ends_with("Title ##"));
+}
+
+// the text of the first header
+fn doc_text(doc: &Pandoc) -> String {
+    first_header(doc)
+        .content
+        .iter()
+        .map(|inline| match inline {
+            Inline::Str(s) => s.text.as_str(),
+            Inline::Space(_) => " ",
+            _ => panic!("Expected only text, got {}", doc),
+        })
+        .collect()
+}
+
+fn read_with_output(input: &str) -> (Pandoc, String) {
+    let mut output = Vec::new();
+    let doc = readers::qmd::read(input.as_bytes(), &mut output).unwrap();
+    (doc, String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn test_empty_atx_headings() {
+    for (input, level) in [("#\n", 1), ("###\n", 3), ("  ###  \n", 3)] {
+        let (doc, output) = read_with_output(input);
+        let header = first_header(&doc);
+        assert_eq!(header.level, level, "for {:?}", input);
+        assert!(header.content.is_empty());
+        assert!(!output.contains("No heading marker"));
+    }
+}
+
+#[test]
+fn test_too_many_hashes_is_not_a_heading() {
+    let doc = read("####### x\n");
+    assert!(matches!(doc.blocks.first(), Some(Block::Paragraph(_))));
+}
@@ -0,0 +1,62 @@
+use quarto_markdown_pandoc::pandoc::{Block, Inline};
+use quarto_markdown_pandoc::transforms::id_prefix::prefix_ids;
+
+mod common;
+use common::read;
+
+fn first_link_target(blocks: &[Block]) -> String {
+    for block in blocks {
+        if let Block::Paragraph(para) = block {
+            for inline in &para.content {
+                if let Inline::Link(link) = inline {
+                    return link.target.0.clone();
+                }
+            }
+        }
+    }
+    panic!("No link found");
+}
+
+#[test]
+fn test_internal_link_resolves_after_prefixing() {
+    let doc = prefix_ids(
+        read("# Intro {#intro}\n\nSee [the intro](#intro).\n"),
+        "prefix-",
+    );
+    let Some(Block::Header(header)) = doc.blocks.first() else {
+        panic!("Expected a Header first, got {}", doc);
+    };
+    assert_eq!(header.attr.0, "prefix-intro");
+    let target = first_link_target(&doc.blocks);
+    assert_eq!(target, "#prefix-intro");
+    assert_eq!(target.strip_prefix('#'), Some(header.attr.0.as_str()));
+}
+
+#[test]
+fn test_auto_generated_ids_are_prefixed() {
+    let doc = prefix_ids(read("# Hello World\n"), "ch1-");
+    let Some(Block::Header(header)) = doc.blocks.first() else {
+        panic!("Expected a Header first, got {}", doc);
+    };
+    assert_eq!(header.attr.0, "ch1-hello-world");
+}
+
+#[test]
+fn test_external_links_and_nested_ids() {
+    let doc = prefix_ids(
+        read("::: {#box}\n[a]{#s} [b](https://example.com#frag)\n:::\n"),
+        "p-",
+    );
+    let Some(Block::Div(div)) = doc.blocks.first() else {
+        panic!("Expected a Div first, got {}", doc);
+    };
+    assert_eq!(div.attr.0, "p-box");
+    let Some(Block::Paragraph(para)) = div.content.first() else {
+        panic!("Expected a Paragraph");
+    };
+    let Some(Inline::Span(span)) = para.content.first() else {
+        panic!("Expected a Span");
+    };
+    assert_eq!(span.attr.0, "p-s");
+    assert_eq!(first_link_target(&div.content), "https://example.com#frag");
+}
@@ -0,0 +1,78 @@
+use quarto_markdown_pandoc::pandoc::location::empty_range;
+use quarto_markdown_pandoc::pandoc::{Block, Inline, Meta, Pandoc, Paragraph, RawInline};
+use quarto_markdown_pandoc::transforms::leftovers::{
+    LEFTOVER_FORMAT, LeftoverOptions, strip_leftovers,
+};
+use quarto_markdown_pandoc::utils::diagnostics::Severity;
+
+mod common;
+use common::raw_block;
+
+fn str_inline(text: &str) -> Inline {
+    Inline::from(text)
+}
+
+fn raw_inline(format: &str, text: &str) -> Inline {
+    Inline::RawInline(RawInline {
+        format: format.to_string(),
+        text: text.to_string(),
+    })
+}
+
+fn doc_with_leftovers() -> Pandoc {
+    Pandoc {
+        meta: Meta::default(),
+        blocks: vec![
+            Block::Paragraph(Paragraph {
+                content: vec![
+                    str_inline("a"),
+                    raw_inline(LEFTOVER_FORMAT, "%%"),
+                    raw_inline("html", "<b>"),
+                ],
+                filename: None,
+                range: empty_range(),
+            }),
+            raw_block(LEFTOVER_FORMAT, "???"),
+            raw_block("latex", "\\newpage"),
+        ],
+    }
+}
+
+#[test]
+fn test_leftovers_are_errors_in_strict_mode() {
+    let diagnostics =
+        strip_leftovers(doc_with_leftovers(), &LeftoverOptions { strict: true }).unwrap_err();
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics.iter().all(|d| d.is_error()));
+    assert!(diagnostics[0].title.contains("%%"));
+    assert!(diagnostics[1].title.contains("???"));
+}
+
+#[test]
+fn test_leftover_text_is_kept_with_warnings() {
+    let (doc, warnings) =
+        strip_leftovers(doc_with_leftovers(), &LeftoverOptions::default()).unwrap();
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings.iter().all(|d| d.severity == Severity::Warning));
+    let output = doc.to_string();
+    assert!(!output.contains(LEFTOVER_FORMAT));
+    assert!(output.contains("Str \"%%\""));
+    assert!(output.contains("Plain [Str \"???\"]"));
+    // other raw content is untouched
+    assert!(output.contains("RawInline (Format \"html\") \"<b>\""));
+    assert!(output.contains("RawBlock (Format \"latex\")"));
+}
+
+#[test]
+fn test_document_without_leftovers_is_unchanged() {
+    let mut doc = doc_with_leftovers();
+    doc.blocks.remove(1);
+    let Block::Paragraph(para) = &mut doc.blocks[0] else {
+        unreachable!()
+    };
+    para.content.remove(1);
+    let expected = doc.to_string();
+    let (result, warnings) = strip_leftovers(doc, &LeftoverOptions::default()).unwrap();
+    assert_eq!(result.to_string(), expected);
+    assert!(warnings.is_empty());
+}
@@ -0,0 +1,110 @@
+use quarto_markdown_pandoc::pandoc::Block;
+use quarto_markdown_pandoc::readers::qmd::read;
+use quarto_markdown_pandoc::utils::line_ending::normalize;
+use quarto_markdown_pandoc::{LineEnding, infer_line_ending};
+use std::borrow::Cow;
+
+#[test]
+fn test_infer_lf() {
+    assert_eq!(infer_line_ending(b"# Title\n\ntext\n"), LineEnding::Lf);
+}
+
+#[test]
+fn test_infer_crlf() {
+    assert_eq!(
+        infer_line_ending(b"# Title\r\n\r\ntext\r\n"),
+        LineEnding::Crlf
+    );
+}
+
+#[test]
+fn test_infer_uses_first_line_break() {
+    assert_eq!(infer_line_ending(b"a\r\nb\n"), LineEnding::Crlf);
+    assert_eq!(infer_line_ending(b"a\nb\r\n"), LineEnding::Lf);
+}
+
+#[test]
+fn test_infer_without_line_breaks() {
+    assert_eq!(infer_line_ending(b""), LineEnding::Lf);
+    assert_eq!(infer_line_ending(b"text"), LineEnding::Lf);
+}
+
+#[test]
+fn test_infer_cr() {
+    assert_eq!(infer_line_ending(b"# Title\r\rtext\r"), LineEnding::Cr);
+    assert_eq!(infer_line_ending(b"\r"), LineEnding::Cr);
+    assert_eq!(infer_line_ending(b"a\rb\r\n"), LineEnding::Cr);
+    assert_eq!(infer_line_ending(b"a\r\nb\r"), LineEnding::Crlf);
+}
+
+#[test]
+fn test_crlf_input_reads_like_lf_input() {
+    let lf = read(b"# Title\n\na\nb\n", &mut std::io::sink()).unwrap();
+    let crlf = read(b"# Title\r\n\r\na\r\nb\r\n", &mut std::io::sink()).unwrap();
+    assert_eq!(lf.to_string(), crlf.to_string());
+}
+
+#[test]
+fn test_line_ending_as_str() {
+    assert_eq!(LineEnding::Lf.as_str(), "\n");
+    assert_eq!(LineEnding::Crlf.as_str(), "\r\n");
+    assert_eq!(LineEnding::Cr.as_str(), "\r");
+}
+
+#[test]
+fn test_normalize_crlf() {
+    assert_eq!(normalize("a\r\nb\r\n\r\nc"), "a\nb\n\nc");
+    assert_eq!(normalize("\r\n"), "\n");
+}
+
+#[test]
+fn test_normalize_keeps_lf_input() {
+    let input = "a\nb\n\nc\n";
+    let normalized = normalize(input);
+    assert!(matches!(normalized, Cow::Borrowed(_)));
+    assert_eq!(normalized, input);
+}
+
+#[test]
+fn test_normalize_mixed_line_endings() {
+    assert_eq!(normalize("a\r\nb\nc\r\n"), "a\nb\nc\n");
+}
+
+#[test]
+fn test_normalize_lone_cr() {
+    assert_eq!(normalize("a\rb\r\rc\r"), "a\nb\n\nc\n");
+    assert_eq!(normalize("\r"), "\n");
+}
+
+#[test]
+fn test_normalize_mixed_cr_crlf_and_lf() {
+    assert_eq!(normalize("a\rb\r\nc\nd\r"), "a\nb\nc\nd\n");
+    // `\n\r` is two line breaks, not one
+    assert_eq!(normalize("a\n\rb"), "a\n\nb");
+    assert_eq!(normalize("a\r\r\nb"), "a\n\nb");
+}
+
+#[test]
+fn test_cr_input_reads_like_lf_input() {
+    let lf = read(b"# Title\n\na\nb\n", &mut std::io::sink()).unwrap();
+    let cr = read(b"# Title\r\ra\rb\r", &mut std::io::sink()).unwrap();
+    assert_eq!(lf.to_string(), cr.to_string());
+}
+
+#[test]
+fn test_cr_code_block_reads_like_lf_input() {
+    let lf = read(b"# Title\n\n```\na\nb\n```\n", &mut std::io::sink()).unwrap();
+    let cr = read(b"# Title\r\r```\ra\rb\r```\r", &mut std::io::sink()).unwrap();
+    assert_eq!(lf.to_string(), cr.to_string());
+}
+
+#[test]
+fn test_crlf_ranges_keep_rows_and_columns() {
+    let lf = read(b"a\nb\n", &mut std::io::sink()).unwrap();
+    let crlf = read(b"a\r\nb\r\n", &mut std::io::sink()).unwrap();
+    let range = |doc: &quarto_markdown_pandoc::pandoc::Pandoc| match doc.blocks.first() {
+        Some(Block::Paragraph(para)) => para.range.clone(),
+        _ => panic!("Expected a Paragraph first, got {}", doc),
+    };
+    assert_eq!(range(&lf), range(&crlf));
+}
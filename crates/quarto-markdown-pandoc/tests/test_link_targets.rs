@@ -0,0 +1,126 @@
+use quarto_markdown_pandoc::pandoc::{Block, Inline, MetaValue, Pandoc};
+use quarto_markdown_pandoc::transforms::link_targets::{LinkTargetOptions, normalize_link_targets};
+
+mod common;
+use common::read;
+
+fn targets(doc: &Pandoc) -> Vec<(String, String)> {
+    let Some(Block::Paragraph(para)) = doc.blocks.first() else {
+        panic!("Expected a Paragraph first, got {}", doc);
+    };
+    para.content
+        .iter()
+        .filter_map(|inline| match inline {
+            Inline::Link(link) => Some(link.target.clone()),
+            Inline::Image(image) => Some(image.target.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_entities_are_decoded_in_link_destinations() {
+    let doc = read("[x](https://example.com/?a=1&amp;b=2 \"Q &amp; A\")\n");
+    assert_eq!(
+        targets(&doc),
+        vec![(
+            "https://example.com/?a=1&b=2".to_string(),
+            "Q & A".to_string()
+        )]
+    );
+}
+
+#[test]
+fn test_spaces_are_percent_encoded_on_request() {
+    let input = "[x](my&#32;file.html) ![y](my&#32;image.png)\n";
+
+    let doc = read(input);
+    assert_eq!(
+        targets(&doc),
+        vec![
+            ("my file.html".to_string(), "".to_string()),
+            ("my image.png".to_string(), "".to_string())
+        ]
+    );
+
+    let options = LinkTargetOptions {
+        percent_encode_spaces: true,
+        ..Default::default()
+    };
+    let doc = normalize_link_targets(read(input), &options);
+    assert_eq!(
+        targets(&doc),
+        vec![
+            ("my%20file.html".to_string(), "".to_string()),
+            ("my%20image.png".to_string(), "".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_angle_bracket_destination_keeps_spaces() {
+    let doc = read("[x](<url with spaces> \"title\") ![y](<my image.png>)\n");
+    assert_eq!(
+        targets(&doc),
+        vec![
+            ("url with spaces".to_string(), "title".to_string()),
+            ("my image.png".to_string(), "".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_empty_angle_bracket_destination() {
+    let doc = read("[x](<>) and [@doe](<>)\n");
+    assert_eq!(
+        targets(&doc),
+        vec![
+            ("".to_string(), "".to_string()),
+            ("".to_string(), "".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_default_options_do_not_decode_twice() {
+    let doc = normalize_link_targets(
+        read("[x](a?b=1&amp;amp;c=2)\n"),
+        &LinkTargetOptions::default(),
+    );
+    assert_eq!(
+        targets(&doc),
+        vec![("a?b=1&amp;c=2".to_string(), "".to_string())]
+    );
+
+    let options = LinkTargetOptions {
+        decode_entities: true,
+        ..Default::default()
+    };
+    let doc = normalize_link_targets(read("[x](a?b=1&amp;amp;c=2)\n"), &options);
+    assert_eq!(
+        targets(&doc),
+        vec![("a?b=1&c=2".to_string(), "".to_string())]
+    );
+}
+
+#[test]
+fn test_metadata_links_are_normalized() {
+    let options = LinkTargetOptions {
+        percent_encode_spaces: true,
+        ..Default::default()
+    };
+    let doc = normalize_link_targets(
+        read("---\nabout: \"[x](a&#32;b.html)\"\n---\n\nText.\n"),
+        &options,
+    );
+    let Some(MetaValue::MetaInlines(inlines)) = doc.meta.get("about") else {
+        panic!(
+            "Expected inlines for `about`, got {:?}",
+            doc.meta.get("about")
+        );
+    };
+    let Some(Inline::Link(link)) = inlines.first() else {
+        panic!("Expected a Link, got {:?}", inlines);
+    };
+    assert_eq!(link.target.0, "a%20b.html");
+}
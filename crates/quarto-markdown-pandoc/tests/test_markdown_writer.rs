@@ -0,0 +1,702 @@
+use indexmap::IndexMap;
+use quarto_markdown_pandoc::pandoc::attr::empty_attr;
+use quarto_markdown_pandoc::pandoc::location::empty_range;
+use quarto_markdown_pandoc::pandoc::{
+    Block, Caption, Code, CodeBlock, DefinitionList, Emph, Figure, Inline, LineBlock, Meta, Note,
+    Pandoc, Paragraph, Plain, Space, Table,
+};
+use quarto_markdown_pandoc::readers;
+use quarto_markdown_pandoc::writers;
+use quarto_markdown_pandoc::writers::markdown::{MarkdownWriterOptions, TableStyle, table_style};
+
+mod common;
+use common::{read, write_markdown};
+
+fn first_code(doc: &Pandoc) -> &Code {
+    let Some(Block::Paragraph(para)) = doc.blocks.first() else {
+        panic!("Expected a Paragraph first, got {}", doc);
+    };
+    para.content
+        .iter()
+        .find_map(|inline| match inline {
+            Inline::Code(code) => Some(code),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("Expected a Code inline, got {}", doc))
+}
+
+fn code_doc(code: Code) -> Pandoc {
+    Pandoc {
+        meta: Meta::default(),
+        blocks: vec![Block::Paragraph(Paragraph {
+            content: vec![Inline::Code(code)],
+            filename: None,
+            range: empty_range(),
+        })],
+    }
+}
+
+fn assert_roundtrip(input: &str) {
+    let doc = read(input);
+    let output = write_markdown(&doc);
+    let reread = read(&output);
+    assert_eq!(
+        doc.to_string(),
+        reread.to_string(),
+        "roundtrip through {:?} changed the document",
+        output
+    );
+}
+
+#[test]
+fn test_code_without_attributes() {
+    assert_eq!(write_markdown(&read("`x`\n")), "`x`\n");
+}
+
+#[test]
+fn test_code_class_is_written() {
+    let output = write_markdown(&read("`x`{.rust}\n"));
+    assert_eq!(output, "`x`{.rust}\n");
+    assert_eq!(first_code(&read(&output)).attr.1, vec!["rust".to_string()]);
+}
+
+#[test]
+fn test_code_attributes_roundtrip() {
+    assert_roundtrip("`x`{.rust}\n");
+    assert_roundtrip("`let x = 1;`{#snippet .rust}\n");
+    assert_roundtrip("see `x`{.rust key=\"some value\"} here\n");
+}
+
+#[test]
+fn test_code_containing_backticks() {
+    let doc = code_doc(Code {
+        attr: ("".to_string(), vec!["rust".to_string()], IndexMap::new()),
+        text: "a `b` c".to_string(),
+    });
+    let output = write_markdown(&doc);
+    assert_eq!(output, "``a `b` c``{.rust}\n");
+    let reread = read(&output);
+    let code = first_code(&reread);
+    assert_eq!(code.text, "a `b` c");
+    assert_eq!(code.attr.1, vec!["rust".to_string()]);
+
+    let doc = code_doc(Code {
+        attr: ("".to_string(), vec![], IndexMap::new()),
+        text: "a `` b".to_string(),
+    });
+    assert_eq!(write_markdown(&doc), "`a `` b`\n");
+}
+
+fn write_code_text(text: &str) -> String {
+    write_markdown(&code_doc(Code {
+        attr: ("".to_string(), vec![], IndexMap::new()),
+        text: text.to_string(),
+    }))
+}
+
+#[test]
+fn test_code_fence_is_the_shortest_absent_backtick_run() {
+    assert_eq!(write_code_text("a`b"), "``a`b``\n");
+    assert_eq!(write_code_text("a`b``c"), "```a`b``c```\n");
+    assert_eq!(write_code_text("a``b"), "`a``b`\n");
+}
+
+#[test]
+fn test_code_starting_or_ending_with_backticks_is_padded() {
+    assert_eq!(write_code_text("`"), "`` ` ``\n");
+    assert_eq!(write_code_text("`x`"), "`` `x` ``\n");
+    assert_eq!(write_code_text("x`"), "`` x` ``\n");
+}
+
+#[test]
+fn test_code_block_uses_three_backtick_fence() {
+    assert_eq!(
+        write_markdown(&read("```python\nprint(1)\n```\n")),
+        "```python\nprint(1)\n```\n"
+    );
+}
+
+#[test]
+fn test_code_block_containing_fence() {
+    let input = "````markdown\n```python\nprint(1)\n```\n````\n";
+    let output = write_markdown(&read(input));
+    assert_eq!(output, input);
+    assert_roundtrip(input);
+
+    let input = "`````\n````\n``\n`````\n";
+    assert_eq!(write_markdown(&read(input)), input);
+}
+
+#[test]
+fn test_raw_block_containing_fence() {
+    assert_roundtrip("````{=latex}\n\\begin{verbatim}\n```\n\\end{verbatim}\n````\n");
+}
+
+fn first_code_block_text(doc: &Pandoc) -> &str {
+    match doc.blocks.first() {
+        Some(Block::CodeBlock(code_block)) => &code_block.text,
+        _ => panic!("Expected a CodeBlock first, got {}", doc),
+    }
+}
+
+#[test]
+fn test_code_block_tabs_are_preserved() {
+    let input = "```\nif x:\n\treturn\ta\n```\n";
+    let doc = read(input);
+    assert_eq!(first_code_block_text(&doc), "if x:\n\treturn\ta");
+    assert_eq!(write_markdown(&doc), input);
+
+    let mut native = Vec::new();
+    writers::native::write(&doc, &mut native).unwrap();
+    assert!(String::from_utf8(native).unwrap().contains("\treturn\ta"));
+}
+
+#[test]
+fn test_indented_code_block_tabs_are_preserved() {
+    // only the four columns of indentation are stripped
+    assert_eq!(first_code_block_text(&read("    \tx\n")), "\tx");
+    assert_eq!(first_code_block_text(&read("      x\n")), "  x");
+    assert_eq!(first_code_block_text(&read("\tx\n    \ty\n")), "x\n\ty");
+}
+
+#[test]
+fn test_code_block_tabs_can_be_expanded() {
+    let doc = read("```\nif x:\n\treturn\ta\n```\n");
+    let options = MarkdownWriterOptions {
+        expand_tabs: Some(4),
+        ..Default::default()
+    };
+    let mut buf = Vec::new();
+    writers::markdown::write_with_options(&doc, &options, &mut buf).unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        "```\nif x:\n    return  a\n```\n"
+    );
+}
+
+fn write_ascii(doc: &Pandoc) -> std::io::Result<String> {
+    let options = MarkdownWriterOptions {
+        ascii: true,
+        ..Default::default()
+    };
+    let mut buf = Vec::new();
+    writers::markdown::write_with_options(doc, &options, &mut buf)?;
+    Ok(String::from_utf8(buf).unwrap())
+}
+
+#[test]
+fn test_ascii_writes_character_references() {
+    let doc = read("Café ☕ 😀 `naive` & &amp;copy;\n");
+    let output = write_ascii(&doc).unwrap();
+    assert_eq!(output, "Caf&#233; &#9749; &#128512; `naive` & \\&copy;\n");
+    assert_eq!(read(&output).to_string(), doc.to_string());
+}
+
+#[test]
+fn test_ascii_writes_math_as_tex_commands() {
+    let doc = read("$α≤β_1$ and $$x ∈ Ω$$\n");
+    assert_eq!(
+        write_ascii(&doc).unwrap(),
+        "$\\alpha\\leq\\beta_1$ and $$x \\in \\Omega$$\n"
+    );
+    assert_eq!(
+        write_ascii(&read("$\\acute{é}$ and $é2 éx$\n")).unwrap(),
+        "$\\acute{\\char\"E9}$ and $\\char\"E9 2 \\char\"E9x$\n"
+    );
+}
+
+#[test]
+fn test_ascii_rejects_non_ascii_code() {
+    for input in ["`é`\n", "```\né\n```\n", "`é`{=html}\n"] {
+        let error = write_ascii(&read(input)).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(error.to_string().contains("as ASCII"), "{}", error);
+    }
+    assert_eq!(write_ascii(&read("`e`\n")).unwrap(), "`e`\n");
+}
+
+#[test]
+fn test_character_reference_text_is_escaped() {
+    let doc = read("\\&#64; and \\&amp; and & alone\n");
+    let output = write_markdown(&doc);
+    assert_eq!(output, "\\&\\#64; and \\&amp; and & alone\n");
+    assert_eq!(read(&output).to_string(), doc.to_string());
+}
+
+#[test]
+fn test_soft_breaks_can_be_written_as_spaces() {
+    let doc = read("one\ntwo\nthree\n\n* four\n  five\n");
+    let options = MarkdownWriterOptions {
+        soft_break_as_space: true,
+        ..Default::default()
+    };
+    let mut buf = Vec::new();
+    writers::markdown::write_with_options(&doc, &options, &mut buf).unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        "one two three\n\n* four five\n"
+    );
+}
+
+fn str_inline(text: &str) -> Inline {
+    Inline::from(text)
+}
+
+fn space_inline() -> Inline {
+    Inline::Space(Space {
+        filename: None,
+        range: empty_range(),
+    })
+}
+
+#[test]
+fn test_line_block_preserves_leading_spaces() {
+    let doc = Pandoc {
+        meta: Meta::default(),
+        blocks: vec![Block::LineBlock(LineBlock {
+            content: vec![
+                vec![str_inline("The"), space_inline(), str_inline("first")],
+                // Pandoc puts the leading spaces of a line in the first Str
+                vec![
+                    str_inline("\u{a0}\u{a0}\u{a0}indented"),
+                    space_inline(),
+                    str_inline("line"),
+                ],
+                vec![],
+                vec![space_inline(), space_inline(), str_inline("spaced")],
+                vec![str_inline("|pipe")],
+            ],
+            filename: None,
+            range: empty_range(),
+        })],
+    };
+    assert_eq!(
+        write_markdown(&doc),
+        "| The first\n| \\ \\ \\ indented line\n|\n| \\ \\ spaced\n| \\|pipe\n"
+    );
+}
+
+fn definition_list_doc(content: Vec<(Vec<Inline>, Vec<Vec<Block>>)>) -> Pandoc {
+    Pandoc {
+        meta: Meta::default(),
+        blocks: vec![Block::DefinitionList(DefinitionList {
+            content,
+            filename: None,
+            range: empty_range(),
+        })],
+    }
+}
+
+fn plain(content: Vec<Inline>) -> Block {
+    Block::Plain(Plain {
+        content,
+        filename: None,
+        range: empty_range(),
+    })
+}
+
+#[test]
+fn test_definition_list_with_block_definitions() {
+    let term = vec![
+        str_inline("A"),
+        space_inline(),
+        Emph {
+            content: vec![str_inline("term")],
+        }
+        .into(),
+    ];
+    let first = vec![
+        vec![
+            str_inline("First"),
+            space_inline(),
+            str_inline("paragraph."),
+        ]
+        .into(),
+        vec![
+            str_inline("Second"),
+            space_inline(),
+            str_inline("paragraph."),
+        ]
+        .into(),
+    ];
+    let second = vec![
+        vec![
+            str_inline("Another"),
+            space_inline(),
+            str_inline("definition."),
+        ]
+        .into(),
+    ];
+    let doc = definition_list_doc(vec![
+        (term, vec![first, second]),
+        (
+            vec![str_inline("Next")],
+            vec![vec![vec![str_inline("More.")].into()]],
+        ),
+    ]);
+    assert_eq!(
+        write_markdown(&doc),
+        "A *term*\n\n:   First paragraph.\n\n    Second paragraph.\n\n:   Another definition.\n\nNext\n\n:   More.\n"
+    );
+}
+
+#[test]
+fn test_tight_definition_list() {
+    let doc = definition_list_doc(vec![
+        (
+            vec![str_inline("One")],
+            vec![
+                vec![plain(vec![str_inline("first")])],
+                vec![plain(vec![str_inline("second")])],
+            ],
+        ),
+        (
+            vec![str_inline("Two")],
+            vec![vec![plain(vec![str_inline("third")])]],
+        ),
+    ]);
+    assert_eq!(
+        write_markdown(&doc),
+        "One\n:   first\n:   second\n\nTwo\n:   third\n"
+    );
+}
+
+#[test]
+fn test_shortcode_like_text_is_not_read_back_as_shortcode() {
+    // text that looks like a shortcode, as from `\{\{\< x \>\}\}`
+    let doc = Pandoc {
+        meta: Meta::default(),
+        blocks: vec![Block::from(vec![
+            str_inline("{{<"),
+            space_inline(),
+            str_inline("x"),
+            space_inline(),
+            str_inline(">}}"),
+            space_inline(),
+            str_inline("{{{<y>}}}"),
+        ])],
+    };
+    let output = write_markdown(&doc);
+    assert_eq!(output, "\\{\\{\\< x \\>\\}\\} \\{\\{\\{\\<y\\>\\}\\}\\}\n");
+    assert_eq!(read(&output).to_string(), doc.to_string());
+}
+
+#[test]
+fn test_shortcode_like_text_roundtrip() {
+    assert_roundtrip("\\{\\{\\< x \\>\\}\\} and [\\{\\{\\< y \\>\\}\\}]{.c}\n");
+}
+
+#[test]
+fn test_div_fence() {
+    let input = "::: {.note}\nx\n:::\n";
+    assert_eq!(write_markdown(&read(input)), input);
+}
+
+#[test]
+fn test_nested_div_gets_longer_outer_fence() {
+    let output = write_markdown(&read("::: {.outer}\n::: {.inner}\nx\n:::\n:::\n"));
+    assert_eq!(output, ":::: {.outer}\n::: {.inner}\nx\n:::\n::::\n");
+    assert_roundtrip(&output);
+
+    let output = write_markdown(&read(
+        "::: {.a}\n::: {.b}\n::: {.c}\nx\n:::\n:::\n\n::: {.d}\ny\n:::\n:::\n",
+    ));
+    assert_eq!(
+        output,
+        "::::: {.a}\n:::: {.b}\n::: {.c}\nx\n:::\n::::\n\n::: {.d}\ny\n:::\n:::::\n"
+    );
+}
+
+fn first_table(doc: &Pandoc) -> &Table {
+    match doc.blocks.first() {
+        Some(Block::Table(table)) => table,
+        _ => panic!("Expected a Table first, got {}", doc),
+    }
+}
+
+#[test]
+fn test_simple_table_is_written_as_pipe_table() {
+    let input = "| foo | bar | baz | qux |\n|----:|:----|:---:|-----|\n| 1 | `x` | *a b* | |\n";
+    let doc = read(input);
+    assert_eq!(table_style(first_table(&doc)), TableStyle::Pipe);
+    let output = write_markdown(&doc);
+    assert_eq!(
+        output,
+        concat!(
+            "| foo | bar | baz   | qux |\n",
+            "| --: | :-- | :---: | --- |\n",
+            "| 1   | `x` | *a b* |     |\n",
+        )
+    );
+    assert_roundtrip(input);
+    assert_roundtrip("| a \\| b | [link](u) |\n|---|---|\n| 1 | 2 |\n");
+}
+
+#[test]
+fn test_table_with_caption_is_not_a_pipe_table() {
+    let mut doc = read("| a |\n|---|\n| 1 |\n");
+    let Some(Block::Table(table)) = doc.blocks.first_mut() else {
+        panic!("Expected a Table first");
+    };
+    table.caption.long = Some(vec![Block::Plain(Plain {
+        content: vec![str_inline("Caption")],
+        filename: None,
+        range: empty_range(),
+    })]);
+    assert_eq!(table_style(first_table(&doc)), TableStyle::Grid);
+}
+
+fn paragraph(text: &str) -> Block {
+    Block::from(vec![Inline::from(text)])
+}
+
+#[test]
+fn test_table_with_block_content_is_written_as_grid_table() {
+    let mut doc = read(
+        "| Fruit | Price | Notes |\n|:--|--:|:-:|\n| Bananas | 1.34 | x |\n| Kiwi | 2.00 | y |\n",
+    );
+    let Some(Block::Table(table)) = doc.blocks.first_mut() else {
+        panic!("Expected a Table first");
+    };
+    table.bodies[0].body[0].cells[2].content = vec![paragraph("first"), paragraph("second")];
+    assert_eq!(table_style(first_table(&doc)), TableStyle::Grid);
+    assert_eq!(
+        write_markdown(&doc),
+        concat!(
+            "+---------+-------+--------+\n",
+            "| Fruit   | Price | Notes  |\n",
+            "+:========+======:+:======:+\n",
+            "| Bananas | 1.34  | first  |\n",
+            "|         |       |        |\n",
+            "|         |       | second |\n",
+            "+---------+-------+--------+\n",
+            "| Kiwi    | 2.00  | y      |\n",
+            "+---------+-------+--------+\n",
+        )
+    );
+}
+
+#[test]
+fn test_grid_table_caption_and_foot() {
+    let mut doc = read("| a | b |\n|---|---|\n| 1 | 2 |\n| 3 | 4 |\n");
+    let Some(Block::Table(table)) = doc.blocks.first_mut() else {
+        panic!("Expected a Table first");
+    };
+    table.foot.rows.push(table.bodies[0].body.pop().unwrap());
+    table.caption.long = Some(vec![Block::Plain(Plain {
+        content: vec![str_inline("Totals")],
+        filename: None,
+        range: empty_range(),
+    })]);
+    assert_eq!(
+        write_markdown(&doc),
+        concat!(
+            "+-----+-----+\n",
+            "| a   | b   |\n",
+            "+=====+=====+\n",
+            "| 1   | 2   |\n",
+            "+=====+=====+\n",
+            "| 3   | 4   |\n",
+            "+=====+=====+\n",
+            "\n",
+            ": Totals\n",
+        )
+    );
+}
+
+fn write_with_column_width(doc: &Pandoc, width: usize) -> String {
+    let options = MarkdownWriterOptions {
+        max_table_column_width: Some(width),
+        ..Default::default()
+    };
+    let mut buf = Vec::new();
+    writers::markdown::write_with_options(doc, &options, &mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn test_grid_table_cells_wrap_at_word_boundaries() {
+    let mut doc = read(
+        "| Name | Notes |\n|---|---|\n| a | see `some code` and the docs at [the site](https://example.com/a/very/long/path/to/a/page.html) |\n",
+    );
+    let Some(Block::Table(table)) = doc.blocks.first_mut() else {
+        panic!("Expected a Table first");
+    };
+    let Block::Plain(plain) = &table.bodies[0].body[0].cells[1].content[0] else {
+        panic!("Expected a Plain cell");
+    };
+    table.bodies[0].body[0].cells[1].content = vec![
+        Block::Paragraph(Paragraph {
+            content: plain.content.clone(),
+            filename: None,
+            range: empty_range(),
+        }),
+        paragraph("x"),
+    ];
+    // the link is longer than the column and overflows
+    assert_eq!(
+        write_with_column_width(&doc, 20),
+        concat!(
+            "+------+-----------------------------------------------------------------+\n",
+            "| Name | Notes                                                           |\n",
+            "+======+=================================================================+\n",
+            "| a    | see `some code` and                                             |\n",
+            "|      | the docs at                                                     |\n",
+            "|      | [the site](https://example.com/a/very/long/path/to/a/page.html) |\n",
+            "|      |                                                                 |\n",
+            "|      | x                                                               |\n",
+            "+------+-----------------------------------------------------------------+\n",
+        )
+    );
+}
+
+fn try_write(doc: &Pandoc) -> std::io::Result<String> {
+    let mut buf = Vec::new();
+    writers::markdown::write(doc, &mut buf)?;
+    Ok(String::from_utf8(buf).unwrap())
+}
+
+#[test]
+fn test_metadata_is_written_as_front_matter() {
+    let input = "---\ntitle: My *doc*\nauthor:\n  - A\n  - B\n---\n\nText.\n";
+    let doc = read(input);
+    assert_eq!(write_markdown(&doc), input);
+    assert_eq!(read(&write_markdown(&doc)), doc);
+}
+
+#[test]
+fn test_content_without_markdown_syntax_is_an_error() {
+    let note = Inline::Note(Note {
+        content: vec![Block::CodeBlock(CodeBlock {
+            attr: empty_attr(),
+            text: "x".to_string(),
+            filename: None,
+            range: empty_range(),
+        })],
+    });
+    let doc = Pandoc {
+        meta: Meta::default(),
+        blocks: vec![Block::from(vec![Inline::from("a"), note])],
+    };
+    let error = try_write(&doc).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("note holding a CodeBlock"));
+
+    let figure = Block::Figure(Figure {
+        attr: empty_attr(),
+        caption: Caption {
+            short: None,
+            long: None,
+        },
+        content: vec![paragraph("x")],
+        filename: None,
+        range: empty_range(),
+    });
+    let doc = Pandoc {
+        meta: Meta::default(),
+        blocks: vec![figure],
+    };
+    assert!(try_write(&doc).is_err());
+}
+
+#[test]
+fn test_multi_paragraph_note_is_an_error() {
+    let note = Inline::Note(Note {
+        content: vec![paragraph("one"), paragraph("two")],
+    });
+    let doc = Pandoc {
+        meta: Meta::default(),
+        blocks: vec![Block::from(vec![Inline::from("a"), note])],
+    };
+    let error = try_write(&doc).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("note with more than one block"));
+}
+
+#[test]
+fn test_quotes_inside_words_are_not_escaped() {
+    assert_eq!(write_markdown(&read("don't a\"b\n")), "don't a\"b\n");
+    assert_eq!(write_markdown(&read("\\'a \\\"b\n")), "\\'a \\\"b\n");
+}
+
+#[test]
+fn test_span_without_attributes_is_written_as_its_content() {
+    assert_eq!(write_markdown(&read("[a *b*]{} c\n")), "a *b* c\n");
+}
+
+#[test]
+fn test_text_starting_a_line_is_not_read_as_a_block() {
+    assert_eq!(
+        write_markdown(&read("1\\. a\nb\n\\- c\n")),
+        "1\\. a\nb\n\\- c\n"
+    );
+    assert_eq!(write_markdown(&read("a\n\\===\n")), "a\n\\===\n");
+}
+
+#[test]
+fn test_dashes_and_dots_read_without_smart_punctuation_roundtrip() {
+    let options = readers::qmd::MarkdownParserOptions {
+        no_smart: true,
+        ..Default::default()
+    };
+    let read_no_smart = |input: &str| {
+        readers::qmd::read_with_options(input.as_bytes(), &options, &mut std::io::sink())
+            .unwrap()
+            .0
+    };
+    let doc = read_no_smart("a -- b --- c ...\n");
+    let output = write_markdown(&doc);
+    assert_eq!(output, "a -\\- b -\\-\\- c .\\.\\.\n");
+    assert_eq!(read(&output).to_string(), doc.to_string());
+}
+
+#[test]
+fn test_table_with_spans_is_written_as_grid_table() {
+    let mut doc = read("| a | b |\n|---|---|\n| 1 | 2 |\n| 3 | 4 |\n");
+    let Some(Block::Table(table)) = doc.blocks.first_mut() else {
+        panic!("Expected a Table first");
+    };
+    let head = &mut table.head.rows[0];
+    head.cells.pop();
+    head.cells[0].col_span = 2;
+    let body = &mut table.bodies[0].body;
+    body[0].cells[0].row_span = 2;
+    body[1].cells.remove(0);
+    assert_eq!(table_style(first_table(&doc)), TableStyle::Grid);
+    assert_eq!(
+        write_markdown(&doc),
+        concat!(
+            "+-----+-----+\n",
+            "| a         |\n",
+            "+=====+=====+\n",
+            "| 1   | 2   |\n",
+            "|     +-----+\n",
+            "|     | 4   |\n",
+            "+-----+-----+\n",
+        )
+    );
+}
+
+#[test]
+fn test_spanning_cells_use_the_lines_between_rows() {
+    let mut doc = read("| a | b |\n|---|---|\n| 1 | 2 |\n| 3 | 4 |\n");
+    let Some(Block::Table(table)) = doc.blocks.first_mut() else {
+        panic!("Expected a Table first");
+    };
+    let body = &mut table.bodies[0].body;
+    body[0].cells[1].row_span = 2;
+    body[0].cells[1].content = vec![paragraph("x"), paragraph("y")];
+    body[1].cells.pop();
+    assert_eq!(
+        write_markdown(&doc),
+        concat!(
+            "+-----+-----+\n",
+            "| a   | b   |\n",
+            "+=====+=====+\n",
+            "| 1   | x   |\n",
+            "+-----+     |\n",
+            "| 3   | y   |\n",
+            "+-----+-----+\n",
+        )
+    );
+}
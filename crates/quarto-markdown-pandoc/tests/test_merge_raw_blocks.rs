@@ -0,0 +1,66 @@
+use quarto_markdown_pandoc::pandoc::location::empty_range;
+use quarto_markdown_pandoc::pandoc::{Block, Div, Pandoc};
+use quarto_markdown_pandoc::readers;
+use quarto_markdown_pandoc::transforms::merge_raw_blocks::merge_raw_blocks;
+
+mod common;
+use common::raw_block;
+
+fn doc(blocks: Vec<Block>) -> Pandoc {
+    Pandoc {
+        blocks,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_adjacent_html_blocks_merge() {
+    let merged = merge_raw_blocks(doc(vec![
+        raw_block("html", "<div class=\"box\">"),
+        raw_block("html", "<p>hi</p>"),
+        raw_block("html", "</div>"),
+    ]));
+    assert_eq!(
+        merged.blocks,
+        vec![raw_block("html", "<div class=\"box\">\n<p>hi</p>\n</div>")]
+    );
+}
+
+#[test]
+fn test_differing_formats_do_not_merge() {
+    let blocks = vec![
+        raw_block("html", "<hr>"),
+        raw_block("latex", "\\hrule"),
+        raw_block("html", "<hr>"),
+    ];
+    assert_eq!(merge_raw_blocks(doc(blocks.clone())).blocks, blocks);
+}
+
+#[test]
+fn test_merge_inside_div() {
+    let merged = merge_raw_blocks(doc(vec![Block::Div(Div {
+        attr: (String::new(), vec![], Default::default()),
+        content: vec![raw_block("html", "<b>"), raw_block("html", "</b>")],
+        filename: None,
+        range: empty_range(),
+    })]));
+    let Some(Block::Div(div)) = merged.blocks.first() else {
+        panic!("Expected a Div, got {}", merged);
+    };
+    assert_eq!(div.content, vec![raw_block("html", "<b>\n</b>")]);
+}
+
+#[test]
+fn test_merge_read_blocks() {
+    let input = "```{=latex}\n\\begin{center}\n```\n\n```{=latex}\n\\end{center}\n```\n";
+    let doc = readers::qmd::read(input.as_bytes(), &mut std::io::sink()).unwrap();
+    assert_eq!(doc.blocks.len(), 2);
+    let merged = merge_raw_blocks(doc);
+    let [Block::RawBlock(raw)] = merged.blocks.as_slice() else {
+        panic!("Expected a single RawBlock, got {}", merged);
+    };
+    assert_eq!(raw.format, "latex");
+    assert_eq!(raw.text, "\\begin{center}\n\\end{center}");
+    assert_eq!(raw.range.start.row, 0);
+    assert_eq!(raw.range.end.row, 7);
+}
@@ -1,5 +1,6 @@
 use quarto_markdown_pandoc::pandoc::location::{Location, Range};
-use quarto_markdown_pandoc::pandoc::{MetaValue, RawBlock, rawblock_to_meta};
+use quarto_markdown_pandoc::pandoc::{Block, MetaValue, RawBlock, rawblock_to_meta};
+use quarto_markdown_pandoc::readers::qmd::{MarkdownParserOptions, read_with_options};
 use std::fs;
 
 #[test]
@@ -50,3 +51,51 @@ fn test_metadata_parsing() {
         Some(MetaValue::MetaList(_))
     ));
 }
+
+fn read_keeping_meta_raw(
+    input: &str,
+    keep_meta_raw: bool,
+) -> quarto_markdown_pandoc::pandoc::Pandoc {
+    let options = MarkdownParserOptions {
+        keep_meta_raw,
+        ..Default::default()
+    };
+    read_with_options(input.as_bytes(), &options, &mut std::io::sink())
+        .unwrap()
+        .0
+}
+
+#[test]
+fn test_keep_meta_raw() {
+    let input = "---\ntitle: Hello\n---\n\nText.\n";
+    let doc = read_keeping_meta_raw(input, true);
+    let Some(Block::RawBlock(raw)) = doc.blocks.first() else {
+        panic!("Expected a RawBlock first, got {}", doc);
+    };
+    assert_eq!(raw.format, "quarto_minus_metadata");
+    assert_eq!(raw.text, "---\ntitle: Hello\n---\n");
+    assert!(matches!(doc.blocks.get(1), Some(Block::Paragraph(_))));
+    // the metadata is still parsed
+    assert!(matches!(
+        doc.meta.get("title"),
+        Some(MetaValue::MetaInlines(_))
+    ));
+
+    let doc = read_keeping_meta_raw(input, false);
+    assert_eq!(doc.blocks.len(), 1);
+    assert!(doc.meta.contains_key("title"));
+}
+
+#[test]
+fn test_keep_meta_raw_with_lexical_metadata() {
+    let input = "---\n_scope: lexical\nkey: value\n---\n\nText.\n";
+    let doc = read_keeping_meta_raw(input, true);
+    assert!(matches!(
+        doc.blocks.as_slice(),
+        [
+            Block::RawBlock(_),
+            Block::BlockMetadata(_),
+            Block::Paragraph(_)
+        ]
+    ));
+}
@@ -0,0 +1,62 @@
+use quarto_markdown_pandoc::utils::meta_schema::{MetaType, meta_schema, write_meta_schema};
+
+mod common;
+use common::read;
+
+fn schema(input: &str) -> String {
+    let mut buf = Vec::new();
+    write_meta_schema(&read(input).meta, &mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn test_mixed_front_matter() {
+    let input = concat!(
+        "---\n",
+        "title: Hello *world*\n",
+        "draft: true\n",
+        "authors:\n",
+        "  - name: A\n",
+        "    email: a@example.com\n",
+        "  - name: B\n",
+        "    orcid: 1\n",
+        "tags: [a, b]\n",
+        "format:\n",
+        "  html:\n",
+        "    toc: true\n",
+        "empty: []\n",
+        "---\n",
+        "\n",
+        "Text.\n",
+    );
+    assert_eq!(
+        schema(input),
+        concat!(
+            "title: inlines\n",
+            "draft: bool\n",
+            "authors: list of map\n",
+            "  name: inlines\n",
+            "  email: inlines\n",
+            "  orcid: inlines\n",
+            "tags: list of inlines\n",
+            "format: map\n",
+            "  html: map\n",
+            "    toc: bool\n",
+            "empty: list\n",
+        )
+    );
+}
+
+#[test]
+fn test_list_with_mixed_elements() {
+    let doc = read("---\nitems:\n  - a\n  - b: true\n  - c\n  - [d]\n---\n");
+    assert_eq!(
+        meta_schema(&doc.meta).get("items").map(MetaType::name),
+        Some("list of inlines | map | list of inlines".to_string())
+    );
+}
+
+#[test]
+fn test_no_front_matter() {
+    assert_eq!(schema("Text.\n"), "");
+}
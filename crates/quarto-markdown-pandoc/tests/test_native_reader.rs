@@ -0,0 +1,115 @@
+use quarto_markdown_pandoc::pandoc::{Block, Inline, MetaValue, Pandoc};
+use quarto_markdown_pandoc::readers;
+
+fn read_qmd(input: &str) -> Pandoc {
+    readers::qmd::read(input.as_bytes(), &mut std::io::sink()).unwrap()
+}
+
+fn read_native(input: &str) -> Pandoc {
+    readers::native::read(input.as_bytes()).unwrap()
+}
+
+fn native_error(input: &str) -> String {
+    let errors = readers::native::read(input.as_bytes()).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    errors[0].clone()
+}
+
+// Reading our native output back in and writing it again gives the same
+// output.
+fn assert_native_round_trips(qmd: &str) {
+    let native = read_qmd(qmd).to_string();
+    assert_eq!(read_native(&native).to_string(), native, "{:?}", qmd);
+}
+
+#[test]
+fn test_round_trip_inlines() {
+    assert_native_round_trips(
+        "A *b* **c** `d` $e$ [f](g \"h\") \"q\" ^sup^ ~sub~ [span]{.x} ~~del~~\nnext\\\nline\n",
+    );
+}
+
+#[test]
+fn test_round_trip_blocks() {
+    assert_native_round_trips(
+        "# Hi {#top .c k=v}\n\n- one\n- two\n\n3. x\n4. y\n\n> quote\n\n```{.python}\nprint(\"\\\"hi\\\"\")\n```\n\n::: {.div}\npara\n:::\n\n---\n",
+    );
+}
+
+#[test]
+fn test_round_trip_table_figure_and_cites() {
+    assert_native_round_trips(
+        "| a | b |\n|---|--:|\n| 1 | 2 |\n\n![cap](i.png){#fig-i}\n\n@cite says [see @foo, p. 3].\n",
+    );
+}
+
+#[test]
+fn test_round_trip_note() {
+    assert_native_round_trips("A note.^[With *content*.]\n");
+}
+
+#[test]
+fn test_pandoc_layout() {
+    // `pandoc -t native` lays the output out over several lines, with
+    // non-ASCII characters as decimal escapes
+    let doc = read_native(
+        "[ Header\n    1\n    ( \"caf\\233\" , [] , [] )\n    [ Str \"Caf\\233\\&1\" ]\n, Para [ Str \"tab\\tand\\nnewline\" ]\n]\n",
+    );
+    assert_eq!(
+        doc.to_string(),
+        "[ Header 1 ( \"café\" , [] , [] ) [Str \"Café1\"], Para [Str \"tab\tand\\nnewline\"] ]"
+    );
+}
+
+#[test]
+fn test_standalone_document() {
+    let doc = read_native(
+        "Pandoc\n  Meta\n    { unMeta =\n        fromList [ ( \"title\" , MetaInlines [ Str \"T\" ] ) , ( \"draft\" , MetaBool True ) ]\n    }\n  [ Para [ Str \"x\" ] ]\n",
+    );
+    assert!(
+        matches!(doc.meta.get("title"), Some(MetaValue::MetaInlines(inlines)) if inlines == &vec![Inline::from("T")])
+    );
+    assert!(matches!(
+        doc.meta.get("draft"),
+        Some(MetaValue::MetaBool(true))
+    ));
+    assert!(matches!(doc.blocks.as_slice(), [Block::Paragraph(_)]));
+}
+
+#[test]
+fn test_parenthesized_constructors() {
+    let doc = read_native("[(Para [(Str \"x\"), RawInline (Format \"html\") \"<br>\"])]");
+    assert_eq!(
+        doc.to_string(),
+        "[ Para [Str \"x\", RawInline (Format \"html\") \"<br>\"] ]"
+    );
+}
+
+#[test]
+fn test_empty_document() {
+    assert_eq!(read_native("[]"), Pandoc::default());
+}
+
+#[test]
+fn test_unsupported_constructor() {
+    assert_eq!(
+        native_error("[ Para [Str \"x\", Blink [Str \"y\"]] ]"),
+        "Error: unsupported inline `Blink` at line 1, column 18 of the native input"
+    );
+}
+
+#[test]
+fn test_syntax_errors() {
+    assert_eq!(
+        native_error("[ Para [Str \"x\"]\n, Para [Str \"y\"]"),
+        "Error: expected `,` or `]` at line 2, column 17 of the native input"
+    );
+    assert_eq!(
+        native_error("[ Para [Str \"x]] ]"),
+        "Error: unterminated string at line 1, column 19 of the native input"
+    );
+    assert_eq!(
+        native_error("[ Para [] ] trailing"),
+        "Error: unexpected input after the document at line 1, column 13 of the native input"
+    );
+}
@@ -0,0 +1,55 @@
+use quarto_markdown_pandoc::pandoc::{Meta, Pandoc};
+use quarto_markdown_pandoc::utils::node_stats::{NodeStats, count_nodes};
+
+mod common;
+use common::read;
+
+fn read_fixture(name: &str) -> Pandoc {
+    let path = format!("tests/node-stats/{}", name);
+    let input = std::fs::read_to_string(&path).expect("Failed to read fixture");
+    read(&input)
+}
+
+#[test]
+fn test_block_counts() {
+    let stats = count_nodes(&read_fixture("mixed.qmd"));
+    assert_eq!(stats.block_count("Header"), 2);
+    assert_eq!(stats.block_count("Para"), 2);
+    assert_eq!(stats.block_count("CodeBlock"), 1);
+    assert_eq!(stats.block_count("BulletList"), 1);
+    // two list items, plus the figure's caption and content
+    assert_eq!(stats.block_count("Plain"), 4);
+    assert_eq!(stats.block_count("Div"), 1);
+    assert_eq!(stats.block_count("Figure"), 1);
+    assert_eq!(stats.block_count("Table"), 0);
+    assert_eq!(stats.total_blocks(), 12);
+}
+
+#[test]
+fn test_inline_counts() {
+    let stats = count_nodes(&read_fixture("mixed.qmd"));
+    assert_eq!(stats.inline_count("Link"), 2);
+    assert_eq!(stats.inline_count("Cite"), 1);
+    assert_eq!(stats.inline_count("Code"), 1);
+    assert_eq!(stats.inline_count("Strong"), 1);
+    assert_eq!(stats.inline_count("Image"), 1);
+    assert_eq!(stats.inline_count("Math"), 0);
+}
+
+#[test]
+fn test_empty_document() {
+    let doc = Pandoc {
+        meta: Meta::default(),
+        blocks: vec![],
+    };
+    assert_eq!(count_nodes(&doc), NodeStats::default());
+}
+
+#[test]
+fn test_nested_inlines_are_counted() {
+    let stats = count_nodes(&read("*a **b [c]{.x}**d*\n"));
+    assert_eq!(stats.inline_count("Emph"), 1);
+    assert_eq!(stats.inline_count("Strong"), 1);
+    assert_eq!(stats.inline_count("Span"), 1);
+    assert_eq!(stats.inline_count("Str"), 4);
+}
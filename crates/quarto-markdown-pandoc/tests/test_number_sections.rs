@@ -0,0 +1,98 @@
+use quarto_markdown_pandoc::pandoc::{Block, Inline, Pandoc};
+use quarto_markdown_pandoc::transforms::number_sections::{NumberSectionsOptions, number_sections};
+
+mod common;
+use common::read;
+
+// Returns the section number of every header, or None if it wasn't numbered.
+fn section_numbers(doc: &Pandoc) -> Vec<Option<String>> {
+    doc.blocks
+        .iter()
+        .filter_map(|block| match block {
+            Block::Header(header) => Some(match header.content.first() {
+                Some(Inline::Span(span))
+                    if span.attr.1 == vec!["header-section-number".to_string()] =>
+                {
+                    match span.content.as_slice() {
+                        [Inline::Str(s)] => Some(s.text.clone()),
+                        other => panic!("Unexpected section number content {:?}", other),
+                    }
+                }
+                _ => None,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+const MIXED: &str = r#"# One
+
+## One A
+
+### One A i
+
+## Skipped {.unnumbered}
+
+## One B
+
+# Preface {.unnumbered}
+
+## Under preface
+
+# Two
+
+### Two, skipping a level
+"#;
+
+#[test]
+fn test_number_sections_mixed_hierarchy() {
+    let doc = number_sections(read(MIXED), &NumberSectionsOptions::default());
+    assert_eq!(
+        section_numbers(&doc),
+        vec![
+            Some("1".to_string()),
+            Some("1.1".to_string()),
+            Some("1.1.1".to_string()),
+            None,
+            Some("1.2".to_string()),
+            None,
+            Some("1.3".to_string()),
+            Some("2".to_string()),
+            Some("2.0.1".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_number_sections_keeps_header_content() {
+    let doc = number_sections(read("# Hello\n"), &NumberSectionsOptions::default());
+    let Some(Block::Header(header)) = doc.blocks.first() else {
+        panic!("Expected a Header first, got {}", doc);
+    };
+    assert_eq!(header.content.len(), 3);
+    assert!(matches!(header.content[1], Inline::Space(_)));
+    assert!(matches!(&header.content[2], Inline::Str(s) if s.text == "Hello"));
+}
+
+#[test]
+fn test_number_sections_start_level_and_depth() {
+    let options = NumberSectionsOptions {
+        start_level: 2,
+        depth: 1,
+    };
+    let doc = number_sections(read(MIXED), &options);
+    assert_eq!(
+        section_numbers(&doc),
+        vec![
+            None,
+            Some("1".to_string()),
+            None,
+            None,
+            Some("2".to_string()),
+            None,
+            Some("3".to_string()),
+            None,
+            None,
+        ]
+    );
+}
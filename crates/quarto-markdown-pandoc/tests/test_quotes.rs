@@ -0,0 +1,138 @@
+use quarto_markdown_pandoc::pandoc::{Block, Inline, QuoteType};
+use quarto_markdown_pandoc::readers;
+use quarto_markdown_pandoc::readers::qmd::{MarkdownParserOptions, read_with_options};
+
+mod common;
+use common::read_inlines;
+
+// The text of a paragraph, with single quotes written as ‘ and ’ when they
+// were read as Quoted.
+fn quoted_text(input: &str) -> String {
+    fn go(inlines: &[Inline], out: &mut String) {
+        for inline in inlines {
+            match inline {
+                Inline::Str(s) => out.push_str(&s.text),
+                Inline::Space(_) | Inline::SoftBreak(_) => out.push(' '),
+                Inline::Quoted(q) => {
+                    let (open, close) = match q.quote_type {
+                        QuoteType::SingleQuote => ('‘', '’'),
+                        QuoteType::DoubleQuote => ('“', '”'),
+                    };
+                    out.push(open);
+                    go(&q.content, out);
+                    out.push(close);
+                }
+                Inline::Emph(e) => go(&e.content, out),
+                other => panic!("Unexpected inline {:?}", other),
+            }
+        }
+    }
+    let mut out = String::new();
+    go(&read_inlines(input), &mut out);
+    out
+}
+
+#[test]
+fn test_contractions_keep_apostrophes() {
+    assert_eq!(quoted_text("don't\n"), "don't");
+    assert_eq!(quoted_text("'tis the season\n"), "'tis the season");
+    assert_eq!(quoted_text("the '90s\n"), "the '90s");
+    assert_eq!(quoted_text("_'tis_\n"), "'tis");
+}
+
+#[test]
+fn test_possessives_keep_apostrophes() {
+    assert_eq!(quoted_text("the dogs' bones\n"), "the dogs' bones");
+    assert_eq!(quoted_text("James' book\n"), "James' book");
+}
+
+#[test]
+fn test_single_quotes() {
+    assert_eq!(quoted_text("'quoted'\n"), "‘quoted’");
+    assert_eq!(quoted_text("'a' and 'b'\n"), "‘a’ and ‘b’");
+    assert_eq!(quoted_text("'x', he said\n"), "‘x’, he said");
+    assert_eq!(quoted_text("*'a'*\n"), "‘a’");
+}
+
+#[test]
+fn test_apostrophes_inside_quotes() {
+    assert_eq!(
+        quoted_text("He said 'don't go' loudly.\n"),
+        "He said ‘don't go’ loudly."
+    );
+    assert_eq!(quoted_text("it's 'fine'\n"), "it's ‘fine’");
+    // a possessive that could close the quote does, as in Pandoc
+    assert_eq!(quoted_text("'the dogs' bones'\n"), "‘the dogs’ bones'");
+}
+
+#[test]
+fn test_quotes_in_code_spans_do_not_close() {
+    let inlines = read_inlines("Use 'single `x'` quotes.\n");
+    assert!(!inlines.iter().any(|i| matches!(i, Inline::Quoted(_))));
+    assert!(
+        inlines
+            .iter()
+            .any(|i| matches!(i, Inline::Code(code) if code.text == "x'"))
+    );
+
+    let inlines = read_inlines("'a `x` b'\n");
+    assert!(matches!(inlines.as_slice(), [Inline::Quoted(_)]));
+}
+
+fn read_inlines_no_smart(input: &str) -> Vec<Inline> {
+    let options = MarkdownParserOptions {
+        no_smart: true,
+        ..Default::default()
+    };
+    let doc = read_with_options(input.as_bytes(), &options, &mut std::io::sink())
+        .unwrap()
+        .0;
+    let Some(Block::Paragraph(para)) = doc.blocks.into_iter().next() else {
+        panic!("Expected a Paragraph");
+    };
+    para.content
+}
+
+#[test]
+fn test_no_smart_keeps_straight_quotes() {
+    let inlines = read_inlines_no_smart("'quoted'\n");
+    let [Inline::Str(s)] = inlines.as_slice() else {
+        panic!("Expected a single Str, got {:?}", inlines);
+    };
+    assert_eq!(s.text, "'quoted'");
+
+    let inlines = read_inlines_no_smart("say \"hi\"\n");
+    assert!(!inlines.iter().any(|i| matches!(i, Inline::Quoted(_))));
+    let Some(Inline::Str(s)) = inlines.last() else {
+        panic!("Expected a Str last, got {:?}", inlines);
+    };
+    assert_eq!(s.text, "\"hi\"");
+}
+
+#[test]
+fn test_no_smart_keeps_dashes_and_ellipses() {
+    let inlines = read_inlines_no_smart("a -- b --- c...\n");
+    let strs: Vec<&str> = inlines
+        .iter()
+        .filter_map(|inline| match inline {
+            Inline::Str(s) => Some(s.text.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(strs, vec!["a", "--", "b", "---", "c..."]);
+}
+
+#[test]
+fn test_no_smart_reads_quotes_the_quote_parser_rejects() {
+    // an unclosed quote is an error when quotes are smart
+    for input in ["Say \"hi.\n", "Hello 'world [of' quotes](u).\n"] {
+        assert!(readers::qmd::read(input.as_bytes(), &mut std::io::sink()).is_err());
+        let inlines = read_inlines_no_smart(input);
+        assert!(!inlines.iter().any(|i| matches!(i, Inline::Quoted(_))));
+    }
+    let inlines = read_inlines_no_smart("Say \"hi.\n");
+    let Some(Inline::Str(s)) = inlines.last() else {
+        panic!("Expected a Str last, got {:?}", inlines);
+    };
+    assert_eq!(s.text, "\"hi.");
+}
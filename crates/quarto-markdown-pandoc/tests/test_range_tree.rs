@@ -0,0 +1,48 @@
+use quarto_markdown_pandoc::pandoc::location::empty_range;
+use quarto_markdown_pandoc::readers;
+use quarto_markdown_pandoc::utils::range_tree::{format_range, write_range_tree};
+
+fn range_tree(input: &str) -> String {
+    let doc = readers::qmd::read(input.as_bytes(), &mut std::io::sink()).unwrap();
+    let mut buf = Vec::new();
+    write_range_tree(&doc, &mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn test_range_tree_of_small_document() {
+    assert_eq!(
+        range_tree("# Title\n\nHello *world*\n"),
+        concat!(
+            "Header 1 1:1-2:1\n",
+            "  Str \"Title\" 1:3-1:8\n",
+            "Para 3:1-4:1\n",
+            "  Str \"Hello\" 3:1-3:6\n",
+            "  Space 3:6-3:7\n",
+            "  Emph\n",
+            "    Str \"world\" 3:8-3:13\n",
+        )
+    );
+}
+
+#[test]
+fn test_range_tree_of_nested_blocks() {
+    let output = range_tree("> - one\n> - two\n");
+    assert!(output.starts_with("BlockQuote 1:1-"), "{}", output);
+    assert!(output.contains("\n  BulletList 1:3-"), "{}", output);
+    assert!(
+        output.contains("\n        Str \"one\" 1:5-1:8\n"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("\n        Str \"two\" 2:5-2:8\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn test_empty_range_is_marked() {
+    assert_eq!(format_range(&empty_range()), "(no range)");
+}
@@ -0,0 +1,35 @@
+use quarto_markdown_pandoc::utils::raw_formats::collect_raw_formats;
+use std::collections::BTreeSet;
+
+mod common;
+use common::read;
+
+fn formats(names: &[&str]) -> BTreeSet<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn test_collect_raw_formats() {
+    let doc = read(concat!(
+        "A `<b>`{=html} word.\n",
+        "\n",
+        "```{=latex}\n",
+        "\\newpage\n",
+        "```\n",
+        "\n",
+        "::: {.note}\n",
+        "- `\\emph{x}`{=latex} and `<i>`{=html}\n",
+        "- `x`{=typst}\n",
+        ":::\n",
+    ));
+    assert_eq!(
+        collect_raw_formats(&doc),
+        formats(&["html", "latex", "typst"])
+    );
+}
+
+#[test]
+fn test_collect_raw_formats_without_raw_content() {
+    let doc = read("Just *text*.\n");
+    assert!(collect_raw_formats(&doc).is_empty());
+}
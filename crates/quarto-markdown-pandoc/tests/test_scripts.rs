@@ -0,0 +1,80 @@
+use quarto_markdown_pandoc::readers;
+use quarto_markdown_pandoc::writers;
+
+fn native(input: &str) -> String {
+    let doc = readers::qmd::read(input.as_bytes(), &mut std::io::sink()).unwrap();
+    let mut buf = Vec::new();
+    writers::native::write(&doc, &mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+fn markdown_roundtrip(input: &str) -> String {
+    let doc = readers::qmd::read(input.as_bytes(), &mut std::io::sink()).unwrap();
+    let mut buf = Vec::new();
+    writers::markdown::write(&doc, &mut buf).unwrap();
+    native(&String::from_utf8(buf).unwrap())
+}
+
+#[test]
+fn test_superscript() {
+    assert_eq!(
+        native("a^b^\n"),
+        "[ Para [Str \"a\", Superscript [Str \"b\"]] ]"
+    );
+}
+
+#[test]
+fn test_superscript_with_space_is_literal() {
+    assert_eq!(
+        native("a^b c^\n"),
+        "[ Para [Str \"a^b\", Space, Str \"c^\"] ]"
+    );
+    assert_eq!(
+        native("^ hello^\n"),
+        "[ Para [Str \"^\", Space, Str \"hello^\"] ]"
+    );
+}
+
+#[test]
+fn test_superscript_with_escaped_space() {
+    assert_eq!(
+        native("a^b\\ c^\n"),
+        "[ Para [Str \"a\", Superscript [Str \"b\u{a0}c\"]] ]"
+    );
+    // an escaped backslash followed by a space is still a space
+    assert_eq!(
+        native("a^b\\\\ c^\n"),
+        "[ Para [Str \"a^b\\\\\", Space, Str \"c^\"] ]"
+    );
+}
+
+#[test]
+fn test_subscript() {
+    assert_eq!(
+        native("H~2~O\n"),
+        "[ Para [Str \"H\", Subscript [Str \"2\"], Str \"O\"] ]"
+    );
+    assert_eq!(
+        native("a~b c~\n"),
+        "[ Para [Str \"a~b\", Space, Str \"c~\"] ]"
+    );
+    assert_eq!(
+        native("a~b\\ c~\n"),
+        "[ Para [Str \"a\", Subscript [Str \"b\u{a0}c\"]] ]"
+    );
+}
+
+#[test]
+fn test_nonbreaking_space_is_text() {
+    assert_eq!(
+        native("x\u{a0}y z\n"),
+        "[ Para [Str \"x\u{a0}y\", Space, Str \"z\"] ]"
+    );
+}
+
+#[test]
+fn test_scripts_roundtrip_through_markdown() {
+    for input in ["a^b^\n", "a^b c^\n", "a^b\\ c^\n", "a~b c~\n", "a~b\\ c~\n"] {
+        assert_eq!(markdown_roundtrip(input), native(input), "{:?}", input);
+    }
+}
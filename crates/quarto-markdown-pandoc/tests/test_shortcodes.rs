@@ -0,0 +1,240 @@
+use indexmap::IndexMap;
+use quarto_markdown_pandoc::pandoc::location::empty_range;
+use quarto_markdown_pandoc::pandoc::shortcode::{ShortcodeArg, is_valid_shortcode_name};
+use quarto_markdown_pandoc::pandoc::{Block, Inline, Meta, Pandoc, Paragraph, Shortcode, Span};
+use quarto_markdown_pandoc::readers;
+use quarto_markdown_pandoc::readers::qmd::MarkdownParserOptions;
+use quarto_markdown_pandoc::utils::diagnostics::{Diagnostic, Severity};
+
+mod common;
+use common::{read, write_markdown};
+
+fn first_span(doc: &Pandoc) -> &Span {
+    let Some(Block::Paragraph(para)) = doc.blocks.first() else {
+        panic!("Expected a Paragraph first, got {}", doc);
+    };
+    match para.content.first() {
+        Some(Inline::Span(span)) => span,
+        _ => panic!("Expected a Span first, got {}", doc),
+    }
+}
+
+// the (key, value) of each parameter of a desugared shortcode span
+fn shortcode_params(span: &Span) -> Vec<(Option<String>, String)> {
+    span.content
+        .iter()
+        .map(|param| {
+            let Inline::Span(param) = param else {
+                panic!("Expected a Span parameter, got {:?}", param);
+            };
+            (
+                param.attr.2.get("data-key").cloned(),
+                param.attr.2["data-value"].clone(),
+            )
+        })
+        .collect()
+}
+
+fn keyword_arg(key: &str, value: ShortcodeArg) -> ShortcodeArg {
+    let mut spec = IndexMap::new();
+    spec.insert(key.to_string(), value);
+    ShortcodeArg::KeyValue(spec)
+}
+
+#[test]
+fn test_keyword_args_keep_source_order() {
+    let doc = read("{{< video url zebra=1 apple=\"two\" mango=true >}}\n");
+    assert_eq!(
+        shortcode_params(first_span(&doc)),
+        vec![
+            (None, "video".to_string()),
+            (None, "url".to_string()),
+            (Some("zebra".to_string()), "1".to_string()),
+            (Some("apple".to_string()), "two".to_string()),
+            (Some("mango".to_string()), "true".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_keyword_args_roundtrip_in_order() {
+    let input = "{{< video url zebra=1 apple=two mango=true >}}\n";
+    assert_eq!(write_markdown(&read(input)), input);
+}
+
+#[test]
+fn test_interleaved_args_are_written_in_order() {
+    let shortcode = Shortcode {
+        is_escaped: false,
+        name: "x".to_string(),
+        args: vec![
+            ShortcodeArg::String("a".to_string()),
+            keyword_arg("b", ShortcodeArg::Number(1.0)),
+            ShortcodeArg::String("c".to_string()),
+            keyword_arg("d", ShortcodeArg::Number(2.0)),
+        ],
+    };
+    assert_eq!(
+        shortcode
+            .positional_args()
+            .map(|arg| format!("{:?}", arg))
+            .collect::<Vec<_>>(),
+        vec!["String(\"a\")", "String(\"c\")"]
+    );
+    assert_eq!(
+        shortcode
+            .keyword_args()
+            .map(|(key, _)| key.as_str())
+            .collect::<Vec<_>>(),
+        vec!["b", "d"]
+    );
+
+    let doc = Pandoc {
+        meta: Meta::default(),
+        blocks: vec![Block::Paragraph(Paragraph {
+            content: vec![Inline::Shortcode(shortcode)],
+            filename: None,
+            range: empty_range(),
+        })],
+    };
+    assert_eq!(write_markdown(&doc), "{{< x a b=1 c d=2 >}}\n");
+}
+
+// reads `input`, returning the plain text of the first paragraph and the
+// reader's warnings
+fn read_invalid(input: &str) -> (String, Vec<Diagnostic>) {
+    let options = MarkdownParserOptions::default();
+    let (doc, warnings) =
+        readers::qmd::read_with_options(input.as_bytes(), &options, &mut std::io::sink()).unwrap();
+    let Some(Block::Paragraph(para)) = doc.blocks.first() else {
+        panic!("Expected a Paragraph first, got {}", doc);
+    };
+    let text = para
+        .content
+        .iter()
+        .map(|inline| match inline {
+            Inline::Str(s) => s.text.as_str(),
+            Inline::Space(_) => " ",
+            _ => panic!("Expected only text, got {}", doc),
+        })
+        .collect();
+    (text, warnings)
+}
+
+fn messages(warnings: &[Diagnostic]) -> Vec<String> {
+    warnings.iter().map(Diagnostic::message).collect()
+}
+
+#[test]
+fn test_numeric_shortcode_name_is_kept_as_text() {
+    let (text, warnings) = read_invalid("{{< 123 >}}\n");
+    assert_eq!(text, "{{< 123 >}}");
+    assert_eq!(
+        messages(&warnings),
+        vec!["Warning: Invalid shortcode at 1:1".to_string()]
+    );
+    assert_eq!(warnings[0].severity, Severity::Warning);
+
+    let (text, warnings) = read_invalid("{{< 1abc x >}}\n");
+    assert_eq!(text, "{{< 1abc x >}}");
+    assert_eq!(warnings.len(), 1);
+}
+
+fn labels(warnings: &[Diagnostic]) -> Vec<&str> {
+    warnings
+        .iter()
+        .map(|warning| warning.primary.as_ref().unwrap().message.as_str())
+        .collect()
+}
+
+#[test]
+fn test_empty_shortcode_name_is_kept_as_text() {
+    let (text, warnings) = read_invalid("{{< >}}\n");
+    assert_eq!(text, "{{< >}}");
+    assert_eq!(
+        messages(&warnings),
+        vec!["Warning: Invalid shortcode at 1:1".to_string()]
+    );
+    assert_eq!(labels(&warnings), vec!["This shortcode has no name."]);
+}
+
+#[test]
+fn test_invalid_shortcodes_after_text_are_kept_as_text() {
+    let (text, warnings) = read_invalid("a {{< 123 >}} b\n");
+    assert_eq!(text, "a {{< 123 >}} b");
+    assert_eq!(
+        messages(&warnings),
+        vec!["Warning: Invalid shortcode at 1:3".to_string()]
+    );
+    assert!(labels(&warnings)[0].starts_with("Shortcode names must start with"));
+
+    let (text, warnings) = read_invalid("x {{< >}}\n");
+    assert_eq!(text, "x {{< >}}");
+    assert_eq!(labels(&warnings), vec!["This shortcode has no name."]);
+
+    let (text, warnings) = read_invalid("a {{< x-y.z >}} b\n");
+    assert_eq!(text, "a {{< x-y.z >}} b");
+    assert_eq!(
+        messages(&warnings),
+        vec!["Warning: Invalid shortcode at 1:3".to_string()]
+    );
+}
+
+#[test]
+fn test_invalid_shortcodes_inside_markup_are_kept_as_text() {
+    assert_eq!(
+        read("*a {{< 1 >}}* b\n").to_string(),
+        "[ Para [Emph [Str \"a\", Space, Str \"{{<\", Space, Str \"1\", Space, Str \">}}\"], Space, Str \"b\"] ]"
+    );
+    assert_eq!(
+        read("> a\n> b {{< >}} c\n").to_string(),
+        "[ BlockQuote [Para [Str \"a\", SoftBreak, Str \"b\", Space, Str \"{{<\", Space, Str \">}}\", Space, Str \"c\"]] ]"
+    );
+    // a valid shortcode right after an unclosed invalid one is still read
+    let doc = read("a {{< 1 {{< v >}}\n");
+    let Some(Block::Paragraph(para)) = doc.blocks.first() else {
+        panic!("Expected a Paragraph first, got {}", doc);
+    };
+    assert!(
+        matches!(para.content.last(), Some(Inline::Span(_))),
+        "{}",
+        doc
+    );
+}
+
+#[test]
+fn test_text_after_an_invalid_shortcode_is_read_as_markdown() {
+    assert_eq!(
+        read("{{< 1 >}} b *c*\n").to_string(),
+        "[ Para [Str \"{{<\", Space, Str \"1\", Space, Str \">}}\", Space, Str \"b\", Space, Emph [Str \"c\"]] ]"
+    );
+    // without a closing `>}}`, only the delimiter is part of the shortcode
+    assert_eq!(
+        read("{{< 1 x *y* z\n").to_string(),
+        "[ Para [Str \"{{<\", Space, Str \"1\", Space, Str \"x\", Space, Emph [Str \"y\"], Space, Str \"z\"] ]"
+    );
+    // block continuations aren't part of the text
+    assert_eq!(
+        read("> {{< >}} b\n> *c*\n").to_string(),
+        "[ BlockQuote [Para [Str \"{{<\", Space, Str \">}}\", Space, Str \"b\", SoftBreak, Emph [Str \"c\"]]] ]"
+    );
+}
+
+#[test]
+fn test_invalid_shortcode_warnings_reach_the_output_stream() {
+    let mut output = Vec::new();
+    readers::qmd::read(b"{{< 123 >}}\n", &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.ends_with("Warning: Invalid shortcode at 1:1\n"));
+}
+
+#[test]
+fn test_shortcode_name_validation() {
+    assert!(is_valid_shortcode_name("meta"));
+    assert!(is_valid_shortcode_name("_private"));
+    assert!(is_valid_shortcode_name("lipsum-2"));
+    assert!(!is_valid_shortcode_name(""));
+    assert!(!is_valid_shortcode_name("123"));
+    assert!(!is_valid_shortcode_name("-a"));
+    assert!(!is_valid_shortcode_name("a.b"));
+}
@@ -0,0 +1,90 @@
+use quarto_markdown_pandoc::pandoc::location::{Location, Range};
+use quarto_markdown_pandoc::pandoc::{Block, Inline};
+use quarto_markdown_pandoc::readers;
+use quarto_markdown_pandoc::utils::source::Source;
+
+const INPUT: &str = "# Title\n\nFirst line of\na *paragraph*.\n\nLast.\n";
+
+fn range(source: &Source, start: usize, end: usize) -> Range {
+    Range {
+        start: source.location(start),
+        end: source.location(end),
+    }
+}
+
+#[test]
+fn test_lines() {
+    let source = Source::new(INPUT);
+    assert_eq!(source.line_count(), 6);
+    assert_eq!(source.line(0), Some("# Title"));
+    assert_eq!(source.line(1), Some(""));
+    assert_eq!(source.line(3), Some("a *paragraph*."));
+    assert_eq!(source.line(5), Some("Last."));
+    assert_eq!(source.line(6), None);
+    assert_eq!(Source::new("a\r\nb").line(0), Some("a"));
+    assert_eq!(Source::new("a\r\nb").line_count(), 2);
+}
+
+#[test]
+fn test_location_and_offset() {
+    let source = Source::new(INPUT);
+    let offset = INPUT.find("paragraph").unwrap();
+    assert_eq!(
+        source.location(offset),
+        Location {
+            offset,
+            row: 3,
+            column: 3,
+        }
+    );
+    assert_eq!(source.offset(3, 3), Some(offset));
+    assert_eq!(source.offset(9, 0), None);
+    assert_eq!(source.location(1000).offset, INPUT.len());
+}
+
+#[test]
+fn test_multiline_snippet() {
+    let source = Source::new(INPUT);
+    let start = INPUT.find("line of").unwrap();
+    let end = INPUT.find("paragraph").unwrap() + "paragraph".len();
+    let range = range(&source, start, end);
+    assert_eq!(source.snippet(&range), "line of\na *paragraph");
+    assert_eq!(source.lines(&range), "First line of\na *paragraph*.");
+}
+
+#[test]
+fn test_snippet_of_parsed_nodes() {
+    let doc = readers::qmd::read(INPUT.as_bytes(), &mut std::io::sink()).unwrap();
+    let source = Source::new(INPUT);
+    let Some(Block::Paragraph(para)) = doc.blocks.get(1) else {
+        panic!("Expected a Paragraph second, got {}", doc);
+    };
+    // the paragraph's range ends after its last newline
+    assert_eq!(
+        source.snippet(&para.range),
+        "First line of\na *paragraph*.\n"
+    );
+    assert_eq!(source.lines(&para.range), "First line of\na *paragraph*.");
+    let Some(Inline::Str(first)) = para.content.first() else {
+        panic!("Expected a Str first");
+    };
+    assert_eq!(source.snippet(&first.range), "First");
+}
+
+#[test]
+fn test_range_outside_the_source() {
+    let source = Source::new("short\n");
+    let range = Range {
+        start: Location {
+            offset: 3,
+            row: 0,
+            column: 3,
+        },
+        end: Location {
+            offset: 100,
+            row: 4,
+            column: 0,
+        },
+    };
+    assert_eq!(source.snippet(&range), "");
+}
@@ -0,0 +1,48 @@
+use quarto_markdown_pandoc::pandoc::location::{Location, Range, merge_ranges};
+use quarto_markdown_pandoc::pandoc::{Block, Inline};
+
+mod common;
+use common::read;
+
+fn location(offset: usize, row: usize, column: usize) -> Location {
+    Location {
+        offset,
+        row,
+        column,
+    }
+}
+
+#[test]
+fn test_merged_str_covers_all_pieces() {
+    // `a`, the decoded entity and `b` are three separate Strs before merging
+    let doc = read("a&#64;b\n");
+    let Some(Block::Paragraph(para)) = doc.blocks.first() else {
+        panic!("Expected a Paragraph first, got {}", doc);
+    };
+    let [Inline::Str(s)] = para.content.as_slice() else {
+        panic!("Expected a single Str, got {}", doc);
+    };
+    assert_eq!(s.text, "a@b");
+    assert_eq!(
+        s.range,
+        Range {
+            start: location(0, 0, 0),
+            end: location(7, 0, 7),
+        }
+    );
+}
+
+#[test]
+fn test_merge_ranges() {
+    let first = Range {
+        start: location(0, 0, 0),
+        end: location(1, 0, 1),
+    };
+    let third = Range {
+        start: location(6, 0, 6),
+        end: location(7, 0, 7),
+    };
+    let merged = merge_ranges(&first, &third);
+    assert_eq!(merged.start, first.start);
+    assert_eq!(merged.end, third.end);
+}
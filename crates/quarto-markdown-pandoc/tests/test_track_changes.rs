@@ -0,0 +1,138 @@
+use quarto_markdown_pandoc::transforms::track_changes::{TrackChanges, apply_track_changes};
+
+mod common;
+use common::read;
+
+// The document after resolving the changes in `input`, written as native
+// so that it can be compared with the document read from `expected`.
+fn assert_resolves_to(input: &str, mode: TrackChanges, expected: &str) {
+    let actual = apply_track_changes(read(input), mode);
+    assert_eq!(
+        actual.to_string(),
+        read(expected).to_string(),
+        "{:?} of {:?}",
+        mode,
+        input
+    );
+}
+
+#[test]
+fn test_insertion() {
+    let input = "a [new]{.insertion} b\n";
+    assert_resolves_to(input, TrackChanges::Accept, "a new b\n");
+    assert_resolves_to(input, TrackChanges::Reject, "a  b\n");
+}
+
+#[test]
+fn test_deletion() {
+    let input = "a [old]{.deletion} b\n";
+    assert_resolves_to(input, TrackChanges::Accept, "a  b\n");
+    assert_resolves_to(input, TrackChanges::Reject, "a old b\n");
+}
+
+#[test]
+fn test_substitution() {
+    let input = "a [old]{.deletion}[new]{.insertion} b\n";
+    assert_resolves_to(input, TrackChanges::Accept, "a new b\n");
+    assert_resolves_to(input, TrackChanges::Reject, "a old b\n");
+}
+
+#[test]
+fn test_comment() {
+    let input = "a[a *comment*]{.comment-start} b[]{.comment-end}\n";
+    assert_resolves_to(input, TrackChanges::Accept, "a b\n");
+    assert_resolves_to(input, TrackChanges::Reject, "a b\n");
+}
+
+#[test]
+fn test_highlight_is_kept() {
+    let input = "a [marked]{.mark} b\n";
+    assert_resolves_to(input, TrackChanges::Accept, input);
+    assert_resolves_to(input, TrackChanges::Reject, input);
+}
+
+#[test]
+fn test_changes_inside_other_inlines() {
+    let input = "*a [new]{.insertion}[old]{.deletion} b*\n";
+    assert_resolves_to(input, TrackChanges::Accept, "*a new b*\n");
+    assert_resolves_to(input, TrackChanges::Reject, "*a old b*\n");
+}
+
+#[test]
+fn test_all_keeps_changes() {
+    let input = "a [new]{.insertion}[old]{.deletion} [c]{.comment-start} b\n";
+    assert_resolves_to(input, TrackChanges::All, input);
+}
+
+// CriticMarkup is read into the same spans
+fn assert_reads_as(input: &str, expected: &str) {
+    assert_eq!(read(input).to_string(), read(expected).to_string());
+}
+
+#[test]
+fn test_critic_markup_insertion() {
+    let input = "a {++new *text*++} b\n";
+    assert_reads_as(input, "a [new *text*]{.insertion} b\n");
+    assert_resolves_to(input, TrackChanges::Accept, "a new *text* b\n");
+    assert_resolves_to(input, TrackChanges::Reject, "a  b\n");
+}
+
+#[test]
+fn test_critic_markup_deletion() {
+    let input = "a {--old--} b\n";
+    assert_reads_as(input, "a [old]{.deletion} b\n");
+    assert_resolves_to(input, TrackChanges::Accept, "a  b\n");
+    assert_resolves_to(input, TrackChanges::Reject, "a old b\n");
+}
+
+#[test]
+fn test_critic_markup_substitution() {
+    let input = "a {~~old~>new~~} b\n";
+    assert_reads_as(input, "a [old]{.deletion}[new]{.insertion} b\n");
+    assert_resolves_to(input, TrackChanges::Accept, "a new b\n");
+    assert_resolves_to(input, TrackChanges::Reject, "a old b\n");
+}
+
+#[test]
+fn test_critic_markup_comment() {
+    let input = "a{>>a *comment*<<} b\n";
+    assert_reads_as(input, "a[a *comment*]{.comment-start} b\n");
+    assert_resolves_to(input, TrackChanges::Accept, "a b\n");
+    assert_resolves_to(input, TrackChanges::Reject, "a b\n");
+}
+
+#[test]
+fn test_critic_markup_highlight() {
+    let input = "a {==marked==} b\n";
+    assert_reads_as(input, "a [marked]{.mark} b\n");
+    assert_resolves_to(input, TrackChanges::Accept, input);
+    assert_resolves_to(input, TrackChanges::Reject, input);
+
+    // a comment right after a highlight is about the highlighted text
+    let input = "a {==marked==}{>>note<<} b\n";
+    assert_reads_as(
+        input,
+        "a [note]{.comment-start}[marked]{.mark}[]{.comment-end} b\n",
+    );
+    assert_resolves_to(input, TrackChanges::Accept, "a [marked]{.mark} b\n");
+    assert_resolves_to(input, TrackChanges::Reject, "a [marked]{.mark} b\n");
+}
+
+#[test]
+fn test_critic_markup_inside_other_inlines() {
+    let input = "*a {++new++} b* and [link {--old--}](u)\n";
+    assert_resolves_to(input, TrackChanges::Accept, "*a new b* and [link ](u)\n");
+    assert_resolves_to(input, TrackChanges::Reject, "*a  b* and [link old](u)\n");
+}
+
+#[test]
+fn test_critic_markup_across_lines() {
+    let input = "> a {++new\n> text++} b\n";
+    assert_resolves_to(input, TrackChanges::Accept, "> a new\n> text b\n");
+}
+
+#[test]
+fn test_unmatched_critic_markup_is_text() {
+    // the delimiters have to be in the same inline
+    assert_reads_as("a {++b *c++} d*\n", "a \\{++b *c++\\} d*\n");
+}
@@ -0,0 +1,39 @@
+use quarto_markdown_pandoc::filters::{Filter, FilterReturn::Unchanged, topdown_traverse};
+use quarto_markdown_pandoc::pandoc::{Inline, Str};
+use quarto_markdown_pandoc::visitors::{Visitor, visit};
+
+mod common;
+use common::read;
+
+#[test]
+fn test_visitor_sees_nodes_in_filter_order() {
+    let doc = read(concat!(
+        "---\ntitle: The *title*\n---\n\n",
+        "# One\n\n",
+        "Text with a note.^[Note *text*.]\n\n",
+        "- item [span]{.x}\n- other\n\n",
+        "| a | b |\n|---|---|\n| c | d |\n\n",
+        "![Caption](x.png)\n",
+    ));
+
+    let mut filtered: Vec<String> = Vec::new();
+    let mut filter = Filter::new().with_str(|s: Str| {
+        filtered.push(s.text.clone());
+        Unchanged(s)
+    });
+    topdown_traverse(doc.clone(), &mut filter);
+    drop(filter);
+
+    let mut visited: Vec<String> = Vec::new();
+    let mut visitor = Visitor::new().with_inline(|inline| {
+        if let Inline::Str(s) = inline {
+            visited.push(s.text.clone());
+        }
+    });
+    visit(&doc, &mut visitor);
+    drop(visitor);
+
+    assert!(visited.contains(&"title".to_string()));
+    assert!(visited.contains(&"Caption".to_string()));
+    assert_eq!(visited, filtered);
+}
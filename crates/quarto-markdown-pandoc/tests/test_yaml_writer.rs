@@ -0,0 +1,53 @@
+use quarto_markdown_pandoc::pandoc::Pandoc;
+use quarto_markdown_pandoc::writers;
+
+mod common;
+use common::read;
+
+fn write_meta(doc: &Pandoc) -> String {
+    let mut buf = Vec::new();
+    writers::yaml::write_meta(&doc.meta, &mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn test_front_matter_roundtrips_through_yaml() {
+    let front_matter = concat!(
+        "title: \"*Hello* world\"\n",
+        "author:\n",
+        "  - name: A\n",
+        "    affiliation: B\n",
+        "  - C\n",
+        "  - - x\n",
+        "    - y\n",
+        "draft: true\n",
+        "abstract: |\n",
+        "  One.\n",
+        "\n",
+        "  Two.\n",
+        "version: 1.0\n",
+        "keywords: []\n",
+        "answer: \"yes\"\n",
+        "code: \"`a: b`\"\n",
+    );
+    let doc = read(&format!("---\n{}---\n\nx\n", front_matter));
+    let yaml = write_meta(&doc);
+    assert_eq!(yaml, front_matter);
+
+    let reread = read(&format!("---\n{}---\n\nx\n", yaml));
+    assert_eq!(write_meta(&reread), yaml);
+}
+
+#[test]
+fn test_map_keys_keep_source_order() {
+    let doc = read("---\nzeta: 1\nalpha: 2\nmid:\n  b: 1\n  a: 2\n---\n\nx\n");
+    assert_eq!(
+        write_meta(&doc),
+        "zeta: 1\nalpha: 2\nmid:\n  b: 1\n  a: 2\n"
+    );
+}
+
+#[test]
+fn test_empty_meta() {
+    assert_eq!(write_meta(&read("x\n")), "{}\n");
+}
@@ -19,6 +19,7 @@ use tree_sitter_language::LanguageFn;
 unsafe extern "C" {
     fn tree_sitter_markdown() -> *const ();
     fn tree_sitter_markdown_inline() -> *const ();
+    fn tree_sitter_markdown_inline_no_smart() -> *const ();
 }
 
 /// The tree-sitter [`LanguageFn`][LanguageFn] for the block grammar.
@@ -28,6 +29,19 @@ pub const LANGUAGE: LanguageFn = unsafe { LanguageFn::from_raw(tree_sitter_markd
 pub const INLINE_LANGUAGE: LanguageFn =
     unsafe { LanguageFn::from_raw(tree_sitter_markdown_inline) };
 
+/// The tree-sitter [`LanguageFn`][LanguageFn] for the inline grammar, with quotes read as
+/// text instead of quoted spans.
+pub const INLINE_LANGUAGE_NO_SMART: LanguageFn =
+    unsafe { LanguageFn::from_raw(inline_language_no_smart) };
+
+// the C side builds this language on its first call, so calls go through a
+// lock
+unsafe extern "C" fn inline_language_no_smart() -> *const () {
+    static LANGUAGE: std::sync::OnceLock<usize> = std::sync::OnceLock::new();
+    *LANGUAGE.get_or_init(|| unsafe { tree_sitter_markdown_inline_no_smart() } as usize)
+        as *const ()
+}
+
 /// The syntax highlighting queries for the block grammar.
 pub const HIGHLIGHT_QUERY_BLOCK: &str =
     include_str!("../../tree-sitter-markdown/queries/highlights.scm");
@@ -78,4 +92,67 @@ mod tests {
             .set_language(&INLINE_LANGUAGE.into())
             .expect("Error loading Markdown inline grammar");
     }
+
+    #[test]
+    fn can_load_inline_grammar_without_smart_quotes() {
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&INLINE_LANGUAGE_NO_SMART.into())
+            .expect("Error loading Markdown inline grammar");
+        let tree = parser.parse("'a' \"b\"", None).unwrap();
+        assert!(!tree.root_node().to_sexp().contains("quote"));
+    }
+
+    #[test]
+    fn critic_markup_parses_without_errors() {
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&INLINE_LANGUAGE.into())
+            .expect("Error loading Markdown inline grammar");
+        let tree = parser
+            .parse("a {++b++} {--c--} {~~d~>e~~} {==f==}{>>g<<}", None)
+            .unwrap();
+        assert!(!tree.root_node().has_error());
+    }
+
+    #[test]
+    fn unnumbered_shorthand_is_a_class() {
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&INLINE_LANGUAGE.into())
+            .expect("Error loading Markdown inline grammar");
+        let tree = parser.parse("Title {#id - .cls}", None).unwrap();
+        assert!(!tree.root_node().has_error());
+        let attribute = tree.root_node().child(2).unwrap();
+        assert_eq!(attribute.kind(), "commonmark_attribute");
+        let mut cursor = attribute.walk();
+        let classes: Vec<_> = attribute
+            .children(&mut cursor)
+            .filter(|child| child.kind() == "class_specifier")
+            .map(|child| child.byte_range())
+            .collect();
+        assert_eq!(classes, vec![11..12, 13..17]);
+    }
+
+    #[test]
+    fn invalid_shortcodes_parse_without_errors() {
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&INLINE_LANGUAGE.into())
+            .expect("Error loading Markdown inline grammar");
+        for input in [
+            "a {{< 123 >}} b",
+            "x {{< >}}",
+            "{{< x-y.z >}}",
+            "*a {{< 1 >}}* b",
+        ] {
+            let tree = parser.parse(input, None).unwrap();
+            assert!(!tree.root_node().has_error(), "{}", input);
+            assert!(
+                !tree.root_node().to_sexp().contains("shortcode"),
+                "{}",
+                input
+            );
+        }
+    }
 }
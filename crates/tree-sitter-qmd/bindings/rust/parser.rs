@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::num::NonZeroU16;
 
-use crate::{INLINE_LANGUAGE, LANGUAGE};
+use crate::{INLINE_LANGUAGE, INLINE_LANGUAGE_NO_SMART, LANGUAGE};
 use tree_sitter::{InputEdit, Language, Node, Parser, Point, Range, Tree, TreeCursor};
 
 /// A parser that produces [`MarkdownTree`]s.
@@ -294,6 +294,15 @@ impl Default for MarkdownParser {
 }
 
 impl MarkdownParser {
+    /// A parser that reads quotes as text instead of quoted spans, using
+    /// [`INLINE_LANGUAGE_NO_SMART`] for inline content.
+    pub fn without_smart_quotes() -> Self {
+        MarkdownParser {
+            inline_language: INLINE_LANGUAGE_NO_SMART.into(),
+            ..Default::default()
+        }
+    }
+
     /// Parse a slice of UTF8 text.
     ///
     /// # Arguments:
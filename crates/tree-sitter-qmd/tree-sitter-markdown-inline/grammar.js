@@ -125,6 +125,10 @@ module.exports = grammar(add_inline_rules({
 
         ...common.rules,
 
+        // as in Pandoc, `-` on its own is short for `.unnumbered` in heading
+        // attributes (`# Title {-}`), which are parsed here
+        class_specifier: $ => /[.][a-zA-Z0-9_\-][a-zA-Z0-9_\-.]*|-/,
+
         // A lot of inlines are defined in `add_inline_rules`, including:
         //
         // * collections of inlines
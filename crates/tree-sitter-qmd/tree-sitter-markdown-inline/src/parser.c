@@ -3811,6 +3811,7 @@ static bool ts_lex(TSLexer *lexer, TSStateId state) {
       END_STATE();
     case 10:
       if (lookahead == '#') ADVANCE(2172);
+      if (lookahead == '-') ADVANCE(4322);
       if (lookahead == '.') ADVANCE(2171);
       if (lookahead == '}') ADVANCE(2221);
       if (lookahead == '\t' ||
@@ -25829,6 +25830,9 @@ static bool ts_lex(TSLexer *lexer, TSStateId state) {
     case 4321:
       ACCEPT_TOKEN(anon_sym_DOT_DOT_DOT);
       END_STATE();
+    case 4322:
+      ACCEPT_TOKEN(sym_class_specifier);
+      END_STATE();
     default:
       return false;
   }
@@ -103,6 +103,14 @@ typedef struct {
     uint8_t inside_strikeout;
     uint8_t inside_single_quote;
     uint8_t inside_double_quote;
+
+    // The character doubled in the closing delimiter of the open CriticMarkup
+    // span (`+` for `{++`, `<` for `{>>`, ...), or 0 outside of one
+    uint8_t inside_critic_markup;
+
+    // Whether quotes open and close `Quoted` spans. This is fixed when the
+    // scanner is created, so it isn't part of the serialized state.
+    bool smart_quotes;
 } Scanner;
 
 // Write the whole state of a Scanner to a byte buffer
@@ -118,6 +126,7 @@ static unsigned serialize(Scanner *s, char *buffer) {
     buffer[size++] = (char)s->inside_strikeout;
     buffer[size++] = (char)s->inside_single_quote;
     buffer[size++] = (char)s->inside_double_quote;
+    buffer[size++] = (char)s->inside_critic_markup;
     return size;
 }
 
@@ -134,6 +143,7 @@ static void deserialize(Scanner *s, const char *buffer, unsigned length) {
     s->inside_strikeout = 0;
     s->inside_single_quote = 0;
     s->inside_double_quote = 0;
+    s->inside_critic_markup = 0;
     if (length > 0) {
         size_t size = 0;
         s->state = (uint8_t)buffer[size++];
@@ -146,9 +156,30 @@ static void deserialize(Scanner *s, const char *buffer, unsigned length) {
         s->inside_strikeout = (uint8_t)buffer[size++];
         s->inside_single_quote = (uint8_t)buffer[size++];
         s->inside_double_quote = (uint8_t)buffer[size++];
+        s->inside_critic_markup = (uint8_t)buffer[size++];
     }
 }
 
+// Scans ahead for a run of exactly `level` delimiters that could close a
+// leaf span (a code span or math).
+static bool is_leaf_delimiter_closed_ahead(TSLexer *lexer, const char delimiter,
+                                           uint8_t level) {
+    size_t close_level = 0;
+    while (!lexer->eof(lexer)) {
+        if (lexer->lookahead == delimiter) {
+            close_level++;
+        } else {
+            if (close_level == level) {
+                // Found a matching delimiter
+                break;
+            }
+            close_level = 0;
+        }
+        lexer->advance(lexer, false);
+    }
+    return close_level == level;
+}
+
 static bool parse_leaf_delimiter(TSLexer *lexer, uint8_t *delimiter_length,
                                  const bool *valid_symbols,
                                  const char delimiter,
@@ -167,20 +198,7 @@ static bool parse_leaf_delimiter(TSLexer *lexer, uint8_t *delimiter_length,
     }
     if (valid_symbols[open_token]) {
         // Parse ahead to check if there is a closing delimiter
-        size_t close_level = 0;
-        while (!lexer->eof(lexer)) {
-            if (lexer->lookahead == delimiter) {
-                close_level++;
-            } else {
-                if (close_level == level) {
-                    // Found a matching delimiter
-                    break;
-                }
-                close_level = 0;
-            }
-            lexer->advance(lexer, false);
-        }
-        if (close_level == level) {
+        if (is_leaf_delimiter_closed_ahead(lexer, delimiter, level)) {
             *delimiter_length = level;
             lexer->result_symbol = open_token;
             return true;
@@ -200,30 +218,151 @@ static bool parse_backtick(Scanner *s, TSLexer *lexer,
                                 CODE_SPAN_CLOSE);
 }
 
+// Scans ahead from an opening `$` to the next `$` and checks that it can
+// close inline math: it must follow a non-whitespace character and must not
+// be followed by a digit.
+static bool is_inline_math_closed_ahead(TSLexer *lexer) {
+    bool last_whitespace = false;
+    while (!lexer->eof(lexer)) {
+        int32_t chr = lexer->lookahead;
+        lexer->advance(lexer, false);
+        if (chr == '\\') {
+            if (!lexer->eof(lexer)) {
+                lexer->advance(lexer, false);
+            }
+            last_whitespace = false;
+            continue;
+        }
+        if (chr != '$') {
+            last_whitespace = chr == ' ' || chr == '\t' || chr == '\n' || chr == '\r';
+            continue;
+        }
+        return lexer->lookahead != '$' && !last_whitespace &&
+               !(lexer->lookahead >= '0' && lexer->lookahead <= '9');
+    }
+    return false;
+}
+
+// Inline math follows Pandoc's `tex_math_dollars` rules: the opening `$`
+// must be followed by a non-whitespace character, and the closing `$` must
+// follow a non-whitespace character and not be followed by a digit, so that
+// prices like `$5 and $6` stay text. Display math (`$$`) has no such rules.
 static bool parse_dollar(Scanner *s, TSLexer *lexer,
                          const bool *valid_symbols) {
-    return parse_leaf_delimiter(lexer, &s->latex_span_delimiter_length,
-                                valid_symbols, '$', LATEX_SPAN_START,
-                                LATEX_SPAN_CLOSE);
+    uint8_t level = 0;
+    while (lexer->lookahead == '$') {
+        lexer->advance(lexer, false);
+        level++;
+    }
+    lexer->mark_end(lexer);
+    if (level == s->latex_span_delimiter_length &&
+        valid_symbols[LATEX_SPAN_CLOSE]) {
+        s->latex_span_delimiter_length = 0;
+        lexer->result_symbol = LATEX_SPAN_CLOSE;
+        return true;
+    }
+    if (!valid_symbols[LATEX_SPAN_START]) {
+        return false;
+    }
+    if (level == 1) {
+        // a `$` that doesn't open inline math is lexed as text
+        if (is_lookahead_whitespace(lexer) ||
+            !is_inline_math_closed_ahead(lexer)) {
+            return false;
+        }
+        s->latex_span_delimiter_length = level;
+        lexer->result_symbol = LATEX_SPAN_START;
+        return true;
+    }
+    if (is_leaf_delimiter_closed_ahead(lexer, '$', level)) {
+        s->latex_span_delimiter_length = level;
+        lexer->result_symbol = LATEX_SPAN_START;
+        return true;
+    }
+    if (valid_symbols[UNCLOSED_SPAN]) {
+        lexer->result_symbol = UNCLOSED_SPAN;
+        return true;
+    }
+    return false;
 }
 
-static bool parse_single_quote(Scanner *s, TSLexer *lexer, const bool *valid_symbols) {
-    lexer->advance(lexer, false);
-    if (s->inside_single_quote > 0) {
-        if (valid_symbols[SINGLE_QUOTE_CLOSE]) {
-            lexer->result_symbol = SINGLE_QUOTE_CLOSE;
-            s->inside_single_quote = 0;
-            return true;
+// Letters and digits, which make a quote next to them part of a word (the
+// apostrophe in `don't`) rather than a delimiter.
+static bool is_word_character(int32_t chr) {
+    return (chr >= 'a' && chr <= 'z') || (chr >= 'A' && chr <= 'Z') ||
+           (chr >= '0' && chr <= '9');
+}
+
+// Scans ahead from an opening single quote for a quote that could close it:
+// one after a non-whitespace character and not followed by a word
+// character. Without one, the opening quote is an apostrophe (as in `'tis`).
+//
+// Quotes inside code spans can't close. A backtick run only starts a code
+// span if a run of the same length follows, which we only know once we get
+// there, so a closer seen inside a span still counts if the span turns out
+// to be unclosed (its backticks are then text).
+static bool is_single_quote_closed_ahead(TSLexer *lexer) {
+    bool last_whitespace = false;
+    unsigned code_span_length = 0;
+    bool closed_in_code_span = false;
+    while (!lexer->eof(lexer)) {
+        int32_t chr = lexer->lookahead;
+        if (chr == '`') {
+            unsigned run = 0;
+            while (!lexer->eof(lexer) && lexer->lookahead == '`') {
+                lexer->advance(lexer, false);
+                run++;
+            }
+            if (code_span_length == 0) {
+                code_span_length = run;
+                closed_in_code_span = false;
+            } else if (run == code_span_length) {
+                code_span_length = 0;
+            }
+            last_whitespace = false;
+            continue;
         }
-        // HEY do we ever get here?
+        lexer->advance(lexer, false);
+        if (chr == '\\' && code_span_length == 0) {
+            if (!lexer->eof(lexer)) {
+                lexer->advance(lexer, false);
+            }
+            last_whitespace = false;
+            continue;
+        }
+        if (chr == '\'' && !last_whitespace && !is_word_character(lexer->lookahead)) {
+            if (code_span_length == 0) {
+                return true;
+            }
+            closed_in_code_span = true;
+        }
+        last_whitespace = chr == ' ' || chr == '\t' || chr == '\n' || chr == '\r';
     }
+    return code_span_length > 0 && closed_in_code_span;
+}
+
+// Single quotes follow flanking rules, like emphasis delimiters: a quote
+// opens after whitespace and before a non-whitespace character, and closes
+// after a non-whitespace character and before anything but a letter or
+// digit. Quotes that do neither are apostrophes, and are lexed as text.
+static bool parse_single_quote(Scanner *s, TSLexer *lexer, const bool *valid_symbols) {
+    lexer->advance(lexer, false);
     lexer->mark_end(lexer);
-    if (valid_symbols[SINGLE_QUOTE_CLOSE]) {
+    if (valid_symbols[SINGLE_QUOTE_CLOSE] &&
+        !valid_symbols[LAST_TOKEN_WHITESPACE] &&
+        !is_word_character(lexer->lookahead)) {
         s->inside_single_quote = 0;
         lexer->result_symbol = SINGLE_QUOTE_CLOSE;
         return true;
     }
-    if (valid_symbols[SINGLE_QUOTE_OPEN] && !is_lookahead_whitespace(lexer)) {
+    if (s->inside_single_quote > 0) {
+        return false;
+    }
+    if (valid_symbols[SINGLE_QUOTE_OPEN] &&
+        (valid_symbols[LAST_TOKEN_WHITESPACE] ||
+         valid_symbols[LAST_TOKEN_PUNCTUATION]) &&
+        !is_lookahead_whitespace(lexer) && lexer->lookahead != '\'' &&
+        is_single_quote_closed_ahead(lexer)) {
         s->inside_single_quote = 1;
         lexer->result_symbol = SINGLE_QUOTE_OPEN;
         return true;
@@ -282,9 +421,93 @@ static bool parse_caret(Scanner *s, TSLexer *lexer, const bool *valid_symbols) {
     return false;
 }
 
+// CriticMarkup (`{++insertion++}`, `{--deletion--}`, `{~~old~>new~~}`,
+// `{>>comment<<}` and `{==highlight==}`) has no rules in the grammar. Its
+// delimiters are emitted as `_unclosed_span` tokens, which the grammar reads
+// as text and leaves out of the tree, and the reader finds them in the gaps
+// between the inline nodes. The content in between is parsed as usual.
+
+// The character doubled in the closing delimiter for the one doubled in the
+// opening delimiter, or 0 if it doesn't open CriticMarkup.
+static uint8_t critic_markup_close_char(int32_t open) {
+    switch (open) {
+        case '+':
+        case '-':
+        case '~':
+        case '=':
+            return (uint8_t)open;
+        case '>':
+            return '<';
+    }
+    return 0;
+}
+
+// Scans ahead for the closing delimiter (`close` twice, then `}`).
+static bool is_critic_markup_closed_ahead(TSLexer *lexer, uint8_t close) {
+    uint8_t level = 0;
+    while (!lexer->eof(lexer)) {
+        int32_t chr = lexer->lookahead;
+        lexer->advance(lexer, false);
+        if (chr == '}' && level >= 2) {
+            return true;
+        }
+        level = chr == close ? level + 1 : 0;
+    }
+    return false;
+}
+
+// Parses an opening delimiter. The lexer is past the `{`.
+static bool parse_critic_markup_open(Scanner *s, TSLexer *lexer,
+                                     const bool *valid_symbols) {
+    int32_t open = lexer->lookahead;
+    uint8_t close = critic_markup_close_char(open);
+    if (close == 0 || s->inside_critic_markup || !valid_symbols[UNCLOSED_SPAN]) {
+        return false;
+    }
+    lexer->advance(lexer, false);
+    if (lexer->lookahead != open) {
+        return false;
+    }
+    lexer->advance(lexer, false);
+    lexer->mark_end(lexer);
+    if (!is_critic_markup_closed_ahead(lexer, close)) {
+        return false;
+    }
+    s->inside_critic_markup = close;
+    lexer->result_symbol = UNCLOSED_SPAN;
+    return true;
+}
+
+// Finishes a closing delimiter. The lexer is past the doubled character.
+static bool parse_critic_markup_close(Scanner *s, TSLexer *lexer,
+                                      const bool *valid_symbols) {
+    if (lexer->lookahead != '}' || !valid_symbols[UNCLOSED_SPAN]) {
+        return false;
+    }
+    lexer->advance(lexer, false);
+    lexer->mark_end(lexer);
+    s->inside_critic_markup = 0;
+    lexer->result_symbol = UNCLOSED_SPAN;
+    return true;
+}
+
+// Parses a closing delimiter made of a character no other token starts with.
+static bool parse_critic_markup_delimiter(Scanner *s, TSLexer *lexer,
+                                          const bool *valid_symbols) {
+    int32_t delimiter = lexer->lookahead;
+    if (s->inside_critic_markup != delimiter) {
+        return false;
+    }
+    lexer->advance(lexer, false);
+    if (lexer->lookahead != delimiter) {
+        return false;
+    }
+    lexer->advance(lexer, false);
+    return parse_critic_markup_close(s, lexer, valid_symbols);
+}
+
 static bool parse_strikeout(Scanner *s, TSLexer *lexer,
                              const bool *valid_symbols) {
-    lexer->advance(lexer, false);
     if (s->inside_strikeout > 0) {
         if (valid_symbols[STRIKEOUT_CLOSE]) {
             lexer->result_symbol = STRIKEOUT_CLOSE;
@@ -310,8 +533,20 @@ static bool parse_strikeout(Scanner *s, TSLexer *lexer,
 static bool parse_tilde(Scanner *s, TSLexer *lexer, const bool *valid_symbols) {
     lexer->advance(lexer, false);    
     if (lexer->lookahead == '~') {
+        lexer->advance(lexer, false);
+        if (s->inside_critic_markup == '~' && lexer->lookahead == '}') {
+            return parse_critic_markup_close(s, lexer, valid_symbols);
+        }
         return parse_strikeout(s, lexer, valid_symbols);
     }
+    // the `~>` between the old and the new text of a substitution
+    if (s->inside_critic_markup == '~' && lexer->lookahead == '>' &&
+        valid_symbols[UNCLOSED_SPAN]) {
+        lexer->advance(lexer, false);
+        lexer->mark_end(lexer);
+        lexer->result_symbol = UNCLOSED_SPAN;
+        return true;
+    }
     if (s->inside_subscript > 0) {
         if (valid_symbols[SUBSCRIPT_CLOSE]) {
             lexer->result_symbol = SUBSCRIPT_CLOSE;
@@ -470,9 +705,13 @@ static bool parse_cite_author_in_text(Scanner *_, TSLexer *lexer,
     return false;
 }
 
-static bool parse_cite_suppress_author(Scanner *_, TSLexer *lexer,
+static bool parse_cite_suppress_author(Scanner *s, TSLexer *lexer,
                                        const bool *valid_symbols) {
     lexer->advance(lexer, false);
+    if (s->inside_critic_markup == '-' && lexer->lookahead == '-') {
+        lexer->advance(lexer, false);
+        return parse_critic_markup_close(s, lexer, valid_symbols);
+    }
     if (lexer->lookahead == '@') {
         lexer->advance(lexer, false);
         if (lexer->lookahead == '{' && valid_symbols[CITE_SUPPRESS_AUTHOR_WITH_OPEN_BRACKET]) {
@@ -489,15 +728,72 @@ static bool parse_cite_suppress_author(Scanner *_, TSLexer *lexer,
     return false;
 }
 
+static bool is_shortcode_space(int32_t chr) { return chr == ' ' || chr == '\t'; }
+
+static bool is_shortcode_name_char(int32_t chr) {
+    return (chr >= 'a' && chr <= 'z') || (chr >= 'A' && chr <= 'Z') ||
+           (chr >= '0' && chr <= '9') || chr == '_' || chr == '-';
+}
+
+// Whether the whitespace and the name that start a shortcode come next.
+// Names are those of the grammar's `shortcode_name`.
+static bool is_shortcode_name_ahead(TSLexer *lexer) {
+    if (!is_shortcode_space(lexer->lookahead)) {
+        return false;
+    }
+    while (is_shortcode_space(lexer->lookahead)) {
+        lexer->advance(lexer, false);
+    }
+    int32_t chr = lexer->lookahead;
+    if (!is_shortcode_name_char(chr) || chr == '-' || (chr >= '0' && chr <= '9')) {
+        return false;
+    }
+    while (is_shortcode_name_char(lexer->lookahead)) {
+        lexer->advance(lexer, false);
+    }
+    return is_shortcode_space(lexer->lookahead);
+}
+
+// Skips past the `>}}` that closes a shortcode without a valid name, if
+// there is one before the next shortcode.
+static void skip_invalid_shortcode(TSLexer *lexer) {
+    int32_t before = 0;
+    int32_t last = 0;
+    while (!lexer->eof(lexer)) {
+        int32_t chr = lexer->lookahead;
+        if (before == '{' && last == '{' && chr == '<') {
+            return;
+        }
+        lexer->advance(lexer, false);
+        if (before == '>' && last == '}' && chr == '}') {
+            lexer->mark_end(lexer);
+            return;
+        }
+        before = last;
+        last = chr;
+    }
+}
+
 static bool parse_shortcode_open(Scanner *s, TSLexer *lexer,
                                  const bool *valid_symbols) {
     lexer->advance(lexer, false);
-    if (lexer->lookahead != '{') return false;
+    if (lexer->lookahead != '{') {
+        return parse_critic_markup_open(s, lexer, valid_symbols);
+    }
     lexer->advance(lexer, false);
     if (lexer->lookahead == '<' && valid_symbols[SHORTCODE_OPEN]) {
         lexer->advance(lexer, false);
-        lexer->result_symbol = SHORTCODE_OPEN;
         lexer->mark_end(lexer);
+        // A shortcode without a valid name (`{{< 123 >}}`, `{{< >}}`) is
+        // skipped as an unclosed span, which leaves it in a gap of the
+        // tree for the reader to report, instead of failing the parse.
+        if (!s->inside_shortcode && valid_symbols[UNCLOSED_SPAN] &&
+            !is_shortcode_name_ahead(lexer)) {
+            skip_invalid_shortcode(lexer);
+            lexer->result_symbol = UNCLOSED_SPAN;
+            return true;
+        }
+        lexer->result_symbol = SHORTCODE_OPEN;
         s->inside_shortcode++;
         return true;
     } else if (lexer->lookahead == '{') {
@@ -570,6 +866,10 @@ static bool scan(Scanner *s, TSLexer *lexer, const bool *valid_symbols) {
             return parse_underscore(s, lexer, valid_symbols);
         case '~':
             return parse_tilde(s, lexer, valid_symbols);
+        case '+':
+        case '=':
+        case '<':
+            return parse_critic_markup_delimiter(s, lexer, valid_symbols);
     }
 
     // we only parse single and double quotes if we are not inside a shortcode
@@ -580,10 +880,13 @@ static bool scan(Scanner *s, TSLexer *lexer, const bool *valid_symbols) {
     // double quotes.
     //
     // this shortcode immediate parsing happens at grammar.js
-    if (!s->inside_shortcode && (valid_symbols[LAST_TOKEN_WHITESPACE] || s->inside_single_quote) && lexer->lookahead == '\'') {
+    if (s->smart_quotes && !s->inside_shortcode &&
+        (valid_symbols[LAST_TOKEN_WHITESPACE] ||
+         valid_symbols[LAST_TOKEN_PUNCTUATION] || s->inside_single_quote) &&
+        lexer->lookahead == '\'') {
         return parse_single_quote(s, lexer, valid_symbols);
     }
-    if (!s->inside_shortcode && (valid_symbols[LAST_TOKEN_WHITESPACE] || s->inside_double_quote) && lexer->lookahead == '"') {
+    if (s->smart_quotes && !s->inside_shortcode && (valid_symbols[LAST_TOKEN_WHITESPACE] || s->inside_double_quote) && lexer->lookahead == '"') {
         return parse_double_quote(s, lexer, valid_symbols);
     }
     return false;
@@ -592,6 +895,13 @@ static bool scan(Scanner *s, TSLexer *lexer, const bool *valid_symbols) {
 void *tree_sitter_markdown_inline_external_scanner_create() {
     Scanner *s = (Scanner *)malloc(sizeof(Scanner));
     deserialize(s, NULL, 0);
+    s->smart_quotes = true;
+    return s;
+}
+
+static void *create_without_smart_quotes(void) {
+    Scanner *s = tree_sitter_markdown_inline_external_scanner_create();
+    s->smart_quotes = false;
     return s;
 }
 
@@ -618,3 +928,23 @@ void tree_sitter_markdown_inline_external_scanner_destroy(void *payload) {
     Scanner *scanner = (Scanner *)payload;
     free(scanner);
 }
+
+const TSLanguage *tree_sitter_markdown_inline(void);
+
+// The inline language with a scanner that never opens quote spans, so quotes
+// are read as text. Tree-sitter has no way to pass options to a scanner, so
+// this is a copy of the language that only differs in how the scanner is
+// created.
+//
+// This initializes a static copy on the first call and isn't thread-safe;
+// the Rust binding only calls it once.
+const TSLanguage *tree_sitter_markdown_inline_no_smart(void) {
+    static TSLanguage language;
+    static bool initialized = false;
+    if (!initialized) {
+        language = *tree_sitter_markdown_inline();
+        language.external_scanner.create = create_without_smart_quotes;
+        initialized = true;
+    }
+    return &language;
+}